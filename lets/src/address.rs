@@ -103,6 +103,24 @@ impl Address {
         self.appaddr
     }
 
+    /// Returns the [`Address`] of a frame of the message published at this address. The first frame
+    /// is published at the address of the message, and the following frames at addresses derived
+    /// from it.
+    ///
+    /// # Arguments
+    /// * `frame_num`: The number of the frame, starting at 1
+    pub fn frame(self, frame_num: u32) -> Address {
+        if frame_num <= 1 {
+            return self;
+        }
+        let mut s = Spongos::<KeccakF1600>::init();
+        s.absorb(self.appaddr);
+        s.absorb(self.msgid);
+        s.absorb(frame_num.to_be_bytes());
+        s.commit();
+        Address::new(self.appaddr, s.squeeze::<MsgId>())
+    }
+
     /// Hash the content of the [`Address`] using `Blake2b256`
     pub fn to_blake2b(self) -> [u8; 32] {
         let hasher = Blake2b256::new();
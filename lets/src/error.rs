@@ -94,6 +94,12 @@ pub enum Error {
     #[error("External error: {0:?}")]
     External(anyhow::Error),
 
+    #[error("Frame {0} of the message is invalid: {1}")]
+    Frame(u32, &'static str),
+
+    #[error("Frames of {0} bytes are too small to carry the {1}")]
+    FrameSize(usize, &'static str),
+
     #[error("{0} must be {1} bytes long, but is {2} bytes long instead")]
     InvalidSize(&'static str, usize, u64),
 
@@ -106,15 +112,41 @@ pub enum Error {
     #[error("Internal Spongos error: {0}")]
     Spongos(SpongosError),
 
+    #[error("Messages of Streams version {0} cannot be read")]
+    UnsupportedVersion(u8),
+
     /// Transport
 
     #[error("Transport error for address {1}: {0}")]
     AddressError(&'static str, Address),
 
+    #[error("No message found at address {0}")]
+    NotFound(Address),
+
+    #[error("Transport timed out while attempting to {0}")]
+    Timeout(&'static str),
+
+    #[error("Network error while attempting to {0}: {1}")]
+    Network(&'static str, anyhow::Error),
+
+    #[error("More than one message found at address {0}, possibly spam or a duplicate")]
+    Spam(Address),
+
+    #[error("Transport cannot {0}")]
+    Unsupported(&'static str),
+
     #[cfg(any(feature = "tangle-client", feature = "tangle-client-wasm"))]
     #[error("Iota client error for {0}: {1}")]
     IotaClient(&'static str, iota_client::Error),
 
+    #[cfg(feature = "fs-client")]
+    #[error("Filesystem error while attempting to {0}: {1}")]
+    Io(&'static str, std::io::Error),
+
+    #[cfg(feature = "sqlite-client")]
+    #[error("SQLite error while attempting to {0}: {1}")]
+    Sqlite(&'static str, rusqlite::Error),
+
     #[error("message '{0}' not found in {1}")]
     MessageMissing(Address, &'static str),
 
@@ -122,6 +154,7 @@ pub enum Error {
     Nonce(f64),
 
     #[cfg(feature = "utangle-client")]
+    #[deprecated(note = "failed requests are reported as `Error::Timeout` or `Error::Network`")]
     #[error("Request HTTP error: {0}")]
     Request(reqwest::Error),
 }
@@ -135,6 +168,31 @@ impl Error {
     pub fn utf(m: &'static str, error: FromUtf8Error) -> Self {
         Self::Encoding(m, "utf8", Box::new(Self::External(error.into())))
     }
+
+    /// Categorizes a failed HTTP request as a [`Error::Timeout`] or a [`Error::Network`] error
+    ///
+    /// # Arguments
+    /// * `action`: What the request was attempting to do
+    /// * `error`: The request error
+    #[cfg(any(feature = "utangle-client", feature = "http-client"))]
+    pub fn request(action: &'static str, error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout(action)
+        } else {
+            Self::Network(action, anyhow::Error::msg(error))
+        }
+    }
+
+    /// Returns true if the transport holds no message at the requested address
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(_))
+    }
+
+    /// Returns true if the transport failed to communicate, in which case the operation might
+    /// succeed if retried
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Timeout(_) | Self::Network(..))
+    }
 }
 
 impl From<SpongosError> for Error {
@@ -155,10 +213,10 @@ impl From<FromHexError> for Error {
     }
 }
 
-#[cfg(feature = "utangle-client")]
+#[cfg(any(feature = "utangle-client", feature = "http-client"))]
 impl From<reqwest::Error> for Error {
     fn from(error: reqwest::Error) -> Self {
-        Self::Request(error)
+        Self::request("send HTTP request", error)
     }
 }
 
@@ -32,6 +32,7 @@ use crate::{
 use crate::{
     error::Result,
     message::{ContentEncrypt, ContentEncryptSizeOf, ContentVerify},
+    sync::MaybeSend,
 };
 
 /// User Identification types
@@ -208,11 +209,12 @@ where
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<IS, F> ContentVerify<Identifier> for unwrap::Context<IS, F>
 where
-    F: PRP,
-    IS: io::IStream,
+    F: PRP + MaybeSend,
+    IS: io::IStream + MaybeSend,
 {
     /// Verifies the signature of the message based on the type of [`Identifier`] of the signing
     /// user. If the sender [`Identifier`] is of type [`Identifier::Ed25519`], then the public
@@ -284,7 +286,8 @@ where
 }
 
 // TODO: Find a better way to represent this logic without the need for an additional trait
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl ContentEncryptSizeOf<Identifier> for sizeof::Context {
     async fn encrypt_sizeof(&mut self, recipient: &Identifier, key: &[u8]) -> SpongosResult<&mut Self> {
         // TODO: Replace with separate logic for EdPubKey and DID instances (pending Identity xkey
@@ -317,11 +320,12 @@ impl ContentEncryptSizeOf<Identifier> for sizeof::Context {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<OS, F> ContentEncrypt<Identifier> for wrap::Context<OS, F>
 where
-    F: PRP,
-    OS: io::OStream,
+    F: PRP + MaybeSend,
+    OS: io::OStream + MaybeSend,
 {
     async fn encrypt(&mut self, recipient: &Identifier, key: &[u8]) -> SpongosResult<&mut Self> {
         // TODO: Replace with separate logic for EdPubKey and DID instances (pending Identity xkey
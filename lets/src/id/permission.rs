@@ -1,4 +1,6 @@
 // Rust
+use alloc::string::ToString;
+use core::convert::TryFrom;
 
 // IOTA
 
@@ -7,7 +9,7 @@ use spongos::{
     ddml::{
         commands::{sizeof, unwrap, wrap, Mask},
         io,
-        types::Uint8,
+        types::{Size, Uint64, Uint8},
     },
     error::{Error as SpongosError, Result as SpongosResult},
     PRP,
@@ -21,14 +23,31 @@ use crate::id::identifier::Identifier;
 pub enum PermissionDuration {
     /// Indefinite `ReadWrite`
     Perpetual,
-    /// `ReadWrite` until the internal `Unix` timestamp elapses
+    /// `ReadWrite` until the internal `Unix` timestamp (in seconds) elapses
     Unix(u64),
-    /// `ReadWrite` until the specified number of messages has been parsed from the branch
+    /// `ReadWrite` until the specified number of packets has been parsed from the branch
     NumBranchMsgs(u32),
-    /// `ReadWrite` until the specified number of messages has been parsed from the channel
+    /// `ReadWrite` until the holder of the permission has published the specified number of packets
     NumPublishedmsgs(u32),
 }
 
+impl PermissionDuration {
+    /// Returns true if the duration has run out, either because the `Unix` timestamp has been
+    /// reached or because the message budget has been used up.
+    ///
+    /// # Arguments
+    /// * `now`: Current `Unix` timestamp in seconds
+    pub fn is_expired(&self, now: u64) -> bool {
+        match self {
+            PermissionDuration::Perpetual => false,
+            PermissionDuration::Unix(timestamp) => now >= *timestamp,
+            PermissionDuration::NumBranchMsgs(remaining) | PermissionDuration::NumPublishedmsgs(remaining) => {
+                *remaining == 0
+            }
+        }
+    }
+}
+
 impl Default for PermissionDuration {
     fn default() -> Self {
         Self::Perpetual
@@ -42,7 +61,18 @@ impl Mask<&PermissionDuration> for sizeof::Context {
                 self.mask(Uint8::new(0))?;
                 Ok(self)
             }
-            _ => todo!(),
+            PermissionDuration::Unix(timestamp) => {
+                self.mask(Uint8::new(1))?.mask(Uint64::new(*timestamp))?;
+                Ok(self)
+            }
+            PermissionDuration::NumBranchMsgs(num_msgs) => {
+                self.mask(Uint8::new(2))?.mask(Size::new(*num_msgs as usize))?;
+                Ok(self)
+            }
+            PermissionDuration::NumPublishedmsgs(num_msgs) => {
+                self.mask(Uint8::new(3))?.mask(Size::new(*num_msgs as usize))?;
+                Ok(self)
+            }
         }
     }
 }
@@ -53,12 +83,23 @@ where
     OS: io::OStream,
 {
     fn mask(&mut self, duration: &PermissionDuration) -> SpongosResult<&mut Self> {
-        match &duration {
+        match duration {
             PermissionDuration::Perpetual => {
                 self.mask(Uint8::new(0))?;
                 Ok(self)
             }
-            _ => todo!(),
+            PermissionDuration::Unix(timestamp) => {
+                self.mask(Uint8::new(1))?.mask(Uint64::new(*timestamp))?;
+                Ok(self)
+            }
+            PermissionDuration::NumBranchMsgs(num_msgs) => {
+                self.mask(Uint8::new(2))?.mask(Size::new(*num_msgs as usize))?;
+                Ok(self)
+            }
+            PermissionDuration::NumPublishedmsgs(num_msgs) => {
+                self.mask(Uint8::new(3))?.mask(Size::new(*num_msgs as usize))?;
+                Ok(self)
+            }
         }
     }
 }
@@ -75,13 +116,38 @@ where
             0 => {
                 *duration = PermissionDuration::Perpetual;
             }
-            1 | 2 | 3 => todo!(),
-            o => return Err(SpongosError::InvalidOption("identifier", o)),
+            1 => {
+                let mut timestamp = Uint64::default();
+                self.mask(&mut timestamp)?;
+                *duration = PermissionDuration::Unix(timestamp.inner());
+            }
+            2 => {
+                let mut num_msgs = Size::default();
+                self.mask(&mut num_msgs)?;
+                *duration = PermissionDuration::NumBranchMsgs(num_msgs_to_u32(num_msgs)?);
+            }
+            3 => {
+                let mut num_msgs = Size::default();
+                self.mask(&mut num_msgs)?;
+                *duration = PermissionDuration::NumPublishedmsgs(num_msgs_to_u32(num_msgs)?);
+            }
+            o => return Err(SpongosError::InvalidOption("permission duration", o)),
         }
         Ok(self)
     }
 }
 
+/// Converts a decoded message budget into the `u32` carried by [`PermissionDuration`]
+fn num_msgs_to_u32(num_msgs: Size) -> SpongosResult<u32> {
+    u32::try_from(num_msgs.inner()).map_err(|_| {
+        SpongosError::InvalidAction(
+            "decode permission duration",
+            num_msgs.inner().to_string(),
+            "message budget exceeds u32::MAX".to_string(),
+        )
+    })
+}
+
 /// Used to assign Read and Write access to branches within a Stream
 #[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum Permissioned<Identifier> {
@@ -132,6 +198,15 @@ impl<Identifier> Permissioned<Identifier> {
     pub fn is_admin(&self) -> bool {
         matches!(self, Permissioned::Admin(..))
     }
+
+    /// Returns if the [`Permissioned`] is a [`Permissioned::ReadWrite`] whose
+    /// [`PermissionDuration`] has run out.
+    ///
+    /// # Arguments
+    /// * `now`: Current `Unix` timestamp in seconds
+    pub fn is_expired(&self, now: u64) -> bool {
+        matches!(self, Permissioned::ReadWrite(_, duration) if duration.is_expired(now))
+    }
 }
 
 impl From<Permissioned<&Identifier>> for Permissioned<Identifier> {
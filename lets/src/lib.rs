@@ -34,3 +34,6 @@ pub mod id;
 
 /// Errors specific for LETS
 pub mod error;
+
+/// Thread-safety bounds of the async traits, switched by the `send` feature
+pub mod sync;
@@ -0,0 +1,294 @@
+//! Frames of the messages too large to be carried by a single [`TransportMessage`].
+//!
+//! Such a message is wrapped as usual, with an initial [`PCF`] and the number of frames recorded
+//! in its [`HDF`](crate::message::HDF), then split. The first frame is published at the address of
+//! the message and carries the beginning of the wrapped message, including the whole header. The
+//! rest of the message is carried by intermediate frames and a final frame, published at the
+//! addresses returned by [`Address::frame()`](crate::address::Address::frame). Each of these frames
+//! is chained to the previous ones by its spongos state:
+//!
+//! ```ddml
+//! frame Continuation {
+//!     absorb u8 frame_type;
+//!     skip u24 payload_frame_num;
+//!     join(spongos);
+//!     absorb bytes chunk;
+//!     commit;
+//!     squeeze byte mac[32];
+//! }
+//! ```
+//!
+//! where `spongos` is the state of the previous continuation frame, or the state that absorbed the
+//! first frame for the first continuation frame.
+//!
+//! The chain is not keyed: anyone can publish frames chained to a published first frame. Readers
+//! should therefore try the other chains of frames found at the addresses of the frames if the
+//! reassembled message cannot be unwrapped.
+// Rust
+use alloc::{boxed::Box, vec::Vec};
+use core::convert::TryInto;
+
+// 3rd-party
+use async_trait::async_trait;
+
+// IOTA
+
+// Streams
+use spongos::{
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Join, Skip, Squeeze},
+        io,
+        types::{Bytes, Mac, NBytes, Size, Uint8},
+    },
+    error::Result as SpongosResult,
+    KeccakF1600, Spongos, PRP,
+};
+
+// Local
+use crate::{
+    error::{Error, Result},
+    message::{
+        content::{ContentSizeof, ContentUnwrap, ContentWrap},
+        hdf::HDF,
+        pcf::PCF,
+        transport::TransportMessage,
+        version::{FINAL_PCF_ID, INTER_PCF_ID},
+    },
+    sync::{MaybeSend, MaybeSync},
+};
+
+/// [`Mac`] for frame verification
+const MAC: Mac = Mac::new(32);
+
+/// Default maximum size of a frame, fitting in the payload of a Tangle block
+pub const DEFAULT_FRAME_SIZE: usize = 32_000;
+
+/// Chunk of a wrapped message carried by a continuation frame
+struct Chunk<'a, F> {
+    /// The [`Spongos`] state of the previous frame that the frame is joined to
+    previous: &'a mut Spongos<F>,
+    /// Bytes of the wrapped message
+    bytes: Vec<u8>,
+}
+
+impl<'a, F> Chunk<'a, F> {
+    /// Creates a new [`Chunk`]
+    ///
+    /// # Arguments
+    /// * `previous`: The [`Spongos`] state of the previous frame
+    /// * `bytes`: Bytes of the wrapped message
+    fn new(previous: &'a mut Spongos<F>, bytes: Vec<u8>) -> Self {
+        Self { previous, bytes }
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, F> ContentSizeof<Chunk<'a, F>> for sizeof::Context
+where
+    F: MaybeSync,
+{
+    async fn sizeof(&mut self, chunk: &Chunk<'a, F>) -> SpongosResult<&mut Self> {
+        self.absorb(Bytes::new(&chunk.bytes))?.commit()?.squeeze(&MAC)?;
+        Ok(self)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, F, OS> ContentWrap<Chunk<'a, F>> for wrap::Context<OS, F>
+where
+    F: PRP + MaybeSend,
+    OS: io::OStream + MaybeSend,
+{
+    async fn wrap(&mut self, chunk: &mut Chunk<'a, F>) -> SpongosResult<&mut Self> {
+        self.join(chunk.previous)?
+            .absorb(Bytes::new(&chunk.bytes))?
+            .commit()?
+            .squeeze(&MAC)?;
+        Ok(self)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, F, IS> ContentUnwrap<Chunk<'a, F>> for unwrap::Context<IS, F>
+where
+    F: PRP + MaybeSend,
+    IS: io::IStream + MaybeSend,
+{
+    async fn unwrap(&mut self, chunk: &mut Chunk<'a, F>) -> SpongosResult<&mut Self> {
+        self.join(chunk.previous)?
+            .absorb(Bytes::new(&mut chunk.bytes))?
+            .commit()?
+            .squeeze(&MAC)?;
+        Ok(self)
+    }
+}
+
+/// Returns the [`Spongos`] state the first continuation frame is joined to
+///
+/// # Arguments
+/// * `first_frame`: The bytes of the first frame of the message
+fn first_frame_state<F>(first_frame: &[u8]) -> Spongos<F>
+where
+    F: PRP + Default,
+{
+    let mut spongos = Spongos::init();
+    spongos.absorb(first_frame);
+    spongos.commit();
+    spongos
+}
+
+/// Returns the number of bytes of the wrapped message carried by a continuation frame
+///
+/// # Arguments
+/// * `max_frame_size`: The maximum size of a frame
+fn chunk_capacity(max_frame_size: usize) -> Result<usize> {
+    let mut ctx = sizeof::Context::new();
+    ctx.absorb(Uint8::new(FINAL_PCF_ID))?
+        .skip(NBytes::<[u8; 3]>::default())?
+        .absorb(Size::new(max_frame_size))?
+        .commit()?
+        .squeeze(&MAC)?;
+    match max_frame_size.checked_sub(ctx.finalize()) {
+        Some(capacity) if capacity > 0 => Ok(capacity),
+        _ => Err(Error::FrameSize(max_frame_size, "continuation frames")),
+    }
+}
+
+/// Returns the number of frames needed to carry a wrapped message
+///
+/// # Arguments
+/// * `message_size`: The size of the wrapped message
+/// * `max_frame_size`: The maximum size of a frame
+pub(crate) fn frame_count(message_size: usize, max_frame_size: usize) -> Result<u32> {
+    if message_size <= max_frame_size {
+        return Ok(1);
+    }
+    let capacity = chunk_capacity(max_frame_size)?;
+    let continuation_frames = (message_size - max_frame_size).div_ceil(capacity);
+    (continuation_frames + 1)
+        .try_into()
+        .map_err(|_| Error::FrameSize(max_frame_size, "message"))
+}
+
+/// Splits a wrapped message into frames of at most `max_frame_size` bytes
+///
+/// # Arguments
+/// * `message`: The wrapped message
+/// * `max_frame_size`: The maximum size of a frame
+pub(crate) async fn split<F>(message: TransportMessage, max_frame_size: usize) -> Result<Vec<TransportMessage>>
+where
+    F: PRP + Default + MaybeSend + MaybeSync,
+{
+    let capacity = chunk_capacity(max_frame_size)?;
+    let body = message.into_body();
+    let (first_frame, mut rest) = body.split_at(max_frame_size);
+
+    let mut previous = first_frame_state::<F>(first_frame);
+    let mut frames = vec![TransportMessage::new(first_frame.to_vec())];
+    while !rest.is_empty() {
+        let (bytes, remaining) = rest.split_at(capacity.min(rest.len()));
+        rest = remaining;
+        let frame_type = match rest.is_empty() {
+            true => FINAL_PCF_ID,
+            false => INTER_PCF_ID,
+        };
+        let frame_num = frames.len() as u32 + 1;
+        let mut pcf = PCF::new(frame_type, frame_num, Chunk::new(&mut previous, bytes.to_vec()))?;
+
+        let mut ctx = sizeof::Context::new();
+        ctx.sizeof(&pcf).await?;
+        let mut buf = vec![0; ctx.finalize()];
+        let mut ctx = wrap::Context::new(&mut buf[..]);
+        ctx.wrap(&mut pcf).await?;
+        previous = ctx.finalize();
+        frames.push(TransportMessage::new(buf));
+    }
+    Ok(frames)
+}
+
+/// Reassembles a message split into several frames, verifying that every continuation frame is
+/// chained to the previous ones. The assembler can be cloned to follow several chains of frames.
+#[derive(Clone)]
+pub struct FrameAssembler<F = KeccakF1600> {
+    /// Bytes of the wrapped message received so far
+    body: Vec<u8>,
+    /// [`Spongos`] state of the latest frame received
+    state: Spongos<F>,
+    /// Number of frames carrying the message
+    frame_count: u32,
+    /// Number of frames received so far
+    received: u32,
+}
+
+impl<F> FrameAssembler<F>
+where
+    F: PRP + Default + Clone + MaybeSend,
+{
+    /// Creates a new [`FrameAssembler`] from the first frame of a message
+    ///
+    /// # Arguments
+    /// * `first_frame`: The first frame of the message, published at its address
+    /// * `frame_count`: The number of frames carrying the message, as found in its header
+    pub fn new(first_frame: TransportMessage, frame_count: u32) -> Self {
+        let body = first_frame.into_body();
+        Self {
+            state: first_frame_state(&body),
+            body,
+            frame_count,
+            received: 1,
+        }
+    }
+
+    /// Returns the number of the next frame to push, or `None` if the message is complete
+    pub fn next_frame_num(&self) -> Option<u32> {
+        match self.received < self.frame_count {
+            true => Some(self.received + 1),
+            false => None,
+        }
+    }
+
+    /// Verifies the next frame of the message and appends its content to the message. The
+    /// assembler is left unchanged if the frame is not valid.
+    ///
+    /// # Arguments
+    /// * `frame`: The frame published at the address of the next frame
+    pub async fn push(&mut self, frame: &TransportMessage) -> Result<()> {
+        let frame_num = self
+            .next_frame_num()
+            .ok_or(Error::Frame(self.received + 1, "beyond the frame count"))?;
+        let mut previous = self.state.clone();
+        let mut pcf = PCF::<()>::default().with_content(Chunk::new(&mut previous, Vec::new()));
+        let mut ctx = unwrap::Context::new(frame.as_ref());
+        ctx.unwrap(&mut pcf).await?;
+        let (state, _) = ctx.finalize();
+
+        let expected_frame_type = match frame_num == self.frame_count {
+            true => FINAL_PCF_ID,
+            false => INTER_PCF_ID,
+        };
+        if pcf.frame_type() != expected_frame_type {
+            return Err(Error::Frame(frame_num, "unexpected frame type"));
+        }
+        if pcf.payload_frame_num() != frame_num {
+            return Err(Error::Frame(frame_num, "unexpected frame number"));
+        }
+
+        self.body.extend(pcf.into_content().bytes);
+        self.state = state;
+        self.received += 1;
+        Ok(())
+    }
+
+    /// Consumes the [`FrameAssembler`], returning the reassembled message. Its header records a
+    /// single frame, so that it is not reassembled again.
+    pub fn finish(mut self) -> Result<TransportMessage> {
+        if let Some(frame_num) = self.next_frame_num() {
+            return Err(Error::Frame(frame_num, "missing"));
+        }
+        HDF::rewrite_payload_frame_count(&mut self.body, 1);
+        Ok(TransportMessage::new(self.body))
+    }
+}
@@ -6,7 +6,7 @@ use spongos::{
         commands::{sizeof, unwrap, wrap, Absorb, Commit, Guard, Mask, Skip, Squeeze},
         io,
         modifiers::External,
-        types::{Mac, Maybe, NBytes, Size, Uint8},
+        types::{Mac, Maybe, NBytes, Size, Uint64, Uint8},
     },
     error::{Error as SpongosError, Result as SpongosResult},
     PRP,
@@ -19,13 +19,21 @@ use crate::{
     message::{
         content::{ContentSizeof, ContentUnwrap, ContentWrap},
         topic::{Topic, TopicHash},
-        version::{HDF_ID, STREAMS_VER, UTF8},
+        version::{is_supported_version, HDF_ID, STREAMS_VER, STREAMS_VER_1, STREAMS_VER_2, UTF8},
     },
+    sync::MaybeSend,
 };
 
 /// [`Mac`] for content verification
 const MAC: Mac = Mac::new(32);
 
+/// Position of the Streams version in a wrapped [`HDF`], following the encoding
+const VERSION_POSITION: usize = 1;
+
+/// Position of the payload frame count in a wrapped [`HDF`], following the encoding, version,
+/// message type, payload length and frame type
+const PAYLOAD_FRAME_COUNT_POSITION: usize = 5;
+
 /// The header of a `Streams` message
 #[non_exhaustive]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -48,6 +56,8 @@ pub struct HDF {
     payload_frame_count: u32,
     /// Link to previous message in Stream
     pub linked_msg_address: Option<MsgId>,
+    /// `Unix` timestamp in seconds of the publication of the message, as declared by the publisher
+    pub timestamp: Option<u64>,
     /// Publisher sequence number
     pub sequence: usize,
     /// Publisher [`Identifier`]
@@ -66,6 +76,7 @@ impl Default for HDF {
             frame_type: HDF_ID,
             payload_frame_count: 0,
             linked_msg_address: Default::default(),
+            timestamp: None,
             sequence: 0,
             publisher: Default::default(),
             topic_hash: Default::default(),
@@ -95,6 +106,7 @@ impl HDF {
             frame_type: HDF_ID,
             payload_frame_count: 0,
             linked_msg_address: None,
+            timestamp: None,
             sequence,
             publisher,
             topic_hash: topic.into(),
@@ -110,6 +122,17 @@ impl HDF {
         self
     }
 
+    /// Injects the publication timestamp into the [`HDF`]. The timestamp is authenticated along
+    /// with the rest of the header, and signed by the signature of signed messages. It is not
+    /// part of the headers of [`STREAMS_VER_2`].
+    ///
+    /// # Arguments
+    /// * `timestamp`: `Unix` timestamp of the publication of the message, in seconds
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
     /// Injects a payload length into the [`HDF`]. Can be a maximum of 10 bits in size
     ///
     /// # Arguments
@@ -124,6 +147,38 @@ impl HDF {
         }
     }
 
+    /// Injects a payload frame count into the [`HDF`]. Can be a maximum of 22 bits in size
+    ///
+    /// # Arguments
+    /// * `payload_frame_count`: The number of frames carrying the message
+    pub fn with_payload_frame_count(mut self, payload_frame_count: u32) -> Result<Self> {
+        match payload_frame_count >> 22 == 0 {
+            true => {
+                self.payload_frame_count = payload_frame_count;
+                Ok(self)
+            }
+            false => Err(Error::InvalidSize(
+                "payload_frame_count",
+                22,
+                payload_frame_count.into(),
+            )),
+        }
+    }
+
+    /// Injects a payload encoding identifier into the [`HDF`]
+    ///
+    /// # Arguments
+    /// * `encoding`: The identifier of the encoding of the payload
+    pub fn with_encoding(mut self, encoding: u8) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the identifier of the encoding of the associated payload
+    pub fn encoding(&self) -> u8 {
+        self.encoding
+    }
+
     /// Returns the message type for the associated payload
     pub fn message_type(&self) -> u8 {
         self.message_type
@@ -154,13 +209,42 @@ impl HDF {
         self.linked_msg_address
     }
 
+    /// Returns the `Unix` timestamp of the publication of the message, if declared by the publisher
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
     /// Returns a reference to the [`TopicHash`] representing a branch
     pub fn topic_hash(&self) -> &TopicHash {
         &self.topic_hash
     }
+
+    /// Returns the Streams version of the [`HDF`] wrapped at the beginning of a message, if the
+    /// message is long enough to hold it
+    ///
+    /// # Arguments
+    /// * `message`: The wrapped message
+    pub(crate) fn version_of(message: &[u8]) -> Option<u8> {
+        message.get(VERSION_POSITION).copied()
+    }
+
+    /// Overwrites the payload frame count of the [`HDF`] wrapped at the beginning of a message. The
+    /// frame count is skipped by the message context, so the message remains valid.
+    ///
+    /// # Arguments
+    /// * `message`: The wrapped message
+    /// * `payload_frame_count`: The new payload frame count, at most 22 bits in size
+    pub(crate) fn rewrite_payload_frame_count(message: &mut [u8], payload_frame_count: u32) {
+        let x = payload_frame_count.to_be_bytes();
+        let bytes = &mut message[PAYLOAD_FRAME_COUNT_POSITION..PAYLOAD_FRAME_COUNT_POSITION + 3];
+        bytes[0] = x[1] & 0b00111111;
+        bytes[1] = x[2];
+        bytes[2] = x[3];
+    }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl ContentSizeof<HDF> for sizeof::Context {
     async fn sizeof(&mut self, hdf: &HDF) -> SpongosResult<&mut Self> {
         let message_type_and_payload_length = NBytes::<[u8; 2]>::default();
@@ -170,9 +254,14 @@ impl ContentSizeof<HDF> for sizeof::Context {
             .skip(message_type_and_payload_length)?
             .absorb(External::new(Uint8::new(hdf.message_type << 4)))?
             .absorb(Uint8::new(hdf.frame_type))?
-            .skip(payload_frame_count)?
-            .absorb(Maybe::new(hdf.linked_msg_address.as_ref()))?
-            .mask(&hdf.topic_hash)?
+            .skip(payload_frame_count)?;
+        match hdf.version {
+            STREAMS_VER_2 => self.absorb(Maybe::new(hdf.linked_msg_address.as_ref()))?,
+            _ => self
+                .absorb(Maybe::new(hdf.linked_msg_address.as_ref()))?
+                .absorb(Maybe::new(hdf.timestamp.map(Uint64::new)))?,
+        };
+        self.mask(&hdf.topic_hash)?
             .mask(&hdf.publisher)?
             .skip(Size::new(hdf.sequence))?
             .commit()?
@@ -182,11 +271,12 @@ impl ContentSizeof<HDF> for sizeof::Context {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<F, OS> ContentWrap<HDF> for wrap::Context<OS, F>
 where
-    F: PRP,
-    OS: io::OStream,
+    F: PRP + MaybeSend,
+    OS: io::OStream + MaybeSend,
 {
     async fn wrap(&mut self, hdf: &mut HDF) -> SpongosResult<&mut Self> {
         let message_type_and_payload_length = {
@@ -209,9 +299,14 @@ where
             .skip(message_type_and_payload_length)?
             .absorb(External::new(Uint8::new(hdf.message_type << 4)))?
             .absorb(Uint8::new(hdf.frame_type))?
-            .skip(payload_frame_count)?
-            .absorb(Maybe::new(hdf.linked_msg_address.as_ref()))?
-            .mask(&hdf.topic_hash)?
+            .skip(payload_frame_count)?;
+        match hdf.version {
+            STREAMS_VER_2 => self.absorb(Maybe::new(hdf.linked_msg_address.as_ref()))?,
+            _ => self
+                .absorb(Maybe::new(hdf.linked_msg_address.as_ref()))?
+                .absorb(Maybe::new(hdf.timestamp.map(Uint64::new)))?,
+        };
+        self.mask(&hdf.topic_hash)?
             .mask(&hdf.publisher)?
             .skip(Size::new(hdf.sequence))?
             .commit()?
@@ -221,11 +316,12 @@ where
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<F, IS> ContentUnwrap<HDF> for unwrap::Context<IS, F>
 where
-    F: PRP,
-    IS: io::IStream,
+    F: PRP + MaybeSend,
+    IS: io::IStream + MaybeSend,
 {
     async fn unwrap(&mut self, mut hdf: &mut HDF) -> SpongosResult<&mut Self> {
         let mut encoding = Uint8::default();
@@ -240,7 +336,7 @@ where
         self.absorb(&mut encoding)?
             .absorb(&mut version)?
             .guard(
-                version.inner() == STREAMS_VER,
+                is_supported_version(version.inner()),
                 SpongosError::Version("Msg", version.inner()),
             )?
             .skip(message_type_and_payload_length.as_mut())?
@@ -259,15 +355,37 @@ where
             )?
             .skip(payload_frame_count_bytes.as_mut())?
             .guard(
-                0 == payload_frame_count_bytes[0] & 0b1100,
+                0 == payload_frame_count_bytes[0] & 0b11000000,
                 SpongosError::Reserved("first 2 bits of payload-frame-count"),
-            )?
-            .absorb(Maybe::new(&mut hdf.linked_msg_address))?
-            .mask(&mut hdf.topic_hash)?
-            .mask(&mut hdf.publisher)?
-            .skip(&mut seq_num)?
-            .commit()?
-            .squeeze(&MAC)?;
+            )?;
+        if version.inner() == STREAMS_VER_1 {
+            // Headers of the first generation end with the linked message, left blank by the
+            // announcement, and the publisher sequence number. They are authenticated by the
+            // content of the message only
+            let mut linked_msg_address = MsgId::default();
+            let mut seq_num = Uint64::default();
+            self.absorb(&mut linked_msg_address)?.skip(&mut seq_num)?;
+            hdf.linked_msg_address = Some(linked_msg_address).filter(|address| *address != MsgId::default());
+            hdf.sequence = seq_num.inner() as usize;
+        } else {
+            match version.inner() {
+                STREAMS_VER_2 => {
+                    self.absorb(Maybe::new(&mut hdf.linked_msg_address))?;
+                }
+                _ => {
+                    let mut timestamp: Option<Uint64> = None;
+                    self.absorb(Maybe::new(&mut hdf.linked_msg_address))?
+                        .absorb(Maybe::new(&mut timestamp))?;
+                    hdf.timestamp = timestamp.map(|timestamp| timestamp.inner());
+                }
+            }
+            self.mask(&mut hdf.topic_hash)?
+                .mask(&mut hdf.publisher)?
+                .skip(&mut seq_num)?
+                .commit()?
+                .squeeze(&MAC)?;
+            hdf.sequence = seq_num.inner();
+        }
 
         hdf.encoding = encoding.inner();
         hdf.version = version.inner();
@@ -281,7 +399,6 @@ where
         x[2] = payload_frame_count_bytes[1];
         x[3] = payload_frame_count_bytes[2];
         hdf.payload_frame_count = u32::from_be_bytes(x);
-        hdf.sequence = seq_num.inner();
 
         Ok(self)
     }
@@ -1,4 +1,5 @@
 // Rust
+use alloc::vec::Vec;
 
 // IOTA
 
@@ -10,13 +11,16 @@ use spongos::{
 
 // Local
 use crate::{
-    error::Result,
+    error::{Error, Result},
     message::{
         content::{ContentSizeof, ContentWrap},
+        frame,
         hdf::HDF,
         pcf::PCF,
         transport::TransportMessage,
+        version::INIT_PCF_ID,
     },
+    sync::{MaybeSend, MaybeSync},
 };
 
 /// Streams Message comprised of a Header ([`HDF`]) and Payload([`PCF`])
@@ -107,4 +111,39 @@ impl<Payload> Message<Payload> {
 
         Ok((TransportMessage::new(buf), spongos))
     }
+
+    /// Encodes the message for transport like [`Message::wrap()`], splitting it into several frames
+    /// if it is larger than `max_frame_size` bytes. The [`HDF`] then records the number of frames,
+    /// and the [`PCF`] becomes an initial frame. The first [`TransportMessage`] returned is to be
+    /// published at the address of the message, and the frame `n` at
+    /// [`Address::frame(n)`](crate::address::Address::frame).
+    ///
+    /// # Arguments
+    /// * `max_frame_size`: The maximum size of a frame
+    pub async fn wrap_frames<F>(&mut self, max_frame_size: usize) -> Result<(Vec<TransportMessage>, Spongos<F>)>
+    where
+        F: PRP + Default + MaybeSend + MaybeSync,
+        for<'b> wrap::Context<&'b mut [u8], F>: ContentWrap<HDF> + ContentWrap<PCF<Payload>>,
+        sizeof::Context: ContentSizeof<HDF> + ContentSizeof<PCF<Payload>>,
+    {
+        let mut ctx = sizeof::Context::new();
+        ctx.sizeof(&self.header).await?;
+        let header_size = ctx.finalize();
+        ctx.commit()?.sizeof(&self.payload).await?;
+        let frame_count = frame::frame_count(ctx.finalize(), max_frame_size)?;
+        if frame_count == 1 {
+            let (transport_msg, spongos) = self.wrap().await?;
+            return Ok((vec![transport_msg], spongos));
+        }
+        if header_size > max_frame_size {
+            return Err(Error::FrameSize(max_frame_size, "message header"));
+        }
+
+        self.header = self.header.clone().with_payload_frame_count(frame_count)?;
+        self.payload.change_frame_type(INIT_PCF_ID);
+        self.payload.with_payload_frame_num(1)?;
+        let (transport_msg, spongos) = self.wrap().await?;
+        let frames = frame::split::<F>(transport_msg, max_frame_size).await?;
+        Ok((frames, spongos))
+    }
 }
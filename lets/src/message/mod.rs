@@ -1,5 +1,7 @@
 /// Traits for implementing Spongos de/serialization
 mod content;
+/// Frames of the messages split for transport
+mod frame;
 /// Header Description Frame
 mod hdf;
 /// Payload Carrying Frame
@@ -20,9 +22,11 @@ pub use content::{
     ContentDecrypt, ContentEncrypt, ContentEncryptSizeOf, ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap,
     ContentVerify, ContentWrap,
 };
+pub use frame::{FrameAssembler, DEFAULT_FRAME_SIZE};
 pub use hdf::HDF;
 pub use message::Message;
 pub use pcf::PCF;
 pub use preparsed::PreparsedMessage;
 pub use topic::{Topic, TopicHash};
 pub use transport::TransportMessage;
+pub use version::{is_supported_version, STREAMS_VER, STREAMS_VER_1, STREAMS_VER_2};
@@ -19,6 +19,7 @@ use crate::{
         content::{ContentSizeof, ContentUnwrap, ContentWrap},
         version::{FINAL_PCF_ID, INIT_PCF_ID, INTER_PCF_ID},
     },
+    sync::{MaybeSend, MaybeSync},
 };
 
 /// Payload Carrying Frame. Contains the body of a Streams message
@@ -34,7 +35,8 @@ pub struct PCF<Content> {
     content: Content,
 }
 
-// All PCF's are Frame Number 1 right now since the increase in tangle payload space to 32kb
+// Messages carried by a single frame only have a final frame, numbered 1. Messages split into
+// several frames start with an initial frame numbered 1, see `Message::wrap_frames()`
 impl PCF<()> {
     /// Creates a default Initial Payload Carrying Frame
     pub fn new_init_frame() -> Self {
@@ -108,6 +110,19 @@ impl<Content> PCF<Content> {
         self.content = content;
     }
 
+    /// Returns the frame type identifier of the [`PCF`]
+    pub fn frame_type(&self) -> u8 {
+        self.frame_type
+    }
+
+    /// Replaces the frame type identifier of the [`PCF`]
+    ///
+    /// # Arguments
+    /// * `frame_type`: Identifier for the type of frame
+    pub(crate) fn change_frame_type(&mut self, frame_type: u8) {
+        self.frame_type = frame_type;
+    }
+
     /// Returns a reference to the payload of the [`PCF`]
     pub fn content(&self) -> &Content {
         &self.content
@@ -133,9 +148,11 @@ impl<Content> PCF<Content> {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<Content> ContentSizeof<PCF<Content>> for sizeof::Context
 where
+    Content: MaybeSync,
     sizeof::Context: ContentSizeof<Content>,
 {
     async fn sizeof(&mut self, pcf: &PCF<Content>) -> SpongosResult<&mut Self> {
@@ -147,11 +164,13 @@ where
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<F, OS, Content> ContentWrap<PCF<Content>> for wrap::Context<OS, F>
 where
-    F: PRP,
-    OS: io::OStream,
+    F: PRP + MaybeSend,
+    OS: io::OStream + MaybeSend,
+    Content: MaybeSend,
     Self: ContentWrap<Content>,
 {
     async fn wrap(&mut self, pcf: &mut PCF<Content>) -> SpongosResult<&mut Self>
@@ -166,11 +185,13 @@ where
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<F, IS, Content> ContentUnwrap<PCF<Content>> for unwrap::Context<IS, F>
 where
-    F: PRP,
-    IS: io::IStream,
+    F: PRP + MaybeSend,
+    IS: io::IStream + MaybeSend,
+    Content: MaybeSend,
     unwrap::Context<IS, F>: ContentUnwrap<Content>,
 {
     async fn unwrap(&mut self, pcf: &mut PCF<Content>) -> SpongosResult<&mut Self> {
@@ -8,8 +8,9 @@ use spongos::{ddml::commands::unwrap, PRP};
 
 // Local
 use crate::{
-    error::Result,
-    message::{content::ContentUnwrap, hdf::HDF, preparsed::PreparsedMessage},
+    error::{Error, Result},
+    message::{content::ContentUnwrap, hdf::HDF, preparsed::PreparsedMessage, version::{is_supported_version, STREAMS_VER_1}},
+    sync::MaybeSend,
 };
 
 /// Binary network Message representation.
@@ -37,19 +38,36 @@ impl TransportMessage {
 }
 
 impl TransportMessage {
-    /// Creates a new [`unwrap::Context`] for the message body and decodes the [`HDF`].
+    /// Returns the Streams version the message is written with, if the message is long enough to
+    /// hold a header
+    pub fn version(&self) -> Option<u8> {
+        HDF::version_of(self.body())
+    }
+
+    /// Creates a new [`unwrap::Context`] for the message body and decodes the [`HDF`], according to
+    /// the Streams version the message is written with. Fails with [`Error::UnsupportedVersion`]
+    /// if the version cannot be read, see [`is_supported_version`].
     /// The remaining context [`spongos::Spongos`] and cursor position are then wrapped with the
-    /// [`HDF`] into a [`PreparsedMessage`] for content processing and returned.
+    /// [`HDF`] into a [`PreparsedMessage`] for content processing and returned. The spongos is
+    /// committed, except for messages of [`STREAMS_VER_1`], whose content carries on with the state
+    /// of the header.
     pub async fn parse_header<F>(self) -> Result<PreparsedMessage<F>>
     where
-        F: PRP + Default,
+        F: PRP + Default + MaybeSend,
     {
+        if let Some(version) = self.version().filter(|version| !is_supported_version(*version)) {
+            return Err(Error::UnsupportedVersion(version));
+        }
         let mut ctx = unwrap::Context::new(self.body().as_ref());
         let mut header = HDF::default();
 
         ctx.unwrap(&mut header).await?;
 
-        let (spongos, cursor) = ctx.finalize();
+        let (spongos, cursor) = if header.version == STREAMS_VER_1 {
+            ctx.into_parts()
+        } else {
+            ctx.finalize()
+        };
 
         Ok(PreparsedMessage::new(self, header, spongos, cursor))
     }
@@ -10,9 +10,27 @@
 //! Streams message syntax version is indicated as the first byte in the binary encoded message.
 //!
 //! Backwards compatibility of the Streams implementations is welcome and not mandatory.
+//!
+//! Messages are written with the version of the stream they are published in: [`STREAMS_VER`] for
+//! new streams, and [`STREAMS_VER_2`] for the streams started by the previous generation of the
+//! protocol, which remain readable and writable. The syntax of [`STREAMS_VER_2`] differs from the
+//! current version in that:
+//! * the `Header` carries no publication timestamp;
+//! * signed and tagged packets carry no attachments and no key sequence;
+//! * branch announcements declare no packet limit.
+//!
+//! Messages of [`STREAMS_VER_1`], the first generation of the protocol, can still be read so that
+//! archived channels remain readable, but their streams are read-only. Their `Header` ends with
+//! the linked message identifier and the sequence number of the publisher, and carries neither a
+//! branch, a publisher nor a MAC. Their message types and content syntax are the ones of the
+//! Channels protocol of Streams 1.0.
 
 /// Streams version number.
-pub(crate) const STREAMS_VER: u8 = 2;
+pub const STREAMS_VER: u8 = 3;
+/// Streams version number of the previous generation of the protocol
+pub const STREAMS_VER_2: u8 = 2;
+/// Streams version number of the first generation of the protocol, which is read-only
+pub const STREAMS_VER_1: u8 = 1;
 
 /// Encoding Constants
 pub(crate) const UTF8: u8 = 0;
@@ -25,3 +43,11 @@ pub(crate) const INIT_PCF_ID: u8 = 5;
 pub(crate) const INTER_PCF_ID: u8 = 12;
 /// Final PCF Frame Identifier
 pub(crate) const FINAL_PCF_ID: u8 = 14;
+
+/// Returns whether messages of the Streams version can be read
+///
+/// # Arguments
+/// * `version`: The Streams version number of a message
+pub fn is_supported_version(version: u8) -> bool {
+    version == STREAMS_VER || version == STREAMS_VER_2 || version == STREAMS_VER_1
+}
@@ -0,0 +1,43 @@
+// Rust
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+
+// Local
+
+/// Bound required on the types held across the `await` points of the async traits of the crate.
+///
+/// Equivalent to `Send` when the `send` feature is enabled, satisfied by every type otherwise.
+#[cfg(feature = "send")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "send")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Bound required on the types held across the `await` points of the async traits of the crate.
+///
+/// Equivalent to `Send` when the `send` feature is enabled, satisfied by every type otherwise.
+#[cfg(not(feature = "send"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "send"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Bound required on the types borrowed across the `await` points of the async traits of the
+/// crate.
+///
+/// Equivalent to `Sync` when the `send` feature is enabled, satisfied by every type otherwise.
+#[cfg(feature = "send")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "send")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// Bound required on the types borrowed across the `await` points of the async traits of the
+/// crate.
+///
+/// Equivalent to `Sync` when the `send` feature is enabled, satisfied by every type otherwise.
+#[cfg(not(feature = "send"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "send"))]
+impl<T: ?Sized> MaybeSync for T {}
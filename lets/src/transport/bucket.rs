@@ -1,18 +1,29 @@
 // Rust
 use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
 
+#[cfg(feature = "std")]
+use core::hash::{Hash, Hasher};
+
 // 3rd-party
 use async_trait::async_trait;
+#[cfg(feature = "std")]
+use futures::{
+    channel::mpsc::{self, UnboundedSender},
+    StreamExt,
+};
 
 // IOTA
 
 // Streams
 
 // Local
+#[cfg(feature = "std")]
+use crate::transport::{Notifications, Subscription};
 use crate::{
     address::Address,
     error::{Error, Result},
     message::TransportMessage,
+    sync::MaybeSend,
     transport::Transport,
 };
 
@@ -23,6 +34,9 @@ pub struct Client<Msg = TransportMessage> {
     // Use BTreeMap instead of HashMap to make BucketTransport nostd without pulling hashbrown
     // (this transport is for hacking purposes only, performance is no concern)
     bucket: BTreeMap<Address, Vec<Msg>>,
+    /// Subscriptions notified of the messages sent to the bucket
+    #[cfg(feature = "std")]
+    subscribers: Subscribers<Msg>,
 }
 
 impl<Msg> Client<Msg> {
@@ -37,14 +51,64 @@ impl<Msg> Default for Client<Msg> {
     fn default() -> Self {
         Self {
             bucket: BTreeMap::default(),
+            #[cfg(feature = "std")]
+            subscribers: Subscribers(Vec::new()),
         }
     }
 }
 
-#[async_trait(?Send)]
-impl<Msg> Transport<'_> for Client<Msg>
+/// Notification channels of the [`Subscription`]s to a [Bucket Client](`Client`).
+///
+/// The channels are not part of the state of the bucket: they are ignored by comparisons and
+/// hashes, and a cloned bucket starts without subscribers.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Subscribers<Msg>(Vec<(Subscription, UnboundedSender<(Address, Msg)>)>);
+
+#[cfg(feature = "std")]
+impl<Msg> Subscribers<Msg>
 where
     Msg: Clone,
+{
+    /// Notifies the message to the matching subscriptions, forgetting the ones that were dropped
+    ///
+    /// # Arguments
+    /// * `address`: The address the message was sent to
+    /// * `msg`: The sent message
+    fn notify(&mut self, address: Address, msg: &Msg) {
+        self.0.retain(|(subscription, sender)| {
+            !subscription.matches(address) || sender.unbounded_send((address, msg.clone())).is_ok()
+        });
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Msg> Clone for Subscribers<Msg> {
+    fn clone(&self) -> Self {
+        Self(Vec::new())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Msg> PartialEq for Subscribers<Msg> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Msg> Eq for Subscribers<Msg> {}
+
+#[cfg(feature = "std")]
+impl<Msg> Hash for Subscribers<Msg> {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Msg> Transport<'_> for Client<Msg>
+where
+    Msg: Clone + MaybeSend + 'static,
 {
     type Msg = Msg;
     type SendResponse = Msg;
@@ -62,11 +126,13 @@ where
         Self::Msg: 'async_trait,
     {
         self.bucket.entry(addr).or_default().push(msg.clone());
+        #[cfg(feature = "std")]
+        self.subscribers.notify(addr, &msg);
         Ok(msg)
     }
 
-    /// Returns a vector of messages from the bucket, or an error if the bucket doesn't contain the
-    /// address
+    /// Returns a vector of messages from the bucket, or [`Error::NotFound`] if the bucket doesn't
+    /// contain the address
     ///
     /// # Arguments
     /// * `address`: The address to retrieve messages from.
@@ -74,9 +140,70 @@ where
     /// Returns:
     /// A vector of messages.
     async fn recv_messages(&mut self, address: Address) -> Result<Vec<Msg>> {
-        self.bucket
-            .get(&address)
-            .cloned()
-            .ok_or(Error::AddressError("No message found", address))
+        self.bucket.get(&address).cloned().ok_or(Error::NotFound(address))
+    }
+
+    /// Returns a stream of the messages sent to the bucket from now on at the addresses of the
+    /// [`Subscription`]. Requires the `std` feature, errors with [`Error::Unsupported`] otherwise.
+    ///
+    /// # Arguments
+    /// * `subscription`: The addresses to be notified of.
+    #[cfg(feature = "std")]
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<Msg>> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribers.0.push((subscription, sender));
+        Ok(Box::pin(receiver.map(Ok)))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use futures::StreamExt;
+
+    use crate::{
+        address::{AppAddr, MsgId},
+        id::Identifier,
+        message::Topic,
+    };
+
+    use super::*;
+
+    fn address(appaddr: AppAddr, cursor: usize) -> Address {
+        Address::new(
+            appaddr,
+            MsgId::gen(appaddr, &Identifier::default(), &Topic::default(), cursor),
+        )
+    }
+
+    #[tokio::test]
+    async fn subscriptions_are_notified_of_matching_messages() -> Result<()> {
+        let mut client = Client::new();
+        let (stream, other_stream) = (AppAddr::default(), AppAddr::new([1; 40]));
+        let notifications = client.subscribe(Subscription::Stream(stream)).await?;
+        let mut address_notifications = client
+            .subscribe(Subscription::Addresses(vec![address(stream, 2)]))
+            .await?;
+
+        for (i, address) in vec![address(other_stream, 1), address(stream, 1), address(stream, 2)]
+            .into_iter()
+            .enumerate()
+        {
+            client
+                .send_message(address, TransportMessage::new(vec![i as u8; 32]))
+                .await?;
+        }
+        drop(client);
+
+        let notified: Vec<Address> = notifications
+            .map(|notification| notification.unwrap().0)
+            .collect()
+            .await;
+        assert_eq!(notified, vec![address(stream, 1), address(stream, 2)]);
+        let (notified, msg) = address_notifications.next().await.unwrap()?;
+        assert_eq!(
+            (notified, msg),
+            (address(stream, 2), TransportMessage::new(vec![2; 32]))
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,335 @@
+// Rust
+extern crate std;
+
+use alloc::{boxed::Box, vec::Vec};
+use core::time::Duration;
+use std::time::Instant;
+
+// 3rd-party
+use async_trait::async_trait;
+use lru::LruCache;
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{
+    address::Address,
+    error::{Error, Result},
+    message::TransportMessage,
+    sync::MaybeSend,
+    transport::{Notifications, Subscription, Transport},
+};
+
+/// Outcome of a `recv_messages` call kept in the cache
+enum Entry<Msg> {
+    /// Messages found at the address at the provided instant
+    Found(Vec<Msg>, Instant),
+    /// No message was found at the address at the provided instant
+    NotFound(Instant),
+}
+
+/// A read-through [`Transport`] wrapper keeping the most recently used `recv_messages` results in
+/// an LRU cache keyed by [`Address`].
+///
+/// Messages are immutable once published, but more messages can be published at an address where
+/// some were already found, such as spam or the genuine message after spam. Found messages are
+/// therefore cached for a time-to-live, and addresses where no message was found for a shorter one,
+/// as a message can be published there at any moment. Sending a message through the wrapper evicts
+/// the cached entry of its address.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use lets::transport::{bucket, cache::CachingTransport};
+/// let transport: CachingTransport<bucket::Client> =
+///     CachingTransport::new(bucket::Client::new(), 1024)
+///         .with_found_ttl(Duration::from_secs(30))
+///         .with_not_found_ttl(Duration::from_millis(500));
+/// ```
+pub struct CachingTransport<Tsp, Msg = TransportMessage> {
+    /// Wrapped [`Transport`]
+    inner: Tsp,
+    /// Cached `recv_messages` results
+    cache: LruCache<Address, Entry<Msg>>,
+    /// Time during which the messages found at an address are served from the cache
+    found_ttl: Duration,
+    /// Time during which an address where no message was found is considered empty
+    not_found_ttl: Duration,
+}
+
+impl<Tsp, Msg> CachingTransport<Tsp, Msg> {
+    /// Wraps the [`Transport`] in a [`CachingTransport`] caching up to `capacity` addresses, for
+    /// 60 seconds, and addresses where no message was found for 1 second.
+    ///
+    /// # Arguments
+    /// * `inner`: The [`Transport`] to cache the results of
+    /// * `capacity`: Maximum number of addresses kept in the cache
+    pub fn new(inner: Tsp, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: LruCache::new(capacity),
+            found_ttl: Duration::from_secs(60),
+            not_found_ttl: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the time during which the messages found at an address are served from the cache
+    /// without querying the inner [`Transport`] again. A zero duration disables the cache.
+    ///
+    /// # Arguments
+    /// * `found_ttl`: Time-to-live of the found messages
+    pub fn with_found_ttl(mut self, found_ttl: Duration) -> Self {
+        self.found_ttl = found_ttl;
+        self
+    }
+
+    /// Sets the time during which an address where no message was found is considered empty
+    /// without querying the inner [`Transport`] again. A zero duration disables negative caching.
+    ///
+    /// # Arguments
+    /// * `not_found_ttl`: Time-to-live of the "not found" entries
+    pub fn with_not_found_ttl(mut self, not_found_ttl: Duration) -> Self {
+        self.not_found_ttl = not_found_ttl;
+        self
+    }
+
+    /// Evicts the cached entry of the [`Address`], if any
+    ///
+    /// # Arguments
+    /// * `address`: The address to evict
+    pub fn invalidate(&mut self, address: Address) {
+        self.cache.pop(&address);
+    }
+
+    /// Evicts every cached entry
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Returns the cached outcome of a `recv_messages` call at the [`Address`], if any
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages
+    fn cached(&mut self, address: Address) -> Option<Result<Vec<Msg>>>
+    where
+        Msg: Clone,
+    {
+        match self.cache.get(&address)? {
+            Entry::Found(msgs, since) if since.elapsed() < self.found_ttl => Some(Ok(msgs.clone())),
+            Entry::NotFound(since) if since.elapsed() < self.not_found_ttl => Some(Err(Error::NotFound(address))),
+            _ => None,
+        }
+    }
+
+    /// Caches the outcome of a `recv_messages` call at the [`Address`]. Errors other than
+    /// [`Error::NotFound`] are not cached.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages
+    /// * `outcome`: The outcome of the call
+    fn store(&mut self, address: Address, outcome: &Result<Vec<Msg>>)
+    where
+        Msg: Clone,
+    {
+        match outcome {
+            Ok(msgs) => {
+                self.cache.put(address, Entry::Found(msgs.clone(), Instant::now()));
+            }
+            Err(e) if e.is_not_found() => {
+                self.cache.put(address, Entry::NotFound(Instant::now()));
+            }
+            Err(_) => (),
+        }
+    }
+
+    /// Returns a reference to the wrapped [`Transport`]
+    pub fn inner(&self) -> &Tsp {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped [`Transport`]
+    pub fn inner_mut(&mut self) -> &mut Tsp {
+        &mut self.inner
+    }
+
+    /// Consumes the [`CachingTransport`], returning the wrapped [`Transport`]
+    pub fn into_inner(self) -> Tsp {
+        self.inner
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, Tsp, Msg, SendResponse> Transport<'a> for CachingTransport<Tsp, Msg>
+where
+    Tsp: Transport<'a, Msg = Msg, SendResponse = SendResponse> + MaybeSend,
+    Msg: Clone + MaybeSend + 'a,
+{
+    type Msg = Msg;
+    type SendResponse = SendResponse;
+
+    /// Sends a message through the inner [`Transport`], evicting the cached entry of the
+    /// [`Address`].
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message.
+    /// * `msg`: The message to send.
+    async fn send_message(&mut self, address: Address, msg: Msg) -> Result<SendResponse>
+    where
+        'a: 'async_trait,
+    {
+        let response = self.inner.send_message(address, msg).await;
+        self.cache.pop(&address);
+        response
+    }
+
+    /// Returns the cached messages of the [`Address`], querying the inner [`Transport`] if they are
+    /// not cached. Errors with [`Error::NotFound`] if no messages are found.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<Msg>> {
+        if let Some(outcome) = self.cached(address) {
+            return outcome;
+        }
+        let outcome = self.inner.recv_messages(address).await;
+        self.store(address, &outcome);
+        outcome
+    }
+
+    /// Returns the cached messages of the addresses, querying the inner [`Transport`] for the ones
+    /// that are not cached in a single batch.
+    ///
+    /// # Arguments
+    /// * `addresses`: The addresses of the messages.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<Msg>>> {
+        let mut outcomes: Vec<Option<Result<Vec<Msg>>>> =
+            addresses.iter().map(|address| self.cached(*address)).collect();
+        let misses: Vec<Address> = addresses
+            .iter()
+            .zip(&outcomes)
+            .filter(|(_, outcome)| outcome.is_none())
+            .map(|(address, _)| *address)
+            .collect();
+
+        let mut fetched = self.inner.recv_batch(&misses).await.into_iter();
+        for (address, outcome) in addresses.iter().zip(&mut outcomes) {
+            if outcome.is_none() {
+                *outcome = fetched.next();
+                if let Some(fetched) = outcome {
+                    self.store(*address, fetched);
+                }
+            }
+        }
+        outcomes.into_iter().flatten().collect()
+    }
+
+    /// Subscribes to new messages through the inner [`Transport`]. Notifications bypass the cache.
+    ///
+    /// # Arguments
+    /// * `subscription`: The addresses to be notified of.
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<Msg>> {
+        self.inner.subscribe(subscription).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        address::{AppAddr, MsgId},
+        id::Identifier,
+        message::Topic,
+        transport::bucket,
+    };
+
+    use super::*;
+
+    /// Transport counting the `recv_messages` calls reaching it
+    #[derive(Default)]
+    struct Counting {
+        inner: bucket::Client,
+        recvs: usize,
+    }
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl Transport<'_> for Counting {
+        type Msg = TransportMessage;
+        type SendResponse = TransportMessage;
+
+        async fn send_message(&mut self, address: Address, msg: TransportMessage) -> Result<TransportMessage>
+        where
+            Self::Msg: 'async_trait,
+        {
+            self.inner.send_message(address, msg).await
+        }
+
+        async fn recv_messages(&mut self, address: Address) -> Result<Vec<TransportMessage>> {
+            self.recvs += 1;
+            self.inner.recv_messages(address).await
+        }
+    }
+
+    fn address(cursor: usize) -> Address {
+        Address::new(
+            AppAddr::default(),
+            MsgId::gen(AppAddr::default(), &Identifier::default(), &Topic::default(), cursor),
+        )
+    }
+
+    #[tokio::test]
+    async fn found_messages_are_served_from_the_cache() -> Result<()> {
+        let mut transport = CachingTransport::new(Counting::default(), 1);
+        let msg = TransportMessage::new(vec![1; 32]);
+        transport.send_message(address(1), msg.clone()).await?;
+
+        assert_eq!(transport.recv_message(address(1)).await?, msg);
+        assert_eq!(transport.recv_message(address(1)).await?, msg);
+        assert_eq!(transport.inner().recvs, 1);
+
+        // The least recently used address is evicted once the capacity is exceeded
+        assert!(transport.recv_message(address(2)).await.is_err());
+        assert_eq!(transport.recv_message(address(1)).await?, msg);
+        assert_eq!(transport.inner().recvs, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn not_found_entries_expire_and_are_evicted_by_sends() -> Result<()> {
+        let mut transport = CachingTransport::new(Counting::default(), 8).with_not_found_ttl(Duration::from_secs(60));
+        assert!(transport.recv_messages(address(1)).await.unwrap_err().is_not_found());
+        assert!(transport.recv_messages(address(1)).await.unwrap_err().is_not_found());
+        assert_eq!(transport.inner().recvs, 1);
+
+        let msg = TransportMessage::new(vec![1; 32]);
+        transport.send_message(address(1), msg.clone()).await?;
+        assert_eq!(transport.recv_message(address(1)).await?, msg);
+        assert_eq!(transport.inner().recvs, 2);
+
+        let mut transport = CachingTransport::new(Counting::default(), 8).with_not_found_ttl(Duration::ZERO);
+        assert!(transport.recv_messages(address(1)).await.is_err());
+        assert!(transport.recv_messages(address(1)).await.is_err());
+        assert_eq!(transport.inner().recvs, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn found_entries_expire_to_serve_messages_published_since() -> Result<()> {
+        let spam = TransportMessage::new(vec![0; 32]);
+        let msg = TransportMessage::new(vec![1; 32]);
+        for (found_ttl, expected) in [
+            (Duration::from_secs(60), vec![spam.clone()]),
+            (Duration::ZERO, vec![spam.clone(), msg.clone()]),
+        ] {
+            let mut transport = CachingTransport::new(Counting::default(), 8).with_found_ttl(found_ttl);
+            transport.send_message(address(1), spam.clone()).await?;
+            assert_eq!(transport.recv_messages(address(1)).await?, vec![spam.clone()]);
+
+            // Published by another writer, bypassing the cache
+            transport.inner_mut().send_message(address(1), msg.clone()).await?;
+            assert_eq!(transport.recv_messages(address(1)).await?, expected);
+        }
+        Ok(())
+    }
+}
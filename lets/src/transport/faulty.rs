@@ -0,0 +1,363 @@
+// Rust
+use alloc::{boxed::Box, vec::Vec};
+
+// 3rd-party
+use async_trait::async_trait;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{
+    address::Address,
+    error::{Error, Result},
+    message::TransportMessage,
+    sync::MaybeSend,
+    transport::{Notifications, Subscription, Transport},
+};
+
+/// Size of the spam messages injected at addresses with no message
+const SPAM_SIZE: usize = 64;
+
+/// Misbehaviour a [`FaultyTransport`] can inject in the operations of its inner [`Transport`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// The sent message is silently discarded
+    Drop,
+    /// The sent message is withheld from the inner transport during the provided number of
+    /// operations
+    Delay(usize),
+    /// The sent message is stored twice
+    Duplicate,
+    /// The received messages are shuffled
+    Reorder,
+    /// One bit of one of the received messages is flipped
+    Corrupt,
+    /// A message of random bytes is added to the received messages
+    Spam,
+}
+
+impl Fault {
+    /// Returns whether the [`Fault`] is injected when sending messages, as opposed to when
+    /// receiving them
+    fn on_send(&self) -> bool {
+        matches!(self, Fault::Drop | Fault::Delay(_) | Fault::Duplicate)
+    }
+}
+
+/// Injection rule of a [`Fault`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    /// Injected [`Fault`]
+    fault: Fault,
+    /// Probability of the [`Fault`] being injected in an operation
+    probability: f64,
+    /// Address the rule is restricted to, if any
+    address: Option<Address>,
+}
+
+impl Rule {
+    /// Creates a [`Rule`] injecting the [`Fault`] in the operations at any [`Address`] with the
+    /// provided probability
+    ///
+    /// # Arguments
+    /// * `fault`: Injected fault
+    /// * `probability`: Probability of injecting the fault in an operation, clamped to `0..=1`
+    pub fn new(fault: Fault, probability: f64) -> Self {
+        Self {
+            fault,
+            probability: probability.clamp(0.0, 1.0),
+            address: None,
+        }
+    }
+
+    /// Restricts the [`Rule`] to the operations at the provided [`Address`]
+    ///
+    /// # Arguments
+    /// * `address`: The address the rule applies to
+    pub fn at(mut self, address: Address) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Returns whether the [`Rule`] applies to operations at the [`Address`]
+    fn applies_to(&self, address: Address) -> bool {
+        self.address.is_none() || self.address == Some(address)
+    }
+}
+
+/// A test-oriented [`Transport`] wrapper injecting faults in the operations of the inner
+/// [`Transport`], to exercise the handling of unreliable networks.
+///
+/// Every operation rolls the [`Rule`] set, in the order the rules were added, with a random number
+/// generator seeded at creation: the same seed, rules and sequence of operations always inject
+/// the same faults.
+///
+/// ```
+/// # use lets::transport::{bucket, faulty::{Fault, FaultyTransport, Rule}};
+/// let transport: FaultyTransport<bucket::Client> =
+///     FaultyTransport::new(bucket::Client::new(), 42)
+///         .with_rule(Rule::new(Fault::Drop, 0.1))
+///         .with_rule(Rule::new(Fault::Reorder, 0.5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FaultyTransport<Tsp> {
+    /// Wrapped [`Transport`]
+    inner: Tsp,
+    /// Seeded source of the fault injection decisions
+    rng: StdRng,
+    /// Fault injection rules
+    rules: Vec<Rule>,
+    /// Withheld messages, with the number of operations left before they are sent
+    delayed: Vec<(Address, TransportMessage, usize)>,
+    /// Log of the injected faults
+    injected: Vec<(Address, Fault)>,
+}
+
+impl<Tsp> FaultyTransport<Tsp> {
+    /// Wraps the [`Transport`] in a [`FaultyTransport`] with no rules
+    ///
+    /// # Arguments
+    /// * `inner`: The [`Transport`] to inject faults in
+    /// * `seed`: Seed of the fault injection decisions
+    pub fn new(inner: Tsp, seed: u64) -> Self {
+        Self {
+            inner,
+            rng: StdRng::seed_from_u64(seed),
+            rules: Vec::new(),
+            delayed: Vec::new(),
+            injected: Vec::new(),
+        }
+    }
+
+    /// Adds a fault injection [`Rule`]
+    ///
+    /// # Arguments
+    /// * `rule`: The rule to add
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Returns the faults injected so far, with the [`Address`] of the operation they were injected
+    /// in
+    pub fn injected(&self) -> &[(Address, Fault)] {
+        &self.injected
+    }
+
+    /// Returns a reference to the wrapped [`Transport`]
+    pub fn inner(&self) -> &Tsp {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped [`Transport`]
+    pub fn inner_mut(&mut self) -> &mut Tsp {
+        &mut self.inner
+    }
+
+    /// Consumes the [`FaultyTransport`], returning the wrapped [`Transport`]. Withheld messages are
+    /// discarded.
+    pub fn into_inner(self) -> Tsp {
+        self.inner
+    }
+
+    /// Rolls the rules applying to an operation at the [`Address`], logging and returning the
+    /// injected faults
+    ///
+    /// # Arguments
+    /// * `address`: The address of the operation
+    /// * `on_send`: Whether the operation is a send or a reception
+    fn roll(&mut self, address: Address, on_send: bool) -> Vec<Fault> {
+        let mut faults = Vec::new();
+        for rule in &self.rules {
+            if rule.fault.on_send() == on_send && rule.applies_to(address) && self.rng.gen_bool(rule.probability) {
+                faults.push(rule.fault);
+                self.injected.push((address, rule.fault));
+            }
+        }
+        faults
+    }
+}
+
+impl<'a, Tsp> FaultyTransport<Tsp>
+where
+    Tsp: Transport<'a, Msg = TransportMessage, SendResponse = TransportMessage>,
+{
+    /// Counts an operation down for every withheld message, sending the ones that are due to the
+    /// inner [`Transport`]
+    async fn release_delayed(&mut self) -> Result<()> {
+        let mut i = 0;
+        while i < self.delayed.len() {
+            match &mut self.delayed[i] {
+                (_, _, 0) => {
+                    let (address, msg, _) = self.delayed.remove(i);
+                    self.inner.send_message(address, msg).await?;
+                }
+                (_, _, operations_left) => {
+                    *operations_left -= 1;
+                    i += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, Tsp> Transport<'a> for FaultyTransport<Tsp>
+where
+    Tsp: Transport<'a, Msg = TransportMessage, SendResponse = TransportMessage> + MaybeSend,
+{
+    type Msg = TransportMessage;
+    type SendResponse = TransportMessage;
+
+    /// Sends a message through the inner [`Transport`], unless it is dropped or delayed.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message.
+    /// * `msg`: The message to send.
+    ///
+    /// Returns:
+    /// The message that was sent, or would have been if it was not dropped or delayed.
+    async fn send_message(&mut self, address: Address, msg: TransportMessage) -> Result<TransportMessage>
+    where
+        Self::Msg: 'async_trait,
+    {
+        self.release_delayed().await?;
+        let faults = self.roll(address, true);
+        if faults.contains(&Fault::Drop) {
+            return Ok(msg);
+        }
+        if let Some(operations) = faults.iter().find_map(|fault| match fault {
+            Fault::Delay(operations) => Some(*operations),
+            _ => None,
+        }) {
+            self.delayed.push((address, msg.clone(), operations));
+            return Ok(msg);
+        }
+        if faults.contains(&Fault::Duplicate) {
+            self.inner.send_message(address, msg.clone()).await?;
+        }
+        self.inner.send_message(address, msg).await
+    }
+
+    /// Receives the messages at the [`Address`] from the inner [`Transport`], reordering,
+    /// corrupting or adding spam to them. Errors with [`Error::NotFound`] if no messages are found.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<TransportMessage>> {
+        self.release_delayed().await?;
+        let mut msgs = match self.inner.recv_messages(address).await {
+            Ok(msgs) => msgs,
+            Err(e) if e.is_not_found() => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        for fault in self.roll(address, false) {
+            match fault {
+                Fault::Reorder => msgs.shuffle(&mut self.rng),
+                Fault::Corrupt if !msgs.is_empty() => {
+                    let i = self.rng.gen_range(0..msgs.len());
+                    let mut body = msgs[i].body().clone();
+                    if !body.is_empty() {
+                        let byte = self.rng.gen_range(0..body.len());
+                        body[byte] ^= 1 << self.rng.gen_range(0..8);
+                    }
+                    msgs[i] = TransportMessage::new(body);
+                }
+                Fault::Spam => {
+                    let size = msgs.first().map_or(SPAM_SIZE, |msg| msg.as_ref().len());
+                    let spam = (0..size).map(|_| self.rng.gen()).collect();
+                    msgs.push(TransportMessage::new(spam));
+                }
+                _ => (),
+            }
+        }
+        match msgs.is_empty() {
+            true => Err(Error::NotFound(address)),
+            false => Ok(msgs),
+        }
+    }
+
+    /// Subscribes to new messages through the inner [`Transport`]. No faults are injected in the
+    /// notifications, but dropped and delayed messages are only notified if and when they reach
+    /// the inner [`Transport`].
+    ///
+    /// # Arguments
+    /// * `subscription`: The addresses to be notified of.
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<TransportMessage>> {
+        self.inner.subscribe(subscription).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        address::{AppAddr, MsgId},
+        id::Identifier,
+        message::Topic,
+        transport::bucket,
+    };
+
+    use super::*;
+
+    fn address(cursor: usize) -> Address {
+        Address::new(
+            AppAddr::default(),
+            MsgId::gen(AppAddr::default(), &Identifier::default(), &Topic::default(), cursor),
+        )
+    }
+
+    #[tokio::test]
+    async fn send_faults_drop_delay_and_duplicate_messages() -> Result<()> {
+        let mut transport = FaultyTransport::new(bucket::Client::new(), 0)
+            .with_rule(Rule::new(Fault::Drop, 1.0).at(address(1)))
+            .with_rule(Rule::new(Fault::Delay(1), 1.0).at(address(2)))
+            .with_rule(Rule::new(Fault::Duplicate, 1.0).at(address(3)));
+        let msg = TransportMessage::new(vec![1; 32]);
+        for cursor in 1..=3 {
+            transport.send_message(address(cursor), msg.clone()).await?;
+        }
+
+        assert!(transport.recv_message(address(1)).await.unwrap_err().is_not_found());
+        assert!(matches!(transport.recv_message(address(3)).await, Err(Error::Spam(_))));
+        // The delayed message is released after one more operation
+        assert_eq!(transport.recv_message(address(2)).await?, msg);
+        assert_eq!(
+            transport.injected(),
+            &[
+                (address(1), Fault::Drop),
+                (address(2), Fault::Delay(1)),
+                (address(3), Fault::Duplicate)
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn receive_faults_are_reproducible_from_the_seed() -> Result<()> {
+        let faulty_transport = |seed| {
+            FaultyTransport::new(bucket::Client::new(), seed)
+                .with_rule(Rule::new(Fault::Reorder, 1.0))
+                .with_rule(Rule::new(Fault::Corrupt, 0.5))
+                .with_rule(Rule::new(Fault::Spam, 0.5))
+        };
+        let msgs: Vec<TransportMessage> = (0..8).map(|i| TransportMessage::new(vec![i; 32])).collect();
+
+        let mut outcomes = Vec::new();
+        for _ in 0..2 {
+            let mut transport = faulty_transport(7);
+            for msg in &msgs {
+                transport.send_message(address(1), msg.clone()).await?;
+            }
+            let received = transport.recv_messages(address(1)).await?;
+            assert_ne!(received, msgs);
+            outcomes.push((received, transport.injected().to_vec()));
+        }
+        assert_eq!(outcomes[0], outcomes[1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+// Rust
+extern crate std;
+
+use alloc::{boxed::Box, format, vec::Vec};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    ffi::OsStr,
+    fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// 3rd-party
+use async_trait::async_trait;
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{
+    address::Address,
+    error::{Error, Result},
+    message::TransportMessage,
+    transport::Transport,
+};
+
+/// File extension of stored messages
+const MESSAGE_EXTENSION: &str = "msg";
+
+/// Discriminates temporary files written concurrently by the same process
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A [`Transport`] Client persisting messages to the local filesystem, so that several processes
+/// on the same machine can share a stream without a Tangle node.
+///
+/// Messages are stored under `<root>/<AppAddr>/<MsgId>/`, one file per message, named after the
+/// order in which they were sent to the [`Address`]. Each message is first written to a temporary
+/// file and then linked into place, so readers never observe partially written messages and
+/// concurrent writers never overwrite each other.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Client {
+    /// Directory under which messages are stored
+    root: PathBuf,
+}
+
+impl Client {
+    /// Creates a new filesystem [`Client`] storing messages under the provided directory. The
+    /// directory is created on the first message sent if it does not exist yet.
+    ///
+    /// # Arguments
+    /// * `root`: Directory under which messages are stored
+    pub fn new<P>(root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self { root: root.into() }
+    }
+
+    /// Returns the directory under which messages are stored
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the directory in which the messages of an [`Address`] are stored
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the messages
+    fn address_dir(&self, address: Address) -> PathBuf {
+        self.root
+            .join(format!("{:x}", address.base()))
+            .join(format!("{:x}", address.relative()))
+    }
+
+    /// Writes the message into a temporary file of the directory, flushing it to disk
+    ///
+    /// # Arguments
+    /// * `dir`: The directory of the [`Address`] the message is sent to
+    /// * `msg`: The message to be written
+    fn write_tmp(dir: &Path, msg: &TransportMessage) -> io::Result<PathBuf> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.subsec_nanos())
+            .unwrap_or_default();
+        let tmp_path = dir.join(format!(
+            ".{}-{}-{}.tmp",
+            process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos
+        ));
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(&tmp_path)?;
+        file.write_all(msg.as_ref())?;
+        file.sync_all()?;
+        Ok(tmp_path)
+    }
+
+    /// Links the temporary file into the first free message slot of the directory. Linking fails
+    /// if the slot is already taken, which makes the reservation atomic across processes.
+    ///
+    /// # Arguments
+    /// * `dir`: The directory of the [`Address`] the message is sent to
+    /// * `tmp_path`: The temporary file containing the message
+    fn commit_tmp(dir: &Path, tmp_path: &Path) -> io::Result<()> {
+        let mut index = Self::message_paths(dir)?.len();
+        loop {
+            let path = dir.join(format!("{:010}.{}", index, MESSAGE_EXTENSION));
+            match fs::hard_link(tmp_path, &path) {
+                Ok(()) => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => index += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns the paths of the messages stored in the directory, in the order they were sent
+    ///
+    /// # Arguments
+    /// * `dir`: The directory of the [`Address`] the messages were sent to
+    fn message_paths(dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        paths.retain(|path| path.extension() == Some(OsStr::new(MESSAGE_EXTENSION)));
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl Transport<'_> for Client {
+    type Msg = TransportMessage;
+    type SendResponse = TransportMessage;
+
+    /// Atomically stores the message in the directory of the [`Address`], next to any message
+    /// previously sent to it.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message to store.
+    /// * `msg`: The message to store.
+    ///
+    /// Returns:
+    /// The message that was sent.
+    async fn send_message(&mut self, address: Address, msg: TransportMessage) -> Result<TransportMessage>
+    where
+        Self::Msg: 'async_trait,
+    {
+        let dir = self.address_dir(address);
+        fs::create_dir_all(&dir).map_err(|e| Error::Io("create message directory", e))?;
+        let tmp_path = Self::write_tmp(&dir, &msg).map_err(|e| Error::Io("write message", e))?;
+        let committed = Self::commit_tmp(&dir, &tmp_path).map_err(|e| Error::Io("store message", e));
+        // The temporary file is no longer needed whether the message was linked into place or not
+        fs::remove_file(&tmp_path).map_err(|e| Error::Io("remove temporary message file", e))?;
+        committed?;
+        Ok(msg)
+    }
+
+    /// Returns the messages stored for the [`Address`] in the order they were sent, or an error if
+    /// there are none.
+    ///
+    /// # Arguments
+    /// * `address`: The address to retrieve messages from.
+    ///
+    /// Returns:
+    /// A vector of messages.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<TransportMessage>> {
+        let paths = match Self::message_paths(&self.address_dir(address)) {
+            Ok(paths) => paths,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(Error::Io("list messages", e)),
+        };
+        if paths.is_empty() {
+            return Err(Error::NotFound(address));
+        }
+        paths
+            .iter()
+            .map(|path| {
+                fs::read(path)
+                    .map(TransportMessage::new)
+                    .map_err(|e| Error::Io("read message", e))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        address::{AppAddr, MsgId},
+        id::Identifier,
+        message::Topic,
+    };
+
+    use super::*;
+
+    /// Creates a client rooted in a fresh directory of the system temporary directory
+    fn tmp_client(name: &str) -> Client {
+        let root = std::env::temp_dir().join(format!("lets-fs-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&root);
+        Client::new(root)
+    }
+
+    fn address(cursor: usize) -> Address {
+        Address::new(
+            AppAddr::default(),
+            MsgId::gen(AppAddr::default(), &Identifier::default(), &Topic::default(), cursor),
+        )
+    }
+
+    #[tokio::test]
+    async fn messages_persist_across_clients() -> Result<()> {
+        let mut client = tmp_client("persist");
+        let msg = TransportMessage::new(vec![12; 1024]);
+        client.send_message(address(1), msg.clone()).await?;
+
+        let mut other_client = Client::new(client.root());
+        assert_eq!(other_client.recv_message(address(1)).await?, msg);
+        assert!(other_client.recv_message(address(2)).await.is_err());
+
+        fs::remove_dir_all(client.root()).map_err(|e| Error::Io("clean up", e))?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn several_messages_at_one_address_are_kept_in_order() -> Result<()> {
+        let mut client = tmp_client("order");
+        let msgs: Vec<TransportMessage> = (0..3).map(|i| TransportMessage::new(vec![i; 16])).collect();
+        for msg in &msgs {
+            client.send_message(address(1), msg.clone()).await?;
+        }
+
+        assert_eq!(client.recv_messages(address(1)).await?, msgs);
+        assert!(client.recv_message(address(1)).await.is_err());
+
+        fs::remove_dir_all(client.root()).map_err(|e| Error::Io("clean up", e))?;
+        Ok(())
+    }
+}
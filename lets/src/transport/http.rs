@@ -0,0 +1,216 @@
+// Rust
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::str::FromStr;
+
+// 3rd-party
+use async_trait::async_trait;
+use futures::{future::join_all, stream};
+use reqwest::StatusCode;
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{
+    address::Address,
+    error::{Error, Result},
+    message::TransportMessage,
+    transport::{Notifications, Subscription, Transport},
+};
+
+/// A [`Transport`] Client for sending and retrieving binary messages through a relay server.
+///
+/// The relay stores the messages in the [`Transport`] it was started with, and exposes them under
+/// `/messages/<address>`: a `POST` request stores the raw request body as a message, while a `GET`
+/// request returns a JSON array with every message stored at the address, hex encoded.
+///
+/// New messages are pushed by the relay as server-sent events under `/notifications`, each event
+/// carrying the address and the hex encoded message separated by a space.
+#[derive(Debug, Clone)]
+pub struct Client {
+    /// Relay endpoint URL
+    relay_url: String,
+    /// HTTP Client
+    client: reqwest::Client,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new("http://localhost:8080")
+    }
+}
+
+impl Client {
+    /// Creates a new HTTP [`Client`] for the relay listening at the provided URL
+    ///
+    /// # Arguments:
+    /// * `relay_url`: Relay endpoint
+    pub fn new<U>(relay_url: U) -> Self
+    where
+        U: Into<String>,
+    {
+        Self {
+            relay_url: relay_url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Returns the relay URL of the messages stored at the [`Address`]
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages
+    fn messages_url(&self, address: Address) -> String {
+        format!("{}/messages/{}", self.relay_url.trim_end_matches('/'), address)
+    }
+
+    /// Returns the relay URL of the notifications of the [`Subscription`]
+    ///
+    /// # Arguments
+    /// * `subscription`: The addresses to be notified of
+    fn notifications_url(&self, subscription: &Subscription) -> String {
+        let query = match subscription {
+            Subscription::Stream(appaddr) => format!("stream={}", appaddr),
+            Subscription::Addresses(addresses) => {
+                let addresses: Vec<String> = addresses.iter().map(|address| address.to_string()).collect();
+                format!("addresses={}", addresses.join(","))
+            }
+        };
+        format!("{}/notifications?{}", self.relay_url.trim_end_matches('/'), query)
+    }
+
+    /// Retrieves the messages indexed at the provided [`Address`] from the relay. Takes `&self` so
+    /// that several addresses can be fetched concurrently.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages to retrieve
+    async fn get_messages(&self, address: Address) -> Result<Vec<TransportMessage>> {
+        let response = self
+            .client
+            .get(self.messages_url(address))
+            .send()
+            .await
+            .map_err(|e| Error::request("receive messages from relay", e))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(Error::NotFound(address));
+        }
+        let msgs: Vec<String> = response
+            .error_for_status()
+            .map_err(|e| Error::request("receive messages from relay", e))?
+            .json()
+            .await
+            .map_err(|e| Error::request("decode messages from relay", e))?;
+        msgs.into_iter()
+            .map(|msg| Ok(TransportMessage::new(hex::decode(msg)?)))
+            .collect()
+    }
+}
+
+/// Parses a server-sent event of the relay. Returns `None` for events carrying no message, such as
+/// keep-alive comments.
+///
+/// # Arguments
+/// * `event`: The lines of the event
+fn parse_event(event: &str) -> Result<Option<(Address, TransportMessage)>> {
+    match event.lines().find_map(|line| line.strip_prefix("data: ")) {
+        Some(data) => {
+            let (address, msg) =
+                data.split_once(' ')
+                    .ok_or(Error::Malformed("relay notification", " ", data.into()))?;
+            Ok(Some((
+                Address::from_str(address)?,
+                TransportMessage::new(hex::decode(msg)?),
+            )))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl Transport<'_> for Client {
+    type Msg = TransportMessage;
+    type SendResponse = TransportMessage;
+
+    /// Sends a message indexed at the provided [`Address`] to the relay.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message.
+    /// * `msg`: The message to send.
+    ///
+    /// Returns:
+    /// The message that was sent.
+    async fn send_message(&mut self, address: Address, msg: TransportMessage) -> Result<TransportMessage>
+    where
+        Self::Msg: 'async_trait,
+    {
+        self.client
+            .post(self.messages_url(address))
+            .header("Content-Type", "application/octet-stream")
+            .body(msg.as_ref().to_vec())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::request("send message to relay", e))?;
+        Ok(msg)
+    }
+
+    /// Retrieves the messages indexed at the provided [`Address`] from the relay. Errors with
+    /// [`Error::NotFound`] if no messages are found.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages to retrieve.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<TransportMessage>> {
+        self.get_messages(address).await
+    }
+
+    /// Retrieves the messages indexed at the provided addresses from the relay, sending all the
+    /// requests at once.
+    ///
+    /// # Arguments
+    /// * `addresses`: The addresses of the messages to retrieve.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<TransportMessage>>> {
+        join_all(addresses.iter().map(|address| self.get_messages(*address))).await
+    }
+
+    /// Opens a stream of the messages sent to the relay from now on at the addresses of the
+    /// [`Subscription`]. The stream ends when the relay shuts down.
+    ///
+    /// # Arguments
+    /// * `subscription`: The addresses to be notified of.
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<TransportMessage>> {
+        let response = self
+            .client
+            .get(self.notifications_url(&subscription))
+            .header("Accept", "text/event-stream")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::request("subscribe to relay", e))?;
+
+        let notifications = stream::try_unfold((response, String::new()), |(mut response, mut buffer)| async move {
+            loop {
+                if let Some(end) = buffer.find("\n\n") {
+                    let event: String = buffer.drain(..end + 2).collect();
+                    match parse_event(&event)? {
+                        Some(notification) => return Ok(Some((notification, (response, buffer)))),
+                        None => continue,
+                    }
+                }
+                match response
+                    .chunk()
+                    .await
+                    .map_err(|e| Error::request("receive notifications from relay", e))?
+                {
+                    Some(chunk) => buffer.push_str(&String::from_utf8_lossy(&chunk)),
+                    None => return Ok(None),
+                }
+            }
+        });
+        Ok(Box::pin(notifications))
+    }
+}
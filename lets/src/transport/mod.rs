@@ -1,9 +1,18 @@
 // Rust
-use alloc::{boxed::Box, rc::Rc, vec::Vec};
+#[cfg(not(feature = "send"))]
+use alloc::rc::Rc;
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+use alloc::{boxed::Box, vec::Vec};
+#[cfg(not(feature = "send"))]
 use core::cell::RefCell;
+use core::pin::Pin;
 
 // 3rd-party
 use async_trait::async_trait;
+#[cfg(feature = "std")]
+use futures::lock::Mutex;
+use futures::Stream;
 
 // IOTA
 
@@ -11,41 +20,110 @@ use async_trait::async_trait;
 
 // Local
 use crate::{
-    address::Address,
+    address::{Address, AppAddr},
     error::{Error, Result},
+    sync::MaybeSend,
 };
 
+/// Set of addresses a [`Transport::subscribe`] call is notified of
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Subscription {
+    /// Messages sent to any of the provided addresses
+    Addresses(Vec<Address>),
+    /// Messages sent to any address of the stream
+    Stream(AppAddr),
+}
+
+impl Subscription {
+    /// Returns whether a message sent to the [`Address`] is notified to the [`Subscription`]
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message
+    pub fn matches(&self, address: Address) -> bool {
+        match self {
+            Subscription::Addresses(addresses) => addresses.contains(&address),
+            Subscription::Stream(appaddr) => address.base() == *appaddr,
+        }
+    }
+}
+
+/// Stream of the messages notified to a [`Subscription`], along with their [`Address`]
+#[cfg(not(feature = "send"))]
+pub type Notifications<Msg> = Pin<Box<dyn Stream<Item = Result<(Address, Msg)>>>>;
+/// Stream of the messages notified to a [`Subscription`], along with their [`Address`]
+#[cfg(feature = "send")]
+pub type Notifications<Msg> = Pin<Box<dyn Stream<Item = Result<(Address, Msg)>> + Send>>;
+
 /// Network transport abstraction.
 /// Parametrized by the type of message addresss.
 /// Message address is used to identify/locate a message (eg. like URL for HTTP).
-#[async_trait(?Send)]
+///
+/// Implementations categorize their failures so that callers can tell a missing message apart from
+/// a failure to reach the transport:
+/// * [`Error::NotFound`]: no message is stored at the address
+/// * [`Error::Timeout`]: the transport did not answer in time
+/// * [`Error::Network`]: the transport could not be reached or failed to process the request
+/// * [`Error::Spam`]: several messages are stored at an address expected to hold only one
+///
+/// Transports able to push new messages implement [`Transport::subscribe`], so that readers can
+/// wait for new messages instead of polling the addresses they expect them at.
+///
+/// With the `send` feature enabled the futures returned by the trait methods, as well as the
+/// messages, are required to be `Send`, so that they can be spawned on multi-threaded executors.
+/// Transports can be shared through an `Arc<Mutex<T>>` with or without the feature, whereas an
+/// `Rc<RefCell<T>>`, which cannot be sent to another thread, is only a [`Transport`] without it.
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 pub trait Transport<'a> {
-    type Msg;
+    type Msg: MaybeSend;
     type SendResponse;
     /// Send a message
     async fn send_message(&mut self, address: Address, msg: Self::Msg) -> Result<Self::SendResponse>
     where
         'a: 'async_trait;
 
-    /// Receive messages
+    /// Receive messages. Errors with [`Error::NotFound`] if there are none.
     async fn recv_messages(&mut self, address: Address) -> Result<Vec<Self::Msg>>
     where
         'a: 'async_trait;
 
-    /// Receive a single message
+    /// Receive a single message. Errors with [`Error::Spam`] if more than one message is found.
     async fn recv_message(&mut self, address: Address) -> Result<Self::Msg> {
         let mut msgs = self.recv_messages(address).await?;
         if let Some(msg) = msgs.pop() {
             match msgs.is_empty() {
                 true => Ok(msg),
-                false => Err(Error::AddressError("More than one found", address)),
+                false => Err(Error::Spam(address)),
             }
         } else {
-            Err(Error::AddressError("not found in transport", address))
+            Err(Error::NotFound(address))
         }
     }
+
+    /// Receive the messages of several addresses, returning one outcome per address in the same
+    /// order. Transports able to perform several requests at once override this method to fetch the
+    /// addresses concurrently, the default implementation fetches them one after the other.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<Self::Msg>>>
+    where
+        'a: 'async_trait,
+    {
+        let mut outcomes = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            outcomes.push(self.recv_messages(*address).await);
+        }
+        outcomes
+    }
+
+    /// Subscribe to the messages sent from now on to the addresses of the [`Subscription`]. Errors
+    /// with [`Error::Unsupported`] if the transport cannot notify new messages, in which case they
+    /// must be polled with [`Transport::recv_messages`].
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<Self::Msg>> {
+        let _ = subscription;
+        Err(Error::Unsupported("subscribe to new messages"))
+    }
 }
 
+#[cfg(not(feature = "send"))]
 #[async_trait(?Send)]
 impl<'a, Tsp: Transport<'a>> Transport<'a> for Rc<RefCell<Tsp>> {
     type Msg = Tsp::Msg;
@@ -63,10 +141,82 @@ impl<'a, Tsp: Transport<'a>> Transport<'a> for Rc<RefCell<Tsp>> {
     async fn recv_messages(&mut self, address: Address) -> Result<Vec<Tsp::Msg>> {
         self.borrow_mut().recv_messages(address).await
     }
+
+    /// Receive the messages of several addresses.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<Tsp::Msg>>>
+    where
+        Tsp::Msg: MaybeSend,
+    {
+        self.borrow_mut().recv_batch(addresses).await
+    }
+
+    /// Subscribe to new messages.
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<Tsp::Msg>> {
+        self.borrow_mut().subscribe(subscription).await
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, Tsp, Msg, SendResponse> Transport<'a> for Arc<Mutex<Tsp>>
+where
+    Tsp: Transport<'a, Msg = Msg, SendResponse = SendResponse> + MaybeSend,
+    Msg: MaybeSend + 'a,
+{
+    type Msg = Msg;
+    type SendResponse = SendResponse;
+
+    /// Send a message.
+    async fn send_message(&mut self, address: Address, msg: Msg) -> Result<SendResponse>
+    where
+        'a: 'async_trait,
+    {
+        self.lock().await.send_message(address, msg).await
+    }
+
+    /// Receive messages with default options.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<Msg>> {
+        self.lock().await.recv_messages(address).await
+    }
+
+    /// Receive the messages of several addresses.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<Msg>>>
+    where
+        'a: 'async_trait,
+    {
+        self.lock().await.recv_batch(addresses).await
+    }
+
+    /// Subscribe to new messages.
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<Msg>> {
+        self.lock().await.subscribe(subscription).await
+    }
 }
 
 /// Localised mapping for tests and simulations
 pub mod bucket;
+/// Read-through caching wrapper of another transport
+#[cfg(feature = "cache-client")]
+pub mod cache;
+/// Fault-injecting wrapper of another transport for resilience testing
+#[cfg(feature = "faulty-client")]
+pub mod faulty;
+/// Filesystem persisted client for local multi-process setups
+#[cfg(feature = "fs-client")]
+pub mod fs;
+/// Relay server client
+#[cfg(feature = "http-client")]
+pub mod http;
+/// Combinator spreading operations over several transports
+#[cfg(feature = "multi-client")]
+pub mod multi;
+/// Wrapper retrying transient failures of another transport
+#[cfg(feature = "retry-client")]
+pub mod retry;
+/// Embedded `SQLite` ledger client
+#[cfg(feature = "sqlite-client")]
+pub mod sqlite;
 /// `iota.rs` based tangle client
 #[cfg(any(feature = "tangle-client", feature = "tangle-client-wasm"))]
 pub mod tangle;
@@ -0,0 +1,370 @@
+// Rust
+use alloc::{boxed::Box, vec::Vec};
+
+// 3rd-party
+use async_trait::async_trait;
+use futures::{future::join_all, stream::select_all};
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{
+    address::Address,
+    error::{Error, Result},
+    sync::MaybeSend,
+    transport::{Notifications, Subscription, Transport},
+};
+
+/// How a [`MultiTransport`] distributes the operations among its backends
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Operations are sent to one backend at a time, moving on to the next one when it fails
+    Failover,
+    /// Messages are sent to every backend at once, receptions fail over like in
+    /// [`Strategy::Failover`]
+    Broadcast,
+    /// Messages are sent to every backend at once and must be accepted by at least the provided
+    /// number of them. Receptions query every backend and only return the messages returned by at
+    /// least that number of backends.
+    Quorum(usize),
+}
+
+/// A [`Transport`] combinator spreading the operations over several backends, so that the failure
+/// of a single node does not interrupt the stream.
+///
+/// ```
+/// # use lets::transport::{bucket, multi::MultiTransport};
+/// let transport = MultiTransport::quorum(
+///     vec![
+///         bucket::Client::<Vec<u8>>::new(),
+///         bucket::Client::new(),
+///         bucket::Client::new(),
+///     ],
+///     2,
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MultiTransport<Tsp> {
+    /// Inner transports
+    backends: Vec<Tsp>,
+    /// Distribution of the operations among the backends
+    strategy: Strategy,
+    /// Index of the backend tried first when failing over, the last one that succeeded
+    preferred: usize,
+}
+
+impl<Tsp> MultiTransport<Tsp> {
+    /// Creates a [`MultiTransport`] distributing the operations among the backends following the
+    /// provided [`Strategy`]
+    ///
+    /// # Arguments
+    /// * `backends`: Inner transports, in the order they are failed over
+    /// * `strategy`: Distribution of the operations among the backends
+    pub fn new(backends: Vec<Tsp>, strategy: Strategy) -> Self {
+        let strategy = match strategy {
+            Strategy::Quorum(n) => Strategy::Quorum(n.max(1)),
+            strategy => strategy,
+        };
+        Self {
+            backends,
+            strategy,
+            preferred: 0,
+        }
+    }
+
+    /// Creates a [`MultiTransport`] using the [`Strategy::Failover`] strategy
+    ///
+    /// # Arguments
+    /// * `backends`: Inner transports, in the order they are failed over
+    pub fn failover(backends: Vec<Tsp>) -> Self {
+        Self::new(backends, Strategy::Failover)
+    }
+
+    /// Creates a [`MultiTransport`] using the [`Strategy::Broadcast`] strategy
+    ///
+    /// # Arguments
+    /// * `backends`: Inner transports, in the order they are failed over
+    pub fn broadcast(backends: Vec<Tsp>) -> Self {
+        Self::new(backends, Strategy::Broadcast)
+    }
+
+    /// Creates a [`MultiTransport`] using the [`Strategy::Quorum`] strategy
+    ///
+    /// # Arguments
+    /// * `backends`: Inner transports
+    /// * `quorum`: Number of backends that must agree for an operation to succeed
+    pub fn quorum(backends: Vec<Tsp>, quorum: usize) -> Self {
+        Self::new(backends, Strategy::Quorum(quorum))
+    }
+
+    /// Returns the [`Strategy`] of the [`MultiTransport`]
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// Returns a reference to the inner transports
+    pub fn backends(&self) -> &[Tsp] {
+        &self.backends
+    }
+
+    /// Returns a mutable reference to the inner transports
+    pub fn backends_mut(&mut self) -> &mut [Tsp] {
+        &mut self.backends
+    }
+
+    /// Consumes the [`MultiTransport`], returning the inner transports
+    pub fn into_backends(self) -> Vec<Tsp> {
+        self.backends
+    }
+
+    /// Returns the order in which the backends are failed over, starting from the preferred one
+    fn failover_order(&self) -> impl Iterator<Item = usize> {
+        let (preferred, len) = (self.preferred, self.backends.len());
+        (0..len).map(move |i| (preferred + i) % len)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, Tsp, Msg, SendResponse> Transport<'a> for MultiTransport<Tsp>
+where
+    Tsp: Transport<'a, Msg = Msg, SendResponse = SendResponse> + MaybeSend,
+    Msg: Clone + PartialEq + MaybeSend + 'static,
+    SendResponse: MaybeSend,
+{
+    type Msg = Msg;
+    type SendResponse = SendResponse;
+
+    /// Sends a message to the backends following the [`Strategy`] of the [`MultiTransport`].
+    ///
+    /// With [`Strategy::Failover`] the response of the first backend accepting the message is
+    /// returned. Otherwise the message is sent to every backend and the response of the first one
+    /// accepting it is returned, provided enough backends accepted it. If the message could not be
+    /// sent the error of the last failing backend is returned.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message.
+    /// * `msg`: The message to send.
+    async fn send_message(&mut self, address: Address, msg: Msg) -> Result<SendResponse>
+    where
+        'a: 'async_trait,
+    {
+        let required = match self.strategy {
+            Strategy::Failover => {
+                let mut last_error = Error::External(anyhow::anyhow!("no transport backend available"));
+                for i in self.failover_order().collect::<Vec<_>>() {
+                    match self.backends[i].send_message(address, msg.clone()).await {
+                        Ok(response) => {
+                            self.preferred = i;
+                            return Ok(response);
+                        }
+                        Err(e) => last_error = e,
+                    }
+                }
+                return Err(last_error);
+            }
+            Strategy::Broadcast => 1,
+            Strategy::Quorum(n) => n,
+        };
+
+        let mut accepted = 0;
+        let mut first_response = None;
+        let mut last_error = Error::External(anyhow::anyhow!("no transport backend available"));
+        let sends = self
+            .backends
+            .iter_mut()
+            .map(|backend| backend.send_message(address, msg.clone()));
+        for outcome in join_all(sends).await {
+            match outcome {
+                Ok(response) => {
+                    accepted += 1;
+                    first_response.get_or_insert(response);
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        match first_response {
+            Some(response) if accepted >= required => Ok(response),
+            _ => Err(last_error),
+        }
+    }
+
+    /// Receives the messages at the [`Address`] following the [`Strategy`] of the
+    /// [`MultiTransport`].
+    ///
+    /// With [`Strategy::Quorum`] every backend is queried and only the messages returned by enough
+    /// of them are returned. [`Error::NotFound`] is returned when enough backends answered but no
+    /// message reached the quorum, and the error of the last failing backend when too few backends
+    /// answered.
+    ///
+    /// Otherwise the backends are queried one at a time until one returns the messages. If none
+    /// does, [`Error::NotFound`] is returned if any backend reported the address empty, and the
+    /// error of the last failing backend otherwise.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<Msg>> {
+        let quorum = match self.strategy {
+            Strategy::Quorum(n) => n,
+            Strategy::Failover | Strategy::Broadcast => {
+                let mut not_found = None;
+                let mut last_error = Error::External(anyhow::anyhow!("no transport backend available"));
+                for i in self.failover_order().collect::<Vec<_>>() {
+                    match self.backends[i].recv_messages(address).await {
+                        Ok(msgs) => {
+                            self.preferred = i;
+                            return Ok(msgs);
+                        }
+                        Err(e) if e.is_not_found() => not_found = Some(e),
+                        Err(e) => last_error = e,
+                    }
+                }
+                return Err(not_found.unwrap_or(last_error));
+            }
+        };
+
+        let mut answered = 0;
+        let mut votes: Vec<(Msg, usize)> = Vec::new();
+        let mut last_error = Error::External(anyhow::anyhow!("no transport backend available"));
+        let recvs = self.backends.iter_mut().map(|backend| backend.recv_messages(address));
+        for outcome in join_all(recvs).await {
+            match outcome {
+                Ok(msgs) => {
+                    answered += 1;
+                    for (i, msg) in msgs.iter().enumerate() {
+                        // A backend only votes once for each message
+                        if msgs[..i].contains(msg) {
+                            continue;
+                        }
+                        match votes.iter_mut().find(|(voted, _)| voted == msg) {
+                            Some((_, count)) => *count += 1,
+                            None => votes.push((msg.clone(), 1)),
+                        }
+                    }
+                }
+                Err(e) if e.is_not_found() => answered += 1,
+                Err(e) => last_error = e,
+            }
+        }
+        if answered < quorum {
+            return Err(last_error);
+        }
+        let msgs: Vec<Msg> = votes
+            .into_iter()
+            .filter(|(_, count)| *count >= quorum)
+            .map(|(msg, _)| msg)
+            .collect();
+        match msgs.is_empty() {
+            true => Err(Error::NotFound(address)),
+            false => Ok(msgs),
+        }
+    }
+
+    /// Subscribes to new messages through every backend, merging their notifications. A message
+    /// stored by several backends is notified once per backend. Backends that fail to subscribe are
+    /// skipped, the error of the last one is returned if none succeeds.
+    ///
+    /// # Arguments
+    /// * `subscription`: The addresses to be notified of.
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<Msg>> {
+        let mut notifications = Vec::new();
+        let mut last_error = Error::External(anyhow::anyhow!("no transport backend available"));
+        for backend in &mut self.backends {
+            match backend.subscribe(subscription.clone()).await {
+                Ok(backend_notifications) => notifications.push(backend_notifications),
+                Err(e) => last_error = e,
+            }
+        }
+        match notifications.is_empty() {
+            true => Err(last_error),
+            false => Ok(Box::pin(select_all(notifications))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        address::{AppAddr, MsgId},
+        id::Identifier,
+        message::{Topic, TransportMessage},
+        transport::bucket,
+    };
+
+    use super::*;
+
+    /// Backend that can be taken offline
+    #[derive(Default)]
+    struct Node {
+        inner: bucket::Client,
+        offline: bool,
+    }
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl Transport<'_> for Node {
+        type Msg = TransportMessage;
+        type SendResponse = TransportMessage;
+
+        async fn send_message(&mut self, address: Address, msg: TransportMessage) -> Result<TransportMessage>
+        where
+            Self::Msg: 'async_trait,
+        {
+            match self.offline {
+                true => Err(Error::Network("send message", anyhow::anyhow!("node is offline"))),
+                false => self.inner.send_message(address, msg).await,
+            }
+        }
+
+        async fn recv_messages(&mut self, address: Address) -> Result<Vec<TransportMessage>> {
+            match self.offline {
+                true => Err(Error::Network("receive messages", anyhow::anyhow!("node is offline"))),
+                false => self.inner.recv_messages(address).await,
+            }
+        }
+    }
+
+    fn nodes(n: usize) -> Vec<Node> {
+        (0..n).map(|_| Node::default()).collect()
+    }
+
+    fn address() -> Address {
+        Address::new(
+            AppAddr::default(),
+            MsgId::gen(AppAddr::default(), &Identifier::default(), &Topic::default(), 1),
+        )
+    }
+
+    #[tokio::test]
+    async fn failover_skips_unhealthy_backends() -> Result<()> {
+        let mut transport = MultiTransport::failover(nodes(2));
+        transport.backends_mut()[0].offline = true;
+        let msg = TransportMessage::new(vec![1; 32]);
+        transport.send_message(address(), msg.clone()).await?;
+        assert_eq!(transport.recv_message(address()).await?, msg);
+
+        transport.backends_mut()[1].offline = true;
+        assert!(transport.recv_message(address()).await.unwrap_err().is_transient());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quorum_reads_require_enough_backends_to_agree() -> Result<()> {
+        let mut transport = MultiTransport::quorum(nodes(3), 2);
+        let msg = TransportMessage::new(vec![1; 32]);
+        transport.send_message(address(), msg.clone()).await?;
+        // A message only known by a single backend is not accepted
+        transport.backends_mut()[0]
+            .send_message(address(), TransportMessage::new(vec![2; 32]))
+            .await?;
+        assert_eq!(transport.recv_messages(address()).await?, vec![msg.clone()]);
+
+        transport.backends_mut()[2].offline = true;
+        assert_eq!(transport.recv_messages(address()).await?, vec![msg]);
+        transport.backends_mut()[1].offline = true;
+        assert!(transport.recv_messages(address()).await.unwrap_err().is_transient());
+        Ok(())
+    }
+}
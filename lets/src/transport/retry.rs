@@ -0,0 +1,361 @@
+// Rust
+extern crate std;
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{future::Future, time::Duration};
+
+// 3rd-party
+use async_trait::async_trait;
+use futures::{
+    future::{self, Either},
+    FutureExt,
+};
+use futures_timer::Delay;
+use rand::Rng;
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{
+    address::Address,
+    error::{Error, Result},
+    sync::MaybeSend,
+    transport::{Notifications, Subscription, Transport},
+};
+
+/// A [`Transport`] wrapper retrying the operations of the inner [`Transport`] that fail with a
+/// transient error ([`Error::Timeout`] or [`Error::Network`]).
+///
+/// Failed attempts are retried after an exponentially increasing delay, randomized by a jitter to
+/// spread the retries of concurrent clients, until the operation succeeds, fails with a
+/// non-transient error or the maximum number of attempts is reached. In the latter case the error
+/// of the last attempt is returned.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use lets::transport::{bucket, retry};
+/// let transport = retry::Client::new(bucket::Client::<Vec<u8>>::new())
+///     .with_max_attempts(3)
+///     .with_backoff(Duration::from_millis(200), Duration::from_secs(5))
+///     .with_timeout(Duration::from_secs(10));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Client<Tsp> {
+    /// Wrapped [`Transport`]
+    inner: Tsp,
+    /// Maximum number of attempts of every operation, the first one included
+    max_attempts: u32,
+    /// Delay before the first retry
+    initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts
+    max_backoff: Duration,
+    /// Factor the delay is multiplied by after every retry
+    backoff_factor: u32,
+    /// Fraction of each delay that is randomized
+    jitter: f64,
+    /// Time after which an attempt is abandoned and considered failed with [`Error::Timeout`]
+    timeout: Option<Duration>,
+}
+
+impl<Tsp> Client<Tsp> {
+    /// Wraps the [`Transport`] in a [`Client`] making up to 5 attempts of every operation, waiting
+    /// from 100 milliseconds up to 10 seconds between them, with no timeout per attempt.
+    ///
+    /// # Arguments
+    /// * `inner`: The [`Transport`] to retry the operations of
+    pub fn new(inner: Tsp) -> Self {
+        Self {
+            inner,
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            backoff_factor: 2,
+            jitter: 0.5,
+            timeout: None,
+        }
+    }
+
+    /// Sets the maximum number of attempts of every operation, the first one included. A value of
+    /// 1 disables the retries.
+    ///
+    /// # Arguments
+    /// * `max_attempts`: Maximum number of attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry and the upper bound of the delay between two attempts
+    ///
+    /// # Arguments
+    /// * `initial`: Delay before the first retry
+    /// * `max`: Maximum delay between two attempts
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the factor the delay is multiplied by after every retry
+    ///
+    /// # Arguments
+    /// * `backoff_factor`: Growth factor of the delay
+    pub fn with_backoff_factor(mut self, backoff_factor: u32) -> Self {
+        self.backoff_factor = backoff_factor;
+        self
+    }
+
+    /// Sets the fraction of each delay that is randomized: with a jitter of `0.5` a delay of 1
+    /// second is shortened to anywhere between 0.5 and 1 second. Values are clamped to `0..=1`.
+    ///
+    /// # Arguments
+    /// * `jitter`: Randomized fraction of the delays
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the time after which an attempt is abandoned and considered failed with
+    /// [`Error::Timeout`]
+    ///
+    /// # Arguments
+    /// * `timeout`: Maximum duration of an attempt
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns a reference to the wrapped [`Transport`]
+    pub fn inner(&self) -> &Tsp {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped [`Transport`]
+    pub fn inner_mut(&mut self) -> &mut Tsp {
+        &mut self.inner
+    }
+
+    /// Consumes the [`Client`], returning the wrapped [`Transport`]
+    pub fn into_inner(self) -> Tsp {
+        self.inner
+    }
+
+    /// Returns the delay to wait for after the provided failed attempt
+    ///
+    /// # Arguments
+    /// * `attempt`: Number of the failed attempt, starting from 1
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.backoff_factor.saturating_pow(attempt - 1);
+        let backoff = self
+            .initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+        backoff.mul_f64(1.0 - self.jitter * rand::thread_rng().gen::<f64>())
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a, Tsp, Msg, SendResponse> Transport<'a> for Client<Tsp>
+where
+    Tsp: Transport<'a, Msg = Msg, SendResponse = SendResponse> + MaybeSend,
+    Msg: Clone + MaybeSend + 'a,
+{
+    type Msg = Msg;
+    type SendResponse = SendResponse;
+
+    /// Sends a message through the inner [`Transport`], retrying on transient errors.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message.
+    /// * `msg`: The message to send.
+    async fn send_message(&mut self, address: Address, msg: Msg) -> Result<SendResponse>
+    where
+        'a: 'async_trait,
+    {
+        let mut attempt = 1;
+        loop {
+            let call = self.inner.send_message(address, msg.clone());
+            match timed(self.timeout, "send message", call).await {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {}
+                outcome => return outcome,
+            }
+            Delay::new(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Receives the messages at the [`Address`] from the inner [`Transport`], retrying on transient
+    /// errors.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<Msg>> {
+        let mut attempt = 1;
+        loop {
+            let call = self.inner.recv_messages(address);
+            match timed(self.timeout, "receive messages", call).await {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {}
+                outcome => return outcome,
+            }
+            Delay::new(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Receives the messages of several addresses from the inner [`Transport`], retrying the
+    /// addresses that fail with transient errors. A timeout fails every address of the attempt.
+    ///
+    /// # Arguments
+    /// * `addresses`: The addresses of the messages.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<Msg>>> {
+        let mut outcomes: Vec<Option<Result<Vec<Msg>>>> = addresses.iter().map(|_| None).collect();
+        let mut pending: Vec<usize> = (0..addresses.len()).collect();
+        let mut attempt = 1;
+        loop {
+            let batch: Vec<Address> = pending.iter().map(|i| addresses[*i]).collect();
+            let call = self.inner.recv_batch(&batch).map(Ok);
+            let batch_outcomes = timed(self.timeout, "receive messages", call)
+                .await
+                .unwrap_or_else(|_| batch.iter().map(|_| Err(Error::Timeout("receive messages"))).collect());
+
+            let mut failed = Vec::new();
+            for (i, outcome) in pending.into_iter().zip(batch_outcomes) {
+                match outcome {
+                    Err(e) if e.is_transient() && attempt < self.max_attempts => failed.push(i),
+                    outcome => outcomes[i] = Some(outcome),
+                }
+            }
+            if failed.is_empty() {
+                return outcomes.into_iter().flatten().collect();
+            }
+            pending = failed;
+            Delay::new(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Subscribes to new messages through the inner [`Transport`], retrying on transient errors.
+    /// Errors of the notification stream itself are not retried.
+    ///
+    /// # Arguments
+    /// * `subscription`: The addresses to be notified of.
+    async fn subscribe(&mut self, subscription: Subscription) -> Result<Notifications<Msg>> {
+        let mut attempt = 1;
+        loop {
+            let call = self.inner.subscribe(subscription.clone());
+            match timed(self.timeout, "subscribe to new messages", call).await {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {}
+                outcome => return outcome,
+            }
+            Delay::new(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Awaits the call, failing with [`Error::Timeout`] if it does not complete within the timeout
+///
+/// # Arguments
+/// * `timeout`: Maximum duration of the call, if any
+/// * `action`: Description of the call, reported in the timeout error
+/// * `call`: The future of the call
+async fn timed<F, T>(timeout: Option<Duration>, action: &'static str, call: F) -> Result<T>
+where
+    F: Future<Output = Result<T>> + Unpin,
+{
+    match timeout {
+        None => call.await,
+        Some(timeout) => match future::select(call, Delay::new(timeout)).await {
+            Either::Left((outcome, _)) => outcome,
+            Either::Right(_) => Err(Error::Timeout(action)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        address::{AppAddr, MsgId},
+        id::Identifier,
+        message::{Topic, TransportMessage},
+        transport::bucket,
+    };
+
+    use super::*;
+
+    /// Transport failing the first operations with a network error
+    struct Flaky {
+        inner: bucket::Client,
+        failures: usize,
+        attempts: usize,
+    }
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl Transport<'_> for Flaky {
+        type Msg = TransportMessage;
+        type SendResponse = TransportMessage;
+
+        async fn send_message(&mut self, address: Address, msg: TransportMessage) -> Result<TransportMessage>
+        where
+            Self::Msg: 'async_trait,
+        {
+            self.attempts += 1;
+            match self.attempts > self.failures {
+                true => self.inner.send_message(address, msg).await,
+                false => Err(Error::Network("send message", anyhow::anyhow!("connection reset"))),
+            }
+        }
+
+        async fn recv_messages(&mut self, address: Address) -> Result<Vec<TransportMessage>> {
+            self.attempts += 1;
+            match self.attempts > self.failures {
+                true => self.inner.recv_messages(address).await,
+                false => Err(Error::Network("receive messages", anyhow::anyhow!("connection reset"))),
+            }
+        }
+    }
+
+    fn flaky_client(failures: usize) -> Client<Flaky> {
+        let flaky = Flaky {
+            inner: bucket::Client::new(),
+            failures,
+            attempts: 0,
+        };
+        Client::new(flaky)
+            .with_max_attempts(3)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+    }
+
+    fn address() -> Address {
+        Address::new(
+            AppAddr::default(),
+            MsgId::gen(AppAddr::default(), &Identifier::default(), &Topic::default(), 1),
+        )
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried_up_to_max_attempts() -> Result<()> {
+        let msg = TransportMessage::new(vec![1; 32]);
+        let mut client = flaky_client(2);
+        client.send_message(address(), msg.clone()).await?;
+        assert_eq!(client.inner().attempts, 3);
+
+        let mut client = flaky_client(3);
+        let error = client.send_message(address(), msg).await.unwrap_err();
+        assert!(error.is_transient());
+        assert_eq!(client.inner().attempts, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn missing_messages_are_not_retried() -> Result<()> {
+        let mut client = flaky_client(0);
+        assert!(client.recv_messages(address()).await.unwrap_err().is_not_found());
+        assert_eq!(client.inner().attempts, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+// Rust
+extern crate std;
+
+use alloc::{boxed::Box, vec::Vec};
+use core::convert::TryFrom;
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// 3rd-party
+use async_trait::async_trait;
+use rusqlite::{params, Connection, Row};
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{
+    address::{Address, AppAddr, MsgId},
+    error::{Error, Result},
+    message::TransportMessage,
+    transport::Transport,
+};
+
+/// Schema of the message ledger. Messages are indexed by [`Address`], by [`AppAddr`] and by
+/// insertion time; the autoincremented `id` preserves the insertion order of messages inserted
+/// within the same millisecond.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS messages (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        appaddr BLOB NOT NULL,
+        msgid BLOB NOT NULL,
+        body BLOB NOT NULL,
+        inserted_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS messages_by_address ON messages (appaddr, msgid);
+    CREATE INDEX IF NOT EXISTS messages_by_appaddr ON messages (appaddr, inserted_at);
+    CREATE INDEX IF NOT EXISTS messages_by_insertion_time ON messages (inserted_at);
+";
+
+/// A [`Transport`] Client storing messages in an embedded `SQLite` database, providing a single
+/// file, queryable ledger for offline deployments and test fixtures.
+#[derive(Debug)]
+pub struct Client {
+    /// Connection to the `SQLite` database
+    connection: Connection,
+}
+
+impl Client {
+    /// Opens (or creates) the `SQLite` database at the provided path
+    ///
+    /// # Arguments
+    /// * `path`: Path of the database file
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let connection = Connection::open(path).map_err(|e| Error::Sqlite("open database", e))?;
+        Self::from_connection(connection)
+    }
+
+    /// Creates a [`Client`] backed by a transient in-memory `SQLite` database
+    pub fn in_memory() -> Result<Self> {
+        let connection = Connection::open_in_memory().map_err(|e| Error::Sqlite("open database", e))?;
+        Self::from_connection(connection)
+    }
+
+    /// Creates a [`Client`] from an open `SQLite` [`Connection`], creating the message table and
+    /// its indexes if they are missing.
+    ///
+    /// # Arguments
+    /// * `connection`: Connection to the `SQLite` database
+    pub fn from_connection(connection: Connection) -> Result<Self> {
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(|e| Error::Sqlite("create schema", e))?;
+        Ok(Self { connection })
+    }
+
+    /// Returns every message of the stream, in the order they were inserted
+    ///
+    /// # Arguments
+    /// * `appaddr`: The [`AppAddr`] of the stream
+    pub fn stream_messages(&self, appaddr: AppAddr) -> Result<Vec<(Address, TransportMessage)>> {
+        self.stream_messages_since(appaddr, 0)
+    }
+
+    /// Returns the messages of the stream inserted at or after the provided time, in the order they
+    /// were inserted
+    ///
+    /// # Arguments
+    /// * `appaddr`: The [`AppAddr`] of the stream
+    /// * `since`: `Unix` timestamp, in milliseconds, from which messages are returned
+    pub fn stream_messages_since(&self, appaddr: AppAddr, since: u64) -> Result<Vec<(Address, TransportMessage)>> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT appaddr, msgid, body FROM messages WHERE appaddr = ?1 AND inserted_at >= ?2 ORDER BY \
+                 inserted_at, id",
+            )
+            .map_err(|e| Error::Sqlite("list stream messages", e))?;
+        let rows = statement
+            .query_map(params![appaddr.as_bytes(), since as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| Error::Sqlite("list stream messages", e))?;
+        rows.map(|row| {
+            let (appaddr, msgid, body): (Vec<u8>, Vec<u8>, Vec<u8>) =
+                row.map_err(|e| Error::Sqlite("list stream messages", e))?;
+            Ok((
+                Address::new(to_appaddr(appaddr)?, to_msgid(msgid)?),
+                TransportMessage::new(body),
+            ))
+        })
+        .collect()
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl Transport<'_> for Client {
+    type Msg = TransportMessage;
+    type SendResponse = TransportMessage;
+
+    /// Inserts the message into the database, next to any message previously sent to the
+    /// [`Address`].
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message to store.
+    /// * `msg`: The message to store.
+    ///
+    /// Returns:
+    /// The message that was sent.
+    async fn send_message(&mut self, address: Address, msg: TransportMessage) -> Result<TransportMessage>
+    where
+        Self::Msg: 'async_trait,
+    {
+        let inserted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as i64)
+            .unwrap_or_default();
+        self.connection
+            .prepare_cached("INSERT INTO messages (appaddr, msgid, body, inserted_at) VALUES (?1, ?2, ?3, ?4)")
+            .and_then(|mut statement| {
+                statement.execute(params![
+                    address.base().as_bytes(),
+                    address.relative().as_bytes(),
+                    msg.as_ref(),
+                    inserted_at
+                ])
+            })
+            .map_err(|e| Error::Sqlite("insert message", e))?;
+        Ok(msg)
+    }
+
+    /// Returns the messages stored for the [`Address`] in the order they were inserted, or an error
+    /// if there are none.
+    ///
+    /// # Arguments
+    /// * `address`: The address to retrieve messages from.
+    ///
+    /// Returns:
+    /// A vector of messages.
+    async fn recv_messages(&mut self, address: Address) -> Result<Vec<TransportMessage>> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT body FROM messages WHERE appaddr = ?1 AND msgid = ?2 ORDER BY id")
+            .map_err(|e| Error::Sqlite("select messages", e))?;
+        let msgs = statement
+            .query_map(
+                params![address.base().as_bytes(), address.relative().as_bytes()],
+                |row: &Row| row.get(0).map(TransportMessage::new),
+            )
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<TransportMessage>>>())
+            .map_err(|e| Error::Sqlite("select messages", e))?;
+        if msgs.is_empty() {
+            return Err(Error::NotFound(address));
+        }
+        Ok(msgs)
+    }
+}
+
+/// Decodes an [`AppAddr`] stored in the database
+fn to_appaddr(bytes: Vec<u8>) -> Result<AppAddr> {
+    let size = bytes.len();
+    <[u8; 40]>::try_from(bytes)
+        .map(AppAddr::new)
+        .map_err(|_| Error::InvalidSize("AppAddr", 40, size as u64))
+}
+
+/// Decodes a [`MsgId`] stored in the database
+fn to_msgid(bytes: Vec<u8>) -> Result<MsgId> {
+    let size = bytes.len();
+    <[u8; 12]>::try_from(bytes)
+        .map(MsgId::new)
+        .map_err(|_| Error::InvalidSize("MsgId", 12, size as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{id::Identifier, message::Topic};
+
+    use super::*;
+
+    fn address(appaddr: AppAddr, cursor: usize) -> Address {
+        Address::new(
+            appaddr,
+            MsgId::gen(appaddr, &Identifier::default(), &Topic::default(), cursor),
+        )
+    }
+
+    #[tokio::test]
+    async fn send_and_recv_messages() -> Result<()> {
+        let mut client = Client::in_memory()?;
+        let msgs: Vec<TransportMessage> = (0..2).map(|i| TransportMessage::new(vec![i; 16])).collect();
+        for msg in &msgs {
+            client.send_message(address(AppAddr::default(), 1), msg.clone()).await?;
+        }
+
+        assert_eq!(client.recv_messages(address(AppAddr::default(), 1)).await?, msgs);
+        assert!(client.recv_message(address(AppAddr::default(), 2)).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_messages_of_a_stream() -> Result<()> {
+        let mut client = Client::in_memory()?;
+        let stream = AppAddr::new([1; 40]);
+        let other_stream = AppAddr::new([2; 40]);
+        client
+            .send_message(address(stream, 1), TransportMessage::new(vec![1]))
+            .await?;
+        client
+            .send_message(address(other_stream, 1), TransportMessage::new(vec![2]))
+            .await?;
+        client
+            .send_message(address(stream, 2), TransportMessage::new(vec![3]))
+            .await?;
+
+        assert_eq!(
+            client.stream_messages(stream)?,
+            vec![
+                (address(stream, 1), TransportMessage::new(vec![1])),
+                (address(stream, 2), TransportMessage::new(vec![3]))
+            ]
+        );
+        assert!(client.stream_messages_since(stream, u64::MAX >> 1)?.is_empty());
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use core::{
 // 3rd-party
 use async_trait::async_trait;
 use futures::{
-    future::{ready, try_join_all},
+    future::{join_all, ready, try_join_all},
     TryFutureExt,
 };
 
@@ -22,6 +22,7 @@ use crate::{
     address::Address,
     error::{Error, Result},
     message::TransportMessage,
+    sync::MaybeSend,
     transport::Transport,
 };
 
@@ -67,10 +68,44 @@ impl<Message, SendResponse> Client<Message, SendResponse> {
     }
 }
 
-#[async_trait(?Send)]
+impl<Message, SendResponse> Client<Message, SendResponse>
+where
+    Message: TryFrom<IotaMessage, Error = crate::error::Error>,
+{
+    /// Retrieves the messages indexed at the provided [`Address`] from the tangle. Takes `&self` so
+    /// that several addresses can be fetched concurrently.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the messages to retrieve
+    async fn get_messages(&self, address: Address) -> Result<Vec<Message>> {
+        let msg_ids = self
+            .client()
+            .get_message()
+            .index(address.to_msg_index())
+            .await
+            .map_err(|e| transport_error("get messages by index", address, e))?;
+
+        if msg_ids.is_empty() {
+            return Err(Error::NotFound(address));
+        }
+
+        let msgs = try_join_all(msg_ids.iter().map(|msg| {
+            self.client()
+                .get_message()
+                .data(msg)
+                .map_err(|e| transport_error("receive message", address, e))
+                .and_then(|iota_message| ready(iota_message.try_into()))
+        }))
+        .await?;
+        Ok(msgs)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<Message, SendResponse> Transport<'_> for Client<Message, SendResponse>
 where
-    Message: Into<Vec<u8>> + TryFrom<IotaMessage, Error = crate::error::Error>,
+    Message: Into<Vec<u8>> + TryFrom<IotaMessage, Error = crate::error::Error> + MaybeSend,
     SendResponse: TryFrom<IotaMessage, Error = crate::error::Error>,
 {
     type Msg = Message;
@@ -91,36 +126,42 @@ where
             .with_data(msg.into())
             .finish()
             .await
-            .map_err(|e| Error::IotaClient("sending message", e))?
+            .map_err(|e| transport_error("send message", address, e))?
             .try_into()
     }
 
-    /// Retrieves a message indexed at the provided [`Address`] from the tangle. Errors if no
-    /// messages are found.
+    /// Retrieves a message indexed at the provided [`Address`] from the tangle. Errors with
+    /// [`Error::NotFound`] if no messages are found.
     ///
     /// # Arguments
     /// * `address`: The address of the message to retrieve.
     async fn recv_messages(&mut self, address: Address) -> Result<Vec<Message>> {
-        let msg_ids = self
-            .client()
-            .get_message()
-            .index(address.to_msg_index())
-            .await
-            .map_err(|e| Error::IotaClient("get messages by index", e))?;
+        self.get_messages(address).await
+    }
 
-        if msg_ids.is_empty() {
-            return Err(Error::MessageMissing(address, "transport"));
-        }
+    /// Retrieves the messages indexed at the provided addresses from the tangle, sending all the
+    /// requests at once.
+    ///
+    /// # Arguments
+    /// * `addresses`: The addresses of the messages to retrieve.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<Message>>> {
+        join_all(addresses.iter().map(|address| self.get_messages(*address))).await
+    }
+}
 
-        let msgs = try_join_all(msg_ids.iter().map(|msg| {
-            self.client()
-                .get_message()
-                .data(msg)
-                .map_err(|e| Error::IotaClient("receiving message", e))
-                .and_then(|iota_message| ready(iota_message.try_into()))
-        }))
-        .await?;
-        Ok(msgs)
+/// Categorizes a failure of the `IOTA` client when reaching the node. Error statuses returned by
+/// the node are reported as [`Error::NotFound`] or [`Error::Timeout`] when applicable, any other
+/// failure as a [`Error::Network`] error.
+///
+/// # Arguments
+/// * `action`: What the client was attempting to do
+/// * `address`: The [`Address`] of the message the client was operating on
+/// * `error`: The client error
+fn transport_error(action: &'static str, address: Address, error: iota_client::Error) -> Error {
+    match error {
+        iota_client::Error::ResponseError(404, _) => Error::NotFound(address),
+        iota_client::Error::ResponseError(408, _) | iota_client::Error::ResponseError(504, _) => Error::Timeout(action),
+        error => Error::Network(action, anyhow::Error::msg(error)),
     }
 }
 
@@ -7,6 +7,7 @@ use core::{
 
 // 3rd-party
 use async_trait::async_trait;
+use futures::future::join_all;
 use rayon::prelude::*;
 use serde::{de::DeserializeOwned, Deserialize};
 
@@ -25,6 +26,7 @@ use crate::{
     address::Address,
     error::{Error, Result},
     message::TransportMessage,
+    sync::MaybeSend,
     transport::Transport,
 };
 
@@ -74,11 +76,10 @@ impl<Message, SendResponse> Client<Message, SendResponse> {
     async fn get_network_info(&self) -> Result<NetworkInfo> {
         let network_info_path = "api/v1/info";
         let network_info: Response<NetworkInfo> = self
-            .client
-            .get(format!("{}/{}", self.node_url, network_info_path))
-            .send()
-            .await?
-            .json()
+            .request(
+                "get network info",
+                self.client.get(format!("{}/{}", self.node_url, network_info_path)),
+            )
             .await?;
         Ok(network_info.data)
     }
@@ -87,15 +88,32 @@ impl<Message, SendResponse> Client<Message, SendResponse> {
     async fn get_tips(&self) -> Result<Tips> {
         let tips_path = "api/v1/tips";
         let tips: Response<Tips> = self
-            .client
-            .get(format!("{}/{}", self.node_url, tips_path))
-            .send()
-            .await?
-            .json()
+            .request("get tips", self.client.get(format!("{}/{}", self.node_url, tips_path)))
             .await?;
         Ok(tips.data)
     }
 
+    /// Sends a request to the node and decodes its JSON response. Failures to reach the node, as
+    /// well as error statuses returned by it, are categorized as [`Error::Timeout`] or
+    /// [`Error::Network`] errors.
+    ///
+    /// # Arguments
+    /// * `action`: What the request is attempting to do
+    /// * `request`: The request to send
+    async fn request<R>(&self, action: &'static str, request: reqwest::RequestBuilder) -> Result<R>
+    where
+        R: DeserializeOwned,
+    {
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| Error::request(action, e))?
+            .json()
+            .await
+            .map_err(|e| Error::request(action, e))
+    }
+
     /// Serialise message contents into single byte array for sending
     ///
     /// # Arguments
@@ -133,10 +151,42 @@ impl<Message, SendResponse> Client<Message, SendResponse> {
     }
 }
 
-#[async_trait(?Send)]
+impl<Message, SendResponse> Client<Message, SendResponse>
+where
+    Message: TryFrom<TangleMessage, Error = crate::error::Error>,
+{
+    /// Retrieves the message indexed at the provided [`Address`] from the tangle. Takes `&self` so
+    /// that several addresses can be fetched concurrently.
+    ///
+    /// # Arguments
+    /// * `address`: The address of the message to retrieve
+    async fn get_messages(&self, address: Address) -> Result<Vec<Message>> {
+        let path = "api/v1/messages";
+        let index_data: Response<IndexResponse> = self
+            .request(
+                "get messages by index",
+                self.client
+                    .get(format!("{}/{}", self.node_url, path))
+                    .query(&[("index", hex::encode(address.to_msg_index()))]),
+            )
+            .await?;
+
+        let msg_id = index_data.data.message_ids.first().ok_or(Error::NotFound(address))?;
+        let msg: Response<TangleMessage> = self
+            .request(
+                "receive message",
+                self.client.get(format!("{}/{}/{}", self.node_url, path, msg_id)),
+            )
+            .await?;
+        Ok(vec![msg.data.try_into()?])
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<Message, SendResponse> Transport<'_> for Client<Message, SendResponse>
 where
-    Message: AsRef<[u8]> + TryFrom<TangleMessage, Error = crate::error::Error>,
+    Message: AsRef<[u8]> + TryFrom<TangleMessage, Error = crate::error::Error> + MaybeSend,
     SendResponse: DeserializeOwned,
 {
     type Msg = Message;
@@ -158,46 +208,33 @@ where
 
         let path = "api/v1/messages";
         let response: SendResponse = self
-            .client
-            .post(format!("{}/{}", self.node_url, path))
-            .header("Content-Type", "application/octet-stream")
-            .body(message_bytes)
-            .send()
-            .await?
-            .json()
+            .request(
+                "send message",
+                self.client
+                    .post(format!("{}/{}", self.node_url, path))
+                    .header("Content-Type", "application/octet-stream")
+                    .body(message_bytes),
+            )
             .await?;
         Ok(response)
     }
 
-    /// Retrieves a message indexed at the provided [`Address`] from the tangle. Errors if no
-    /// messages are found.
+    /// Retrieves a message indexed at the provided [`Address`] from the tangle. Errors with
+    /// [`Error::NotFound`] if no messages are found.
     ///
     /// # Arguments
     /// * `address`: The address of the message to retrieve.
     async fn recv_messages(&mut self, address: Address) -> Result<Vec<Message>> {
-        let path = "api/v1/messages";
-        let index_data: Response<IndexResponse> = self
-            .client
-            .get(format!("{}/{}", self.node_url, path))
-            .query(&[("index", hex::encode(address.to_msg_index()))])
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.get_messages(address).await
+    }
 
-        let msg_id = index_data
-            .data
-            .message_ids
-            .first()
-            .ok_or(Error::AddressError("No message found", address))?;
-        let msg: Response<TangleMessage> = self
-            .client
-            .get(format!("{}/{}/{}", self.node_url, path, msg_id))
-            .send()
-            .await?
-            .json()
-            .await?;
-        Ok(vec![msg.data.try_into()?])
+    /// Retrieves the messages indexed at the provided addresses from the tangle, sending all the
+    /// requests at once.
+    ///
+    /// # Arguments
+    /// * `addresses`: The addresses of the messages to retrieve.
+    async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<Result<Vec<Message>>> {
+        join_all(addresses.iter().map(|address| self.get_messages(*address))).await
     }
 }
 
@@ -0,0 +1,117 @@
+//! # Streams Relay
+//! A local HTTP relay exposing the `send_message`/`recv_messages` operations of a LETS `Transport`
+//! over REST, so that several processes can share a stream on the same machine through
+//! `lets::transport::http::Client`.
+//!
+//! ```text
+//! streams-relay [--bind <ip:port>] [--store memory|fs:<directory>|sqlite:<file>]
+//! ```
+//!
+//! Messages are kept in memory by default, and lost when the relay exits.
+
+// Rust
+use std::{env, net::TcpListener, process};
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use lets::{
+    message::TransportMessage,
+    transport::{bucket, fs, sqlite, Transport},
+};
+
+// Local
+mod server;
+
+/// Address the relay listens on if none is provided
+const DEFAULT_BIND: &str = "127.0.0.1:8080";
+
+/// Storage backing the relay
+enum Store {
+    Memory,
+    Fs(String),
+    Sqlite(String),
+}
+
+/// Command line configuration of the relay
+struct Config {
+    bind: String,
+    store: Store,
+}
+
+impl Config {
+    /// Parses the configuration from the command line arguments
+    fn from_args() -> Result<Self, String> {
+        let mut config = Config {
+            bind: DEFAULT_BIND.to_string(),
+            store: Store::Memory,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for '{}'", arg));
+            match arg.as_str() {
+                "--bind" => config.bind = value()?,
+                "--store" => {
+                    let store = value()?;
+                    config.store = match store.split_once(':') {
+                        None if store == "memory" => Store::Memory,
+                        Some(("fs", path)) => Store::Fs(path.to_string()),
+                        Some(("sqlite", path)) => Store::Sqlite(path.to_string()),
+                        _ => return Err(format!("unknown store '{}'", store)),
+                    };
+                }
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let config = Config::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("usage: streams-relay [--bind <ip:port>] [--store memory|fs:<directory>|sqlite:<file>]");
+        process::exit(2);
+    });
+    let listener = TcpListener::bind(&config.bind).unwrap_or_else(|e| {
+        eprintln!("cannot listen on {}: {}", config.bind, e);
+        process::exit(1);
+    });
+
+    let served = match config.store {
+        Store::Memory => run(listener, bucket::Client::new()).await,
+        Store::Fs(path) => run(listener, fs::Client::new(path)).await,
+        Store::Sqlite(path) => match sqlite::Client::open(&path) {
+            Ok(client) => run(listener, client).await,
+            Err(e) => {
+                eprintln!("cannot open {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+    };
+    if let Err(e) = served {
+        eprintln!("relay stopped: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Serves the [`Transport`] until the process is interrupted
+///
+/// # Arguments
+/// * `listener`: Socket the relay accepts connections from
+/// * `transport`: The [`Transport`] messages are stored in and retrieved from
+async fn run<T>(listener: TcpListener, transport: T) -> Result<(), hyper::Error>
+where
+    T: for<'a> Transport<'a, Msg = TransportMessage>,
+{
+    if let Ok(address) = listener.local_addr() {
+        println!("relay listening on http://{}", address);
+    }
+    server::serve(listener, transport, async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await
+}
@@ -0,0 +1,344 @@
+// Rust
+use std::{convert::Infallible, future::Future, net::TcpListener, str::FromStr};
+
+// 3rd-party
+use hyper::{
+    body, header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc, oneshot, watch,
+};
+
+// IOTA
+
+// Streams
+use lets::{
+    address::Address,
+    error::{Error, Result},
+    message::TransportMessage,
+    transport::{Subscription, Transport},
+};
+
+// Local
+
+/// Path prefix under which messages are exposed
+const MESSAGES_PATH: &str = "/messages/";
+
+/// Path under which new messages are pushed as server-sent events
+const NOTIFICATIONS_PATH: &str = "/notifications";
+
+/// Maximum number of requests queued for the transport before connections are put on hold
+const QUEUE_SIZE: usize = 64;
+
+/// Maximum number of new messages buffered for a subscriber before the oldest are skipped
+const NOTIFICATIONS_SIZE: usize = 256;
+
+/// Operation forwarded from the HTTP connections to the [`Transport`]
+enum Command {
+    Send(Address, TransportMessage, oneshot::Sender<Result<()>>),
+    Recv(Address, oneshot::Sender<Result<Vec<TransportMessage>>>),
+}
+
+/// Serves the messages of the [`Transport`] over REST until `shutdown` resolves.
+///
+/// Messages are exposed under `/messages/<address>`: `POST` stores the raw request body as a
+/// message, `GET` responds with a JSON array of the hex encoded messages stored at the address.
+///
+/// Messages stored from then on are pushed as server-sent events to the `GET` requests of
+/// `/notifications?stream=<appaddr>` and `/notifications?addresses=<address>,<address>`. Every
+/// event carries the address and the hex encoded message separated by a space. Subscribers falling
+/// more than a few hundred messages behind skip the oldest ones.
+///
+/// The [`Transport`] is owned by the future returned by this function and operated one request at a
+/// time, so it does not need to be `Send` nor `Sync`.
+///
+/// # Arguments
+/// * `listener`: Socket the relay accepts connections from
+/// * `transport`: The [`Transport`] messages are stored in and retrieved from
+/// * `shutdown`: Future signaling the relay to stop accepting connections
+pub async fn serve<T, S>(listener: TcpListener, mut transport: T, shutdown: S) -> core::result::Result<(), hyper::Error>
+where
+    T: for<'a> Transport<'a, Msg = TransportMessage>,
+    S: Future<Output = ()>,
+{
+    let (commands, mut queue) = mpsc::channel(QUEUE_SIZE);
+    let (notifier, _) = broadcast::channel(NOTIFICATIONS_SIZE);
+    // Notification streams never complete on their own, they are closed on shutdown so that the
+    // server can wait for the connections to finish
+    let (closing, closed) = watch::channel(false);
+
+    let worker_notifier = notifier.clone();
+    let make_service = make_service_fn(move |_| {
+        let (commands, notifier, closed) = (commands.clone(), notifier.clone(), closed.clone());
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(request, commands.clone(), notifier.clone(), closed.clone())
+            }))
+        }
+    });
+    let shutdown = async move {
+        shutdown.await;
+        let _ = closing.send(true);
+    };
+    let server = async move {
+        Server::from_tcp(listener)?
+            .serve(make_service)
+            .with_graceful_shutdown(shutdown)
+            .await
+    };
+
+    // The queue is closed once the server, and with it every command sender, has been dropped
+    let worker = async move {
+        while let Some(command) = queue.recv().await {
+            match command {
+                Command::Send(address, msg, response) => {
+                    let sent = transport.send_message(address, msg.clone()).await.map(|_| ());
+                    if sent.is_ok() {
+                        // Sending only fails if nobody is subscribed
+                        let _ = worker_notifier.send((address, msg));
+                    }
+                    let _ = response.send(sent);
+                }
+                Command::Recv(address, response) => {
+                    let _ = response.send(transport.recv_messages(address).await);
+                }
+            }
+        }
+    };
+
+    let (served, ()) = tokio::join!(server, worker);
+    served
+}
+
+/// Routes an HTTP request to the [`Transport`] and translates the outcome into an HTTP response
+///
+/// # Arguments
+/// * `request`: The HTTP request
+/// * `commands`: Queue of the [`Transport`] operations
+/// * `notifier`: Broadcaster of the stored messages
+/// * `closed`: Receiver of the shutdown signal
+async fn handle(
+    request: Request<Body>,
+    commands: mpsc::Sender<Command>,
+    notifier: broadcast::Sender<(Address, TransportMessage)>,
+    closed: watch::Receiver<bool>,
+) -> core::result::Result<Response<Body>, Infallible> {
+    if request.uri().path() == NOTIFICATIONS_PATH {
+        return Ok(match (request.method(), parse_subscription(request.uri().query())) {
+            (&Method::GET, Ok(subscription)) => notify(subscription, notifier.subscribe(), closed),
+            (&Method::GET, Err(e)) => respond(StatusCode::BAD_REQUEST, e.to_string()),
+            _ => respond(StatusCode::METHOD_NOT_ALLOWED, ""),
+        });
+    }
+
+    let address = match request.uri().path().strip_prefix(MESSAGES_PATH).map(Address::from_str) {
+        Some(Ok(address)) => address,
+        Some(Err(e)) => return Ok(respond(StatusCode::BAD_REQUEST, e.to_string())),
+        None => return Ok(respond(StatusCode::NOT_FOUND, "unknown path")),
+    };
+
+    let response = match *request.method() {
+        Method::POST => {
+            let msg = match body::to_bytes(request.into_body()).await {
+                Ok(bytes) => TransportMessage::new(bytes.to_vec()),
+                Err(e) => return Ok(respond(StatusCode::BAD_REQUEST, e.to_string())),
+            };
+            let (response, outcome) = oneshot::channel();
+            dispatch(&commands, Command::Send(address, msg, response), outcome)
+                .await
+                .map(|()| respond(StatusCode::CREATED, ""))
+        }
+        Method::GET => {
+            let (response, outcome) = oneshot::channel();
+            dispatch(&commands, Command::Recv(address, response), outcome)
+                .await
+                .map(|msgs| {
+                    let msgs: Vec<String> = msgs.iter().map(hex::encode).collect();
+                    respond(StatusCode::OK, serde_json::Value::from(msgs).to_string())
+                })
+        }
+        _ => return Ok(respond(StatusCode::METHOD_NOT_ALLOWED, "")),
+    };
+
+    Ok(response.unwrap_or_else(|e| match e {
+        Error::NotFound(_) => respond(StatusCode::NOT_FOUND, e.to_string()),
+        e => respond(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }))
+}
+
+/// Queues a [`Command`] for the [`Transport`] and waits for its outcome
+///
+/// # Arguments
+/// * `commands`: Queue of the [`Transport`] operations
+/// * `command`: The operation to queue
+/// * `outcome`: Receiver of the outcome of the operation
+async fn dispatch<R>(
+    commands: &mpsc::Sender<Command>,
+    command: Command,
+    outcome: oneshot::Receiver<Result<R>>,
+) -> Result<R> {
+    let unavailable = || Error::External(anyhow::anyhow!("relay transport is no longer available"));
+    commands.send(command).await.map_err(|_| unavailable())?;
+    outcome.await.map_err(|_| unavailable())?
+}
+
+/// Parses the [`Subscription`] of a notifications request from its query string
+///
+/// # Arguments
+/// * `query`: The query string of the request, if any
+fn parse_subscription(query: Option<&str>) -> Result<Subscription> {
+    let query = query.unwrap_or_default();
+    match query.split_once('=') {
+        Some(("stream", appaddr)) => Ok(Subscription::Stream(appaddr.parse()?)),
+        Some(("addresses", addresses)) => addresses
+            .split(',')
+            .map(Address::from_str)
+            .collect::<Result<_>>()
+            .map(Subscription::Addresses),
+        _ => Err(Error::Malformed(
+            "notifications query",
+            "stream= or addresses=",
+            query.to_string(),
+        )),
+    }
+}
+
+/// Creates a server-sent events response pushing the stored messages matching the [`Subscription`]
+/// until the client disconnects or the relay shuts down
+///
+/// # Arguments
+/// * `subscription`: The addresses to be notified of
+/// * `notifications`: Receiver of the stored messages
+/// * `closed`: Receiver of the shutdown signal
+fn notify(
+    subscription: Subscription,
+    mut notifications: broadcast::Receiver<(Address, TransportMessage)>,
+    mut closed: watch::Receiver<bool>,
+) -> Response<Body> {
+    let (mut events, body) = Body::channel();
+    tokio::spawn(async move {
+        loop {
+            let notification = tokio::select! {
+                notification = notifications.recv() => notification,
+                _ = closed.changed() => break,
+            };
+            match notification {
+                Ok((address, msg)) if subscription.matches(address) => {
+                    let event = format!("data: {} {}\n\n", address, hex::encode(msg));
+                    if events.send_data(event.into()).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => (),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+    let mut response = respond(StatusCode::OK, body);
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("text/event-stream"),
+    );
+    response
+}
+
+/// Creates a response with the provided status and body
+fn respond<B: Into<Body>>(status: StatusCode, body: B) -> Response<Body> {
+    let mut response = Response::new(body.into());
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use lets::{
+        address::{AppAddr, MsgId},
+        id::Identifier,
+        message::Topic,
+        transport::{bucket, http},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn relay_serves_messages_of_the_transport() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (stop, stopped) = oneshot::channel::<()>();
+        let relay = serve(listener, bucket::Client::new(), async {
+            let _ = stopped.await;
+        });
+
+        let client = async move {
+            let mut client = http::Client::new(url);
+            let address = Address::new(
+                AppAddr::default(),
+                MsgId::gen(AppAddr::default(), &Identifier::default(), &Topic::default(), 1),
+            );
+            let msgs = vec![TransportMessage::new(vec![1; 32]), TransportMessage::new(vec![2; 32])];
+            assert!(client.recv_message(address).await.is_err());
+            for msg in &msgs {
+                client.send_message(address, msg.clone()).await?;
+            }
+            let received = client.recv_messages(address).await;
+            let _ = stop.send(());
+            assert_eq!(received?, msgs);
+            Ok(())
+        };
+
+        let (served, result) = tokio::join!(relay, client);
+        served.unwrap();
+        result
+    }
+
+    #[tokio::test]
+    async fn relay_pushes_new_messages_to_subscribers() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (stop, stopped) = oneshot::channel::<()>();
+        let relay = serve(listener, bucket::Client::new(), async {
+            let _ = stopped.await;
+        });
+
+        let client = async move {
+            let mut client = http::Client::new(url);
+            let address = |cursor| {
+                Address::new(
+                    AppAddr::default(),
+                    MsgId::gen(AppAddr::default(), &Identifier::default(), &Topic::default(), cursor),
+                )
+            };
+            let mut notifications = client
+                .subscribe(Subscription::Addresses(vec![address(2), address(3)]))
+                .await?;
+            for cursor in 1..=3 {
+                client
+                    .send_message(address(cursor), TransportMessage::new(vec![cursor as u8; 32]))
+                    .await?;
+            }
+            let mut received = Vec::new();
+            for _ in 0..2 {
+                received.push(notifications.next().await.unwrap()?);
+            }
+            let _ = stop.send(());
+            assert_eq!(
+                received,
+                vec![
+                    (address(2), TransportMessage::new(vec![2; 32])),
+                    (address(3), TransportMessage::new(vec![3; 32]))
+                ]
+            );
+            // The notifications end with the relay
+            assert!(notifications.next().await.is_none());
+            Ok(())
+        };
+
+        let (served, result) = tokio::join!(relay, client);
+        served.unwrap();
+        result
+    }
+}
@@ -75,6 +75,12 @@ impl<IS, F> Context<IS, F> {
         self.spongos.commit();
         (self.spongos, self.cursor)
     }
+
+    /// Return a tuple of the [`Context`] [`Spongos`], left uncommitted, and read position. Used
+    /// when the `DDML` operations that follow carry on with the same state.
+    pub fn into_parts(self) -> (Spongos<F>, usize) {
+        (self.spongos, self.cursor)
+    }
 }
 
 impl<IS, F> fmt::Debug for Context<IS, F> {
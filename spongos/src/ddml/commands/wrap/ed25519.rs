@@ -10,10 +10,13 @@ use crate::{
     error::Result,
 };
 
-/// Uses the provided Ed25519 Secret Key to sign a hash. The signature is then absorbed into
-/// [`Context`].
-impl<F, OS: io::OStream> Ed25519<&ed25519::SecretKey, External<&NBytes<[u8; 64]>>> for Context<OS, F> {
-    fn ed25519(&mut self, secret_key: &ed25519::SecretKey, hash: External<&NBytes<[u8; 64]>>) -> Result<&mut Self> {
+/// Uses the provided Ed25519 Secret Key to sign a hash of any length. The signature is then
+/// absorbed into [`Context`].
+impl<F, T, OS: io::OStream> Ed25519<&ed25519::SecretKey, External<&NBytes<T>>> for Context<OS, F>
+where
+    T: AsRef<[u8]>,
+{
+    fn ed25519(&mut self, secret_key: &ed25519::SecretKey, hash: External<&NBytes<T>>) -> Result<&mut Self> {
         let signature = secret_key.sign(hash.inner().as_slice());
         self.stream
             .try_advance(ed25519::SIGNATURE_LENGTH)?
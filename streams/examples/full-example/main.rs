@@ -1,7 +1,8 @@
 // Rust
-use std::{cell::RefCell, env, rc::Rc};
+use std::{env, sync::Arc};
 
 // 3rd-party
+use futures::lock::Mutex;
 use rand::Rng;
 
 // IOTA
@@ -78,8 +79,8 @@ async fn main_pure() -> Result<()> {
 
     let transport = bucket::Client::new();
     // BucketTransport is an in-memory storage that needs to be shared between all the users,
-    // hence the Rc<RefCell<BucketTransport>>
-    let transport = Rc::new(RefCell::new(transport));
+    // hence the Arc<Mutex<BucketTransport>>, a transport with or without the `send` feature
+    let transport = Arc::new(Mutex::new(transport));
 
     run_basic_scenario(transport.clone(), "PURESEEDA").await?;
     run_lean_test(transport.clone(), "PURESEEDB").await?;
@@ -107,8 +108,8 @@ async fn main_tangle_client() -> Result<()> {
     );
     println!("\n");
 
-    let transport: Rc<RefCell<tangle::Client>> =
-        Rc::new(RefCell::new(tangle::Client::for_node(&node_url).await.unwrap_or_else(
+    let transport: Arc<Mutex<tangle::Client>> =
+        Arc::new(Mutex::new(tangle::Client::for_node(&node_url).await.unwrap_or_else(
             |e| panic!("error connecting Tangle client to '{}': {}", node_url, e),
         )));
 
@@ -149,7 +150,7 @@ async fn main_utangle_client() -> Result<()> {
     );
     println!("\n");
 
-    let transport: Rc<RefCell<utangle::Client>> = Rc::new(RefCell::new(utangle::Client::new(&node_url)));
+    let transport: Arc<Mutex<utangle::Client>> = Arc::new(Mutex::new(utangle::Client::new(&node_url)));
 
     run_basic_scenario(transport.clone(), &new_seed()).await?;
     #[cfg(feature = "did")]
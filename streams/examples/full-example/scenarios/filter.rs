@@ -60,7 +60,7 @@ pub(crate) async fn example<SR, T: GenericTransport<SR>>(transport: T, author_se
     author.send_keyload_for_all_rw(BRANCH1).await?;
 
     let selectors = vec![Selector::Topic(BRANCH1.into())];
-    let msgs = subscriber_b.messages().from(&selectors).await;
+    let msgs = subscriber_b.messages().from(&selectors).await?;
 
     // Find the 2 messages from BRANCH1 (although unencryptable)
     assert!(msgs.len() == 2);
@@ -76,7 +76,7 @@ pub(crate) async fn example<SR, T: GenericTransport<SR>>(transport: T, author_se
         .await?;
 
     let selectors = vec![Selector::Identifier(subscriber_a.identifier().unwrap().clone())];
-    let msgs = subscriber_b.messages().from(&selectors).await;
+    let msgs = subscriber_b.messages().from(&selectors).await?;
 
     // Find only 1 message from Sub a, not the author message
     assert!(msgs.len() == 1);
@@ -0,0 +1,90 @@
+// Rust
+use alloc::sync::Arc;
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use lets::sync::{MaybeSend, MaybeSync};
+
+// Local
+
+/// Source of the current time of a [`User`](crate::User), used to timestamp the messages it
+/// publishes, to expire time-bounded permissions and to schedule key rotations.
+pub trait Clock: MaybeSend + MaybeSync {
+    /// Returns the current `Unix` timestamp in seconds, if the time is known
+    fn now(&self) -> Option<u64>;
+}
+
+/// [`Clock`] reading the system time. Without a system clock (`no_std` or `wasm32` targets), the
+/// time is unknown.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    fn now(&self) -> Option<u64> {
+        extern crate std;
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|elapsed| elapsed.as_secs())
+    }
+
+    #[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+    fn now(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// [`Clock`] whose time is set manually, for tests and simulations. Clones share the same time,
+/// so that the time of a clock given to a [`User`](crate::User) can still be moved forward.
+///
+/// ```
+/// use core::time::Duration;
+/// use streams::{Clock, ManualClock};
+///
+/// let clock = ManualClock::new(1_000);
+/// let user_clock = clock.clone();
+/// clock.advance(Duration::from_secs(60));
+/// assert_eq!(user_clock.now(), Some(1_060));
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    /// Creates a [`ManualClock`] set at the provided time
+    ///
+    /// # Arguments
+    /// * `now`: `Unix` timestamp of the current instant, in seconds
+    pub fn new(now: u64) -> Self {
+        Self(Arc::new(AtomicU64::new(now)))
+    }
+
+    /// Sets the time of the clock
+    ///
+    /// # Arguments
+    /// * `now`: `Unix` timestamp of the current instant, in seconds
+    pub fn set(&self, now: u64) {
+        self.0.store(now, Ordering::SeqCst);
+    }
+
+    /// Moves the time of the clock forward, with a precision of one second
+    ///
+    /// # Arguments
+    /// * `duration`: The time elapsed
+    pub fn advance(&self, duration: Duration) {
+        self.0.fetch_add(duration.as_secs(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Option<u64> {
+        Some(self.0.load(Ordering::SeqCst))
+    }
+}
@@ -0,0 +1,129 @@
+// Rust
+use alloc::vec::Vec;
+
+// 3rd-party
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::api::compression::CODEC_BITS;
+
+/// Identifier of the payloads that are not encoded by a [`Codec`]
+pub(crate) const RAW: u8 = 0;
+
+/// Largest identifier of a [`Codec`]. The most significant bits of the encoding of a message
+/// header identify the [`Compression`](crate::Compression) of its payloads.
+pub(crate) const MAX_ID: u8 = (1 << CODEC_BITS) - 1;
+
+/// Serialization format of typed payloads.
+///
+/// The identifier of the codec is recorded in the header of the messages whose payload it encodes,
+/// so that [`Message::decode()`](crate::Message::decode) can pick the matching decoder. Identifiers
+/// 0 to 15 are reserved for the codecs of this crate; custom codecs must use an identifier from 16
+/// to 63 and be decoded explicitly with [`Message::decode_as()`](crate::Message::decode_as).
+/// Payloads are encoded with a codec using
+/// [`MessageBuilder::with_typed_as()`](crate::MessageBuilder::with_typed_as).
+pub trait Codec {
+    /// Identifier of the codec, recorded in the header of the messages it encodes
+    const ID: u8;
+
+    /// Encodes a value into a payload
+    ///
+    /// # Arguments
+    /// * `value`: The value to encode
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>>;
+
+    /// Decodes a value from a payload
+    ///
+    /// # Arguments
+    /// * `bytes`: The payload to decode
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T>;
+}
+
+/// [`Codec`] encoding payloads as `JSON` documents
+#[cfg(feature = "json-codec")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Json;
+
+#[cfg(feature = "json-codec")]
+impl Codec for Json {
+    const ID: u8 = 1;
+
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+        serde_json::to_vec(value).map_err(anyhow::Error::msg)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+        serde_json::from_slice(bytes).map_err(anyhow::Error::msg)
+    }
+}
+
+/// [`Codec`] encoding payloads in the Concise Binary Object Representation (`CBOR`)
+#[cfg(feature = "cbor-codec")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor-codec")]
+impl Codec for Cbor {
+    const ID: u8 = 2;
+
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+        serde_cbor::to_vec(value).map_err(anyhow::Error::msg)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+        serde_cbor::from_slice(bytes).map_err(anyhow::Error::msg)
+    }
+}
+
+/// [`Codec`] encoding payloads in the compact `postcard` wire format
+#[cfg(feature = "postcard-codec")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Postcard;
+
+#[cfg(feature = "postcard-codec")]
+impl Codec for Postcard {
+    const ID: u8 = 3;
+
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+        postcard::to_allocvec(value).map_err(anyhow::Error::msg)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+        postcard::from_bytes(bytes).map_err(anyhow::Error::msg)
+    }
+}
+
+/// Returns the identifier of the codec recorded in the encoding of a header
+///
+/// # Arguments
+/// * `encoding`: The encoding of the header
+pub(crate) fn id_of(encoding: u8) -> u8 {
+    encoding & MAX_ID
+}
+
+/// Decodes a payload with the codec of this crate identified by `codec`, if enabled
+///
+/// # Arguments
+/// * `codec`: The identifier of the codec recorded in the message header
+/// * `bytes`: The payload to decode
+///
+/// Returns:
+/// The decoded value, or `None` if no enabled codec has the identifier
+pub(crate) fn decode<T: DeserializeOwned>(codec: u8, bytes: &[u8]) -> Option<Result<T>> {
+    // Unused when every codec is disabled
+    let _ = bytes;
+    match codec {
+        #[cfg(feature = "json-codec")]
+        Json::ID => Some(Json::decode(bytes)),
+        #[cfg(feature = "cbor-codec")]
+        Cbor::ID => Some(Cbor::decode(bytes)),
+        #[cfg(feature = "postcard-codec")]
+        Postcard::ID => Some(Postcard::decode(bytes)),
+        _ => None,
+    }
+}
@@ -0,0 +1,121 @@
+// Rust
+use alloc::vec::Vec;
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{Error, Result};
+
+/// Maximum size of a decompressed payload. Payloads that would decompress into more bytes are
+/// rejected, so that a small message cannot exhaust the memory of its readers.
+pub const MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+/// Number of bits of the encoding of a message header identifying the [`Codec`](crate::Codec) of
+/// its payloads. The remaining most significant bits identify their [`Compression`].
+pub(crate) const CODEC_BITS: u8 = 6;
+
+/// Compression of the public and masked payloads of a packet, applied before they are masked.
+///
+/// The compression of a packet is recorded in the two most significant bits of the encoding of its
+/// header, next to the [`Codec`](crate::Codec) of its payloads. Readers decompress the payloads
+/// when the packet is processed, up to [`MAX_DECOMPRESSED_SIZE`] bytes each. Compressing with an
+/// algorithm requires its feature to be enabled, and so does decompressing.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Compression {
+    /// The payloads are not compressed
+    #[default]
+    None,
+    /// The payloads are compressed with `DEFLATE`. Requires the `deflate-compression` feature.
+    Deflate,
+    /// The payloads are compressed with `Zstandard`. Requires the `zstd-compression` feature.
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the identifier of the compression, recorded in the header of the packets
+    pub(crate) fn id(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Deflate => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    /// Returns the [`Compression`] identified by `id`, if any
+    ///
+    /// # Arguments
+    /// * `id`: The identifier of the compression
+    pub(crate) fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Deflate),
+            2 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Returns the encoding of a header recording the compression and the codec of the payloads
+    ///
+    /// # Arguments
+    /// * `codec`: The identifier of the codec of the payloads
+    pub(crate) fn encoding(self, codec: u8) -> u8 {
+        (self.id() << CODEC_BITS) | codec
+    }
+
+    /// Returns the identifier of the compression recorded in the encoding of a header
+    ///
+    /// # Arguments
+    /// * `encoding`: The encoding of the header
+    pub(crate) fn id_of(encoding: u8) -> u8 {
+        encoding >> CODEC_BITS
+    }
+
+    /// Compresses a payload. Empty payloads are left empty.
+    ///
+    /// # Arguments
+    /// * `payload`: The payload to compress
+    pub(crate) fn compress(self, payload: &[u8]) -> Result<Vec<u8>> {
+        if payload.is_empty() {
+            return Ok(Vec::new());
+        }
+        match self {
+            Compression::None => Ok(payload.to_vec()),
+            #[cfg(feature = "deflate-compression")]
+            Compression::Deflate => Ok(miniz_oxide::deflate::compress_to_vec(payload, 6)),
+            #[cfg(feature = "zstd-compression")]
+            Compression::Zstd => zstd::bulk::compress(payload, 0)
+                .map_err(|e| Error::Compression(self.id(), "compress", anyhow::Error::msg(e))),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::UnsupportedCompression(self.id())),
+        }
+    }
+
+    /// Decompresses a payload, failing if it would decompress into more than `max_size` bytes.
+    /// Empty payloads are left empty.
+    ///
+    /// # Arguments
+    /// * `payload`: The payload to decompress
+    /// * `max_size`: The maximum size of the decompressed payload
+    pub(crate) fn decompress(self, payload: &[u8], max_size: usize) -> Result<Vec<u8>> {
+        // Unused when every compression is disabled
+        let _ = max_size;
+        if payload.is_empty() {
+            return Ok(Vec::new());
+        }
+        match self {
+            Compression::None => Ok(payload.to_vec()),
+            #[cfg(feature = "deflate-compression")]
+            Compression::Deflate => miniz_oxide::inflate::decompress_to_vec_with_limit(payload, max_size)
+                .map_err(|e| Error::Compression(self.id(), "decompress", anyhow::Error::msg(e))),
+            #[cfg(feature = "zstd-compression")]
+            Compression::Zstd => zstd::bulk::decompress(payload, max_size)
+                .map_err(|e| Error::Compression(self.id(), "decompress", anyhow::Error::msg(e))),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::UnsupportedCompression(self.id())),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 // Rust
+use alloc::vec::Vec;
 use core::fmt;
 
 // 3rd-party
@@ -9,7 +10,7 @@ use hashbrown::HashMap;
 // Streams
 use lets::{
     address::MsgId,
-    id::{Identifier, Permissioned},
+    id::{Identifier, PermissionDuration, Permissioned},
     message::Topic,
 };
 
@@ -149,6 +150,67 @@ impl CursorStore {
             .and_then(|branch| branch.cursors.insert(id, cursor))
     }
 
+    /// Downgrades the `ReadWrite` permission of an [`Identifier`] in the branch to
+    /// [`Permissioned::Read`] if its [`PermissionDuration`] has run out, keeping its cursor.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch to check.
+    /// * `id`: The [`Identifier`] whose permission is checked.
+    /// * `now`: Current `Unix` timestamp in seconds.
+    ///
+    /// Returns:
+    /// Whether the permission was downgraded.
+    pub(crate) fn expire_permission(&mut self, topic: &Topic, id: &Identifier, now: u64) -> bool {
+        let expired = match self.get_permission(topic, id) {
+            Some(perm) if perm.is_expired(now) => perm.clone(),
+            _ => return false,
+        };
+        if let Some(branch) = self.0.get_mut(topic) {
+            branch.replace_permission(&expired, Permissioned::Read(id.clone()));
+        }
+        true
+    }
+
+    /// Accounts for a packet published in the branch, decrementing the message budgets it consumes:
+    /// every [`PermissionDuration::NumBranchMsgs`] of the branch, and the
+    /// [`PermissionDuration::NumPublishedmsgs`] of the publisher. Permissions whose budget is used
+    /// up are downgraded to [`Permissioned::Read`].
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch the packet was published in.
+    /// * `publisher`: The [`Identifier`] of the packet publisher.
+    pub(crate) fn spend_message_budgets(&mut self, topic: &Topic, publisher: &Identifier) {
+        if let Some(branch) = self.0.get_mut(topic) {
+            let spent: Vec<(Permissioned<Identifier>, Permissioned<Identifier>)> = branch
+                .cursors
+                .keys()
+                .filter_map(|perm| {
+                    let remaining = match perm {
+                        Permissioned::ReadWrite(_, PermissionDuration::NumBranchMsgs(remaining)) => {
+                            PermissionDuration::NumBranchMsgs(remaining.saturating_sub(1))
+                        }
+                        Permissioned::ReadWrite(id, PermissionDuration::NumPublishedmsgs(remaining))
+                            if id == publisher =>
+                        {
+                            PermissionDuration::NumPublishedmsgs(remaining.saturating_sub(1))
+                        }
+                        _ => return None,
+                    };
+                    let id = perm.identifier().clone();
+                    let new = if remaining.is_expired(0) {
+                        Permissioned::Read(id)
+                    } else {
+                        Permissioned::ReadWrite(id, remaining)
+                    };
+                    Some((perm.clone(), new))
+                })
+                .collect();
+            for (old, new) in spent {
+                branch.replace_permission(&old, new);
+            }
+        }
+    }
+
     /// If the [`Topic`] exists, update the latest_link, otherwise insert a new [`Topic`] with the
     /// latest_link.
     ///
@@ -170,6 +232,57 @@ impl CursorStore {
         }
     }
 
+    /// Sets the maximum number of packets that may follow a keyload in the branch, as declared in
+    /// its branch announcement
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    /// * `key_limit`: The maximum number of packets per keyload, if limited.
+    pub(crate) fn set_key_limit(&mut self, topic: &Topic, key_limit: Option<usize>) {
+        if let Some(branch) = self.0.get_mut(topic) {
+            branch.key_limit = key_limit;
+        }
+    }
+
+    /// Returns the maximum number of packets that may follow a keyload in the branch, if limited
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    pub(crate) fn key_limit(&self, topic: &Topic) -> Option<usize> {
+        self.0.get(topic).and_then(|branch| branch.key_limit)
+    }
+
+    /// Returns the number of packets published in the branch since its latest keyload
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    pub(crate) fn packets_under_key(&self, topic: &Topic) -> usize {
+        self.0.get(topic).map_or(0, |branch| branch.packets_under_key)
+    }
+
+    /// Sets the number of packets published in the branch since its latest keyload
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    /// * `packets`: The number of packets published under the current key.
+    pub(crate) fn set_packets_under_key(&mut self, topic: &Topic, packets: usize) {
+        if let Some(branch) = self.0.get_mut(topic) {
+            branch.packets_under_key = packets;
+        }
+    }
+
+    /// Returns true if the branch declares a key limit and as many packets as allowed have already
+    /// been published since its latest keyload
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    pub(crate) fn key_exhausted(&self, topic: &Topic) -> bool {
+        matches!(
+            self.0.get(topic),
+            Some(InnerCursorStore { key_limit: Some(key_limit), packets_under_key, .. }) if packets_under_key >= key_limit
+        )
+    }
+
     /// Get the latest link for a topic, if it exists.
     ///
     /// # Arguments
@@ -190,11 +303,31 @@ pub(crate) struct InnerCursorStore {
     cursors: HashMap<Permissioned<Identifier>, usize>,
     /// Latest message link processed in the branch
     latest_link: MsgId,
+    /// Maximum number of packets that may follow a keyload in the branch, if limited
+    key_limit: Option<usize>,
+    /// Number of packets published in the branch since its latest keyload
+    packets_under_key: usize,
+}
+
+impl InnerCursorStore {
+    /// Swaps a [`Permissioned`] [`Identifier`] for another one, keeping the cursor it was mapped to
+    ///
+    /// # Arguments
+    /// * `old`: The [`Permissioned`] [`Identifier`] to be replaced.
+    /// * `new`: The [`Permissioned`] [`Identifier`] to map the cursor to.
+    fn replace_permission(&mut self, old: &Permissioned<Identifier>, new: Permissioned<Identifier>) {
+        if let Some(cursor) = self.cursors.remove(old) {
+            self.cursors.insert(new, cursor);
+        }
+    }
 }
 
 impl fmt::Debug for InnerCursorStore {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "\t* latest link: {}", self.latest_link)?;
+        if let Some(key_limit) = self.key_limit {
+            writeln!(f, "\t* packets under key: {}/{}", self.packets_under_key, key_limit)?;
+        }
         writeln!(f, "\t* cursors:")?;
         for (id, cursor) in self.cursors.iter() {
             writeln!(f, "\t\t{:?} => {}", id, cursor)?;
@@ -241,4 +374,66 @@ mod tests {
         assert!(branch_store.get_cursor(&topic_1, &identifier).is_none());
         assert!(branch_store.get_cursor(&topic_2, &identifier).is_none());
     }
+
+    #[test]
+    fn branch_store_downgrades_expired_unix_permissions() {
+        let mut branch_store = CursorStore::new();
+        let identifier = Identity::from(Ed25519::from_seed("identifier 1")).identifier().clone();
+        let permission = Permissioned::ReadWrite(identifier.clone(), PermissionDuration::Unix(1_000));
+        let topic = Topic::new("topic".to_string());
+
+        branch_store.new_branch(topic.clone());
+        branch_store.insert_cursor(&topic, permission.clone(), 10);
+
+        assert!(!branch_store.expire_permission(&topic, &identifier, 999));
+        assert_eq!(branch_store.get_permission(&topic, &identifier), Some(&permission));
+
+        assert!(branch_store.expire_permission(&topic, &identifier, 1_000));
+        assert_eq!(
+            branch_store.get_permission(&topic, &identifier),
+            Some(&Permissioned::Read(identifier.clone()))
+        );
+        assert_eq!(branch_store.get_cursor(&topic, &identifier), Some(10));
+    }
+
+    #[test]
+    fn branch_store_spends_message_budgets() {
+        let mut branch_store = CursorStore::new();
+        let publisher = Identity::from(Ed25519::from_seed("identifier 1")).identifier().clone();
+        let other = Identity::from(Ed25519::from_seed("identifier 2")).identifier().clone();
+        let topic = Topic::new("topic".to_string());
+
+        branch_store.new_branch(topic.clone());
+        branch_store.insert_cursor(
+            &topic,
+            Permissioned::ReadWrite(publisher.clone(), PermissionDuration::NumPublishedmsgs(2)),
+            10,
+        );
+        branch_store.insert_cursor(
+            &topic,
+            Permissioned::ReadWrite(other.clone(), PermissionDuration::NumBranchMsgs(1)),
+            20,
+        );
+
+        branch_store.spend_message_budgets(&topic, &publisher);
+        assert_eq!(
+            branch_store.get_permission(&topic, &publisher),
+            Some(&Permissioned::ReadWrite(
+                publisher.clone(),
+                PermissionDuration::NumPublishedmsgs(1)
+            ))
+        );
+        assert_eq!(
+            branch_store.get_permission(&topic, &other),
+            Some(&Permissioned::Read(other.clone()))
+        );
+
+        branch_store.spend_message_budgets(&topic, &publisher);
+        assert_eq!(
+            branch_store.get_permission(&topic, &publisher),
+            Some(&Permissioned::Read(publisher.clone()))
+        );
+        assert_eq!(branch_store.get_cursor(&topic, &publisher), Some(10));
+        assert_eq!(branch_store.get_cursor(&topic, &other), Some(20));
+    }
 }
@@ -0,0 +1,295 @@
+// Rust
+use alloc::sync::Arc;
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+// 3rd-party
+use anyhow::Result;
+use futures::{
+    ready,
+    task::{AtomicWaker, Context, Poll},
+    Stream, StreamExt,
+};
+use futures_timer::Delay;
+
+// IOTA
+
+// Streams
+use lets::{message::TransportMessage, sync::MaybeSend, transport::Transport};
+
+// Local
+use crate::api::{message::Message, messages::Messages};
+
+/// Item of a [`Following`] stream
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum FollowEvent {
+    /// A new message of the channel
+    Message(Message),
+    /// A polling round found no new message. The stream waits for the provided duration before
+    /// polling again. Consecutive idle events act as a heartbeat of the stream.
+    Idle(Duration),
+}
+
+/// A [`Stream`] over the messages of the channel that never terminates by itself: once every
+/// currently available message has been yielded, the channel is polled again periodically and new
+/// messages are yielded as they appear.
+///
+/// The stream is created from a [`Messages`] stream calling [`Messages::follow()`], and ends only
+/// when cancelled through one of its [`CancelHandle`]s:
+///
+/// ```
+/// use core::time::Duration;
+/// use futures::StreamExt;
+///
+/// use streams::{id::Ed25519, transport::utangle, FollowEvent, Result, User};
+///
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use streams::transport::bucket;
+/// #
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// # let test_transport = Rc::new(RefCell::new(bucket::Client::new()));
+/// let subscriber_transport: utangle::Client =
+///     utangle::Client::new("https://chrysalis-nodes.iota.org");
+/// # let subscriber_transport = test_transport.clone();
+/// let mut subscriber = User::builder()
+///     .with_identity(Ed25519::from_seed(
+///         "cryptographically-secure-random-subscriber-seed",
+///     ))
+///     .with_transport(subscriber_transport)
+///     .build();
+///
+/// let mut following = subscriber
+///     .messages()
+///     .follow()
+///     .with_poll_interval(Duration::from_millis(500), Duration::from_secs(10));
+/// let cancel = following.cancel_handle();
+/// # cancel.cancel();
+/// while let Some(event) = following.next().await {
+///     match event {
+///         Ok(FollowEvent::Message(msg)) => println!("New message at {}", msg.address()),
+///         Ok(FollowEvent::Idle(wait)) => println!("No new message, polling again in {:?}", wait),
+///         Err(e) => println!("Failed to fetch messages, retrying: {}", e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Technical Details
+/// The channel is polled again right after a message is yielded. After a polling round finds no
+/// new message, or fails, the stream waits before polling again. The wait starts at the poll
+/// interval and is multiplied by the backoff factor after every consecutive idle or failed round,
+/// up to the maximum interval. It is reset as soon as a new message is found.
+///
+/// Errors are yielded like in [`Messages`], but do not end the stream: the failed fetch is retried
+/// at the next polling round.
+pub struct Following<'a, T> {
+    /// Stream of the currently available messages, started over at every polling round
+    messages: Messages<'a, T>,
+    /// Wait after the first idle or failed round
+    poll_interval: Duration,
+    /// Upper bound of the wait between two polling rounds
+    max_poll_interval: Duration,
+    /// Factor the wait is multiplied by after every consecutive idle or failed round
+    backoff_factor: u32,
+    /// Wait before the next polling round if it is idle or fails
+    next_wait: Duration,
+    /// Timer of the wait in progress, if any
+    waiting: Option<Delay>,
+    /// Whether a fetch of the inner [`Messages`] stream is in progress
+    fetching: bool,
+    /// Cancellation state shared with the [`CancelHandle`]s
+    cancellation: Arc<Cancellation>,
+}
+
+/// Cancellation state of a [`Following`] stream
+#[derive(Default)]
+struct Cancellation {
+    /// Whether the stream has been cancelled
+    cancelled: AtomicBool,
+    /// Waker of the task polling the stream
+    waker: AtomicWaker,
+}
+
+/// Handle to gracefully end a [`Following`] stream, possibly from another task
+#[derive(Clone)]
+pub struct CancelHandle(Arc<Cancellation>);
+
+impl CancelHandle {
+    /// Ends the [`Following`] stream. A fetch in progress is completed, and its message yielded,
+    /// before the stream ends. A wait in progress is interrupted.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        self.0.waker.wake();
+    }
+
+    /// Returns whether the [`Following`] stream has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+}
+
+impl<'a, T> Following<'a, T> {
+    /// Creates a [`Following`] stream polling the channel every second when idle, backing off up to
+    /// 30 seconds
+    ///
+    /// # Arguments
+    /// * `messages`: The stream of the currently available messages of the channel
+    pub(crate) fn new(messages: Messages<'a, T>) -> Self {
+        let poll_interval = Duration::from_secs(1);
+        Self {
+            messages,
+            poll_interval,
+            max_poll_interval: Duration::from_secs(30),
+            backoff_factor: 2,
+            next_wait: poll_interval,
+            waiting: None,
+            fetching: false,
+            cancellation: Arc::new(Cancellation::default()),
+        }
+    }
+
+    /// Sets the wait after the first idle or failed polling round, and the upper bound of the wait
+    /// between two polling rounds
+    ///
+    /// # Arguments
+    /// * `interval`: Wait after the first idle or failed round
+    /// * `max_interval`: Maximum wait between two rounds
+    pub fn with_poll_interval(mut self, interval: Duration, max_interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self.max_poll_interval = max_interval.max(interval);
+        self.next_wait = interval;
+        self
+    }
+
+    /// Sets the factor the wait is multiplied by after every consecutive idle or failed polling
+    /// round. A factor of 1 polls at a fixed interval.
+    ///
+    /// # Arguments
+    /// * `backoff_factor`: Growth factor of the wait
+    pub fn with_backoff_factor(mut self, backoff_factor: u32) -> Self {
+        self.backoff_factor = backoff_factor.max(1);
+        self
+    }
+
+    /// Returns a [`CancelHandle`] to end the stream
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle(self.cancellation.clone())
+    }
+
+    /// Starts a wait before the next polling round, returning its duration
+    fn wait(&mut self) -> Duration {
+        let wait = self.next_wait;
+        self.waiting = Some(Delay::new(wait));
+        self.next_wait = wait
+            .checked_mul(self.backoff_factor)
+            .map_or(self.max_poll_interval, |next_wait| {
+                next_wait.min(self.max_poll_interval)
+            });
+        wait
+    }
+}
+
+impl<'a, T, TSR> Following<'a, T>
+where
+    T: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+    TSR: MaybeSend,
+{
+    /// Returns the next event of the channel, or `None` once the stream has been cancelled
+    pub async fn next(&mut self) -> Option<Result<FollowEvent>> {
+        StreamExt::next(self).await
+    }
+}
+
+impl<'a, T, TSR> Stream for Following<'a, T>
+where
+    T: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+    TSR: MaybeSend,
+{
+    type Item = Result<FollowEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.cancellation.waker.register(ctx.waker());
+        if this.cancellation.cancelled.load(Ordering::Acquire) && !this.fetching {
+            return Poll::Ready(None);
+        }
+
+        if let Some(waiting) = &mut this.waiting {
+            ready!(Pin::new(waiting).poll(ctx));
+            this.waiting = None;
+        }
+
+        let fetched = Pin::new(&mut this.messages).poll_next(ctx);
+        this.fetching = fetched.is_pending();
+        Poll::Ready(Some(match ready!(fetched) {
+            Some(Ok(msg)) => {
+                this.next_wait = this.poll_interval;
+                Ok(FollowEvent::Message(msg))
+            }
+            Some(Err(e)) => {
+                this.wait();
+                Err(e)
+            }
+            None => Ok(FollowEvent::Idle(this.wait())),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lets::id::Ed25519;
+
+    use crate::api::{message::MessageContent, test_transport::transport, user::User};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn following_backs_off_while_idle_and_ends_when_cancelled() -> Result<()> {
+        let p = b"payload";
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_transport(transport)
+            .build();
+        subscriber.receive_message(announcement.address()).await?;
+        let subscription = subscriber.subscribe().await?;
+        author.receive_message(subscription.address()).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+
+        let ms = Duration::from_millis;
+        let mut following = subscriber.messages().follow().with_poll_interval(ms(1), ms(4));
+        let cancel = following.cancel_handle();
+        assert!(matches!(following.next().await, Some(Ok(FollowEvent::Message(_)))));
+        for wait in [1, 2, 4, 4] {
+            assert!(matches!(following.next().await, Some(Ok(FollowEvent::Idle(idle))) if idle == ms(wait)));
+        }
+
+        // New messages keep being yielded, and reset the backoff
+        author.send_signed_packet("BASE_BRANCH", &p, &p).await?;
+        assert!(matches!(
+            following.next().await,
+            Some(Ok(FollowEvent::Message(Message {
+                content: MessageContent::SignedPacket(_),
+                ..
+            })))
+        ));
+        assert!(matches!(following.next().await, Some(Ok(FollowEvent::Idle(idle))) if idle == ms(1)));
+
+        cancel.cancel();
+        assert!(following.next().await.is_none());
+        Ok(())
+    }
+}
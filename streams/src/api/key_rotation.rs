@@ -0,0 +1,108 @@
+// Rust
+use core::time::Duration;
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+
+// Local
+
+/// Policy rotating the key of a branch, by publishing a new keyload for the recipients of the
+/// latest one before the next packet is sent by the branch admin.
+///
+/// Every keyload of the branch, whoever publishes it, resets the rotation schedule.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum KeyRotation {
+    /// The key is rotated once the provided number of packets have been published in the branch
+    /// under the current key
+    Messages(usize),
+    /// The key is rotated once it has been in use for the provided duration, with a precision of
+    /// one second. Requires a system clock.
+    Interval(Duration),
+    /// The key is only rotated when requested with
+    /// [`User::request_key_rotation()`](crate::User::request_key_rotation)
+    OnDemand,
+}
+
+/// Rotation schedule of the key of a branch
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct RotationSchedule {
+    /// The rotation policy of the branch
+    rotation: KeyRotation,
+    /// Number of packets published in the branch under the current key
+    messages: usize,
+    /// `Unix` timestamp of the instant the current key was put in use, if known
+    since: Option<u64>,
+    /// Whether a rotation has been requested
+    requested: bool,
+}
+
+impl RotationSchedule {
+    /// Creates a [`RotationSchedule`] for a key put in use at the provided instant
+    ///
+    /// # Arguments
+    /// * `rotation`: The rotation policy of the branch
+    /// * `now`: `Unix` timestamp of the current instant, if known
+    pub(crate) fn new(rotation: KeyRotation, now: Option<u64>) -> Self {
+        Self {
+            rotation,
+            messages: 0,
+            since: now,
+            requested: false,
+        }
+    }
+
+    /// Restores a [`RotationSchedule`] from its parts
+    pub(crate) fn from_parts(rotation: KeyRotation, messages: usize, since: Option<u64>, requested: bool) -> Self {
+        Self {
+            rotation,
+            messages,
+            since,
+            requested,
+        }
+    }
+
+    /// Returns the parts of the [`RotationSchedule`]: the policy, the number of packets published
+    /// under the current key, the instant it was put in use and whether a rotation is requested
+    pub(crate) fn parts(&self) -> (KeyRotation, usize, Option<u64>, bool) {
+        (self.rotation, self.messages, self.since, self.requested)
+    }
+
+    /// Counts a packet published under the current key
+    pub(crate) fn record_message(&mut self) {
+        self.messages += 1;
+    }
+
+    /// Requests the rotation of the key before the next packet
+    pub(crate) fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Resets the schedule for a new key put in use at the provided instant
+    ///
+    /// # Arguments
+    /// * `now`: `Unix` timestamp of the current instant, if known
+    pub(crate) fn reset(&mut self, now: Option<u64>) {
+        self.messages = 0;
+        self.since = now;
+        self.requested = false;
+    }
+
+    /// Returns whether the key is due for rotation at the provided instant
+    ///
+    /// # Arguments
+    /// * `now`: `Unix` timestamp of the current instant, if known
+    pub(crate) fn is_due(&self, now: Option<u64>) -> bool {
+        self.requested
+            || match self.rotation {
+                KeyRotation::Messages(messages) => self.messages >= messages,
+                KeyRotation::Interval(interval) => match (self.since, now) {
+                    (Some(since), Some(now)) => now.saturating_sub(since) >= interval.as_secs(),
+                    _ => false,
+                },
+                KeyRotation::OnDemand => false,
+            }
+    }
+}
@@ -0,0 +1,127 @@
+//! Stream published by the implementation of Streams version 2, the previous generation of the
+//! protocol, captured from its transport. The author, seeded with `"legacy author"`, announced the
+//! stream in the `BASE_BRANCH` branch and granted the subscriber, seeded with
+//! `"legacy subscriber"`, access to it with a keyload before publishing a signed packet. It then
+//! branched off to the `ARCHIVE` branch, where it published a keyload for the subscriber and a
+//! tagged packet. The author was also backed up right after it announced the stream.
+
+// Rust
+use alloc::vec::Vec;
+
+// 3rd-party
+use anyhow::Result;
+
+// IOTA
+
+// Streams
+use lets::{address::Address, message::TransportMessage, transport::Transport as _};
+
+// Local
+use crate::api::test_transport::Transport;
+
+/// Seed of the identity of the subscriber of the stream
+pub(crate) const SUBSCRIBER_SEED: &str = "legacy subscriber";
+
+/// Addresses and hexadecimal bodies of the messages of the stream, in order of publication
+const MESSAGES: &[(&str, &str)] = &[
+    // Announcement
+    (
+        "284dc0fab2d606502f064c77cd509fdd24c6c6719ce75dc29add592eb6f6f580d08dbdbef4b67eab:49ef40969b41f740fa6e2933",
+        concat!(
+            "0002000004000000002355d7aeec2f9b24820935e34a586e3e009671f89c3e50f421339274db32b280a46a67546834d8",
+            "8c0775e5d49ddd3d0cab0070b34cfcabde1793fea16d338815f7ce0decdba3368f375eea84b7a21a2889200e000001c7",
+            "213c375b8ec7c00869435748804791d13cd88c8dfad25e0e0eb7b90a6c39bde917d120318dfa9c14cab847f52700aede",
+            "691c632222fe7addeda79c2229cdcede41190d9d0960e10079e8e2301070d0edbf5fd7426e48e5b9d3718e4d42b08167",
+            "3903f2da9b112f0859ae2c16500f",
+        ),
+    ),
+    // Keyload of the base branch
+    (
+        "284dc0fab2d606502f064c77cd509fdd24c6c6719ce75dc29add592eb6f6f580d08dbdbef4b67eab:e32ec21ad9dc85b745c64ac7",
+        concat!(
+            "00022000040000000149ef40969b41f740fa6e29332355d7aeec2f9b24820935e34a586e3e009671f89c3e50f4213392",
+            "74db32b280a46a67546834d88c0775e5d49ddd3d0cab0102bd140a3b91569b0d8195348b9b8cc789a2f868d40dbb9c42",
+            "90af17f0936f23690e0000014520654edc24a3c9da5765f406cf66320101d02c0ffd26932018e5eba988188b86839a4b",
+            "3e617c947ceb4688d2a35a5916739969a2e35854f897d729c5533fd78571c0e8b73271eddb2d2b006d957493ab10ce71",
+            "5e4909069e4e0f4321de67b1ec7e9c422dbe8b3a7e7d2c9519ab6e87453ba48b00001f7669e11e93ce5ea3e54642644b",
+            "b6d3d7aed6b2de72716ddfbe52b81cb8b93ec92fba65eb40f1b8d8aabc43f751cbb9fd792c49f6640b15edabae8a1848",
+            "a50e",
+        ),
+    ),
+    // Signed packet
+    (
+        "284dc0fab2d606502f064c77cd509fdd24c6c6719ce75dc29add592eb6f6f580d08dbdbef4b67eab:4afd943a3addb3cda4a351b9",
+        concat!(
+            "000230000400000001e32ec21ad9dc85b745c64ac72355d7aeec2f9b24820935e34a586e3e009671f89c3e50f4213392",
+            "74db32b280a46a67546834d88c0775e5d49ddd3d0cab0103e9099a15da0073927aa704ab5d7c6c870f353805b302a26a",
+            "b8a4e45ab5cc43770e000001bff849dc67848729f6e5a5332842e72e9dd5b4d49a41777189e8a9d299df5132bb010f61",
+            "72636869766564207075626c69639bb7d497f6e1eed69e4a818b285eed620300395dd47af3924b3e4387508dc1298a5d",
+            "bd2c5d1d6ba833d6488f048e49bcdc29e503da9eb16ae339f22ebaf2dcd75fddfa0bb0f90d32fdca44e66e692f404a01",
+        ),
+    ),
+    // Branch announcement
+    (
+        "284dc0fab2d606502f064c77cd509fdd24c6c6719ce75dc29add592eb6f6f580d08dbdbef4b67eab:340221b060d6a4a2436565db",
+        concat!(
+            "0002100004000000014afd943a3addb3cda4a351b92355d7aeec2f9b24820935e34a586e3e009671f89c3e50f4213392",
+            "74db32b280a46a67546834d88c0775e5d49ddd3d0cab0104498a17a0e97556d6c4f77701925c2f6113cbb35b62b342eb",
+            "5945529e78a883840e0000019013f33d5204e3ec51f12ef0a6583d31db0bbd43cba145409a0f4bf105c10711cf5549d4",
+            "4d019c5295610027c2943e3ef8d0361ea99354d2577a5a9464ac0c45b94eced873a33333ee0029b8b76a2e2495bcd023",
+            "a741c3a44699dac1283fb3825722dbdb7970ea8e304206",
+        ),
+    ),
+    // Keyload of the archive branch
+    (
+        "284dc0fab2d606502f064c77cd509fdd24c6c6719ce75dc29add592eb6f6f580d08dbdbef4b67eab:f5e4209b1688a26e0e590a6f",
+        concat!(
+            "000220000400000001340221b060d6a4a2436565dbabad14056273b4df046e7799d5f7791f009671f89c3e50f4213392",
+            "74db32b280a46a67546834d88c0775e5d49ddd3d0cab01022448337681d859dbbdc1bb184ca11445fc9c549fc8fca4a7",
+            "bc7f7d853a099a270e000001e309786e660c5afa737a9f758b89cce20101f242794a456ea4adccc914ae132ed34c089a",
+            "88481896759f0c39532ed3ce6f8fce5a0e0a79d1ca2ed0f5137c0a9cead1299845b66709a7c2601275c5dafb5afaa85f",
+            "05b5d5fb16157884bbdd6feedadae4fa9e485422f5125163429235244387ce9300006e326ddd9024fc1d09a865c3b995",
+            "3e74391c91dba622277b71bf35da47785b58bd261cad3d6becbfa15a1a79859996e0a4c5202310d543b0b7f0f4404602",
+            "4e07",
+        ),
+    ),
+    // Tagged packet
+    (
+        "284dc0fab2d606502f064c77cd509fdd24c6c6719ce75dc29add592eb6f6f580d08dbdbef4b67eab:9608616dfd7b6e3abd0dcf8c",
+        concat!(
+            "000240000400000001f5e4209b1688a26e0e590a6fabad14056273b4df046e7799d5f7791f009671f89c3e50f4213392",
+            "74db32b280a46a67546834d88c0775e5d49ddd3d0cab0103b3ed54d267fa597b0bcd22a22cb1cec81e68d2dabca93d17",
+            "ff6c71b4a4fd51a90e000001010f6172636869766564207461676765641bbce258fb1b5c43ed87a2e1d7fb7bb6dd848f",
+            "8912c392d5ef5031ca674e850dc31e5a0875196a9102cf27c5cc883fb696",
+        ),
+    ),
+];
+
+/// Password the backup of the author is encrypted with
+pub(crate) const BACKUP_PASSWORD: &str = "legacy password";
+
+/// Hexadecimal backup of the author, made right after it announced the stream
+pub(crate) const AUTHOR_BACKUP: &str = concat!(
+    "0bd52090a4158450e3a438569cf70ee74fcff5cf1a582ac3e1797fbf4bbc59a073b481c473b93314497b79d1b8cd95ed",
+    "386c57294bcec5daef8210c0d230486fc559407634cee6ddc003fdcefba1ba77b189cee6db984f52007f8d8293f23614",
+    "3ad22df5a2a2cb8a0c1911996d1cb9d96466705dbd63352aed400911fb71e3c8736b896bd1c05c8eae734d0045ffe48a",
+    "4c80bc4b8b38ee2e992e2fcfc479488bac70a22a19018a4381884940de55929715a3addf402a79d6e98af1736a6d55a1",
+    "8edf7e92b77d80eda1fdf6caa54357d31dd6faabaf1bc38f1639c09cc1217d0aabdb21ca9d78f28e778cda1b39daee86",
+    "cc17f97026c881be255d488faa05459de10aeee770b0d6666a28c038cfc7e400f91555abfbca27271fba92c8b75049f3",
+    "1c2ce0a635975a7e6516ae5da5cce4825fcd62bc3f506b93071e29ba843ef909c665cdbf6c97bc40027e8ba56654890a",
+    "e565cfd203730cb913d681a078679377a93e478ea19cefb625f512bdd27621f74588de33e074c7fb55e2a0189610bc02",
+    "1b8a2954f8102c42da5d2a76cef3f00c7f1e2e9563779ab10c463d19f12e8843a2db6efa846943ac8c2871a67ed33a13",
+    "d518586a0b70ee2ba22a3d85ac9333d86966e4d0d07d54b8596b8c0d4a7f1dbc9b71bdc79314c9d9134befc5e2d4e6dc",
+);
+
+/// Publishes the messages of the stream in the transport, returning the address of its announcement
+///
+/// # Arguments
+/// * `transport`: The transport to publish the messages in
+pub(crate) async fn publish(transport: &mut Transport) -> Result<Address> {
+    for (address, body) in MESSAGES {
+        let body: Vec<u8> = hex::decode(body)?;
+        transport
+            .send_message(address.parse()?, TransportMessage::new(body))
+            .await?;
+    }
+    Ok(MESSAGES[0].0.parse()?)
+}
@@ -1,7 +1,11 @@
 // Rust
-use alloc::vec::Vec;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 
 // 3rd-party
+use serde::de::DeserializeOwned;
 
 // IOTA
 
@@ -13,8 +17,16 @@ use lets::{
 };
 
 // Local
-use crate::message::{
-    announcement, branch_announcement, keyload, signed_packet, subscription, tagged_packet, unsubscription,
+use crate::{
+    api::{
+        codec::{self, Codec},
+        compression::{Compression, MAX_DECOMPRESSED_SIZE},
+    },
+    message::{
+        announcement, attachment::Attachment, branch_announcement, custom, keyload, signed_packet, subscription,
+        tagged_packet, unsubscription,
+    },
+    Error, Result,
 };
 
 /// A processed Streams message
@@ -26,6 +38,9 @@ pub struct Message {
     pub header: HDF,
     /// The message payload
     pub content: MessageContent,
+    /// The error met while decompressing the payloads of a packet, if any. The payloads are then
+    /// left as they were received.
+    pub decompression_error: Option<String>,
 }
 
 impl Message {
@@ -46,6 +61,7 @@ impl Message {
             address,
             header: parts.0,
             content: parts.1.into_content().into(),
+            decompression_error: None,
         }
     }
 
@@ -67,6 +83,7 @@ impl Message {
                 cursor: parts.3,
                 message: parts.1,
             }),
+            decompression_error: None,
         }
     }
 
@@ -125,6 +142,11 @@ impl Message {
         matches!(self.content, MessageContent::Unsubscription { .. })
     }
 
+    /// Returns true if the message is a [`MessageContent`]`::Custom`
+    pub fn is_custom(&self) -> bool {
+        matches!(self.content, MessageContent::Custom { .. })
+    }
+
     /// Returns true if the message is a [`MessageContent`]`::Orphan`
     pub fn is_orphan(&self) -> bool {
         matches!(self.content, MessageContent::Orphan { .. })
@@ -193,6 +215,15 @@ impl Message {
         }
     }
 
+    /// If the message is a `Custom` message return it as one
+    pub fn as_custom(&self) -> Option<&Custom> {
+        if let MessageContent::Custom(custom) = &self.content {
+            Some(custom)
+        } else {
+            None
+        }
+    }
+
     /// If the message is an `Orphan` return it as one
     pub fn as_orphan(&self) -> Option<&Orphan> {
         if let MessageContent::Orphan(orphan) = &self.content {
@@ -225,6 +256,125 @@ impl Message {
             _ => None,
         }
     }
+
+    /// Get the attachments of the message
+    ///
+    /// If the message is a [`MessageContent`]`::TaggedPacket` or [`MessageContent`]`::SignedPacket`
+    /// it returns `Some(attachments)`, otherwise returns `None`.
+    pub fn attachments(&self) -> Option<&[Attachment]> {
+        match &self.content {
+            MessageContent::TaggedPacket(TaggedPacket { attachments, .. })
+            | MessageContent::SignedPacket(SignedPacket { attachments, .. }) => Some(attachments),
+            _ => None,
+        }
+    }
+
+    /// Get the attachment of the message with the provided name, if any
+    ///
+    /// # Arguments
+    /// * `name`: The name of the attachment
+    pub fn attachment(&self, name: &str) -> Option<&Attachment> {
+        self.attachments()?.iter().find(|attachment| attachment.name() == name)
+    }
+
+    /// Returns the Streams version the message is written with, as recorded in its header. Messages
+    /// of older versions are read as they were written, see [`lets::message::STREAMS_VER_2`].
+    pub fn version(&self) -> u8 {
+        self.header.version
+    }
+
+    /// Returns the `Unix` timestamp in seconds at which the message was published, if the publisher
+    /// declared one. The timestamp is authenticated with the message, and signed in signed
+    /// messages, but it is only as accurate as the [`Clock`](crate::Clock) of the publisher.
+    pub fn timestamp(&self) -> Option<u64> {
+        self.header.timestamp()
+    }
+
+    /// Returns the identifier of the [`Codec`] the payload of the message is encoded with, as
+    /// recorded in its header. Payloads that are not encoded by a codec are identified by 0.
+    pub fn codec(&self) -> u8 {
+        codec::id_of(self.header.encoding())
+    }
+
+    /// Returns the [`Compression`] the payloads of the message were sent with, as recorded in its
+    /// header, or `None` if the compression is unknown. Payloads are decompressed when the message
+    /// is processed, see [`Message::decompression_error`].
+    pub fn compression(&self) -> Option<Compression> {
+        Compression::from_id(Compression::id_of(self.header.encoding()))
+    }
+
+    /// Returns the error met while decompressing the payloads of the message when it was processed,
+    /// if any. The payloads are then left as they were received.
+    pub fn decompression_error(&self) -> Option<&str> {
+        self.decompression_error.as_deref()
+    }
+
+    /// Decompresses the public and masked payloads of a packet with the [`Compression`] recorded in
+    /// its header. If either payload cannot be decompressed, both are left as they were received
+    /// and the error is recorded in the message.
+    pub(crate) fn decompress_payloads(mut self) -> Self {
+        let compression = self.compression();
+        let (public_payload, masked_payload) = match &mut self.content {
+            MessageContent::SignedPacket(SignedPacket {
+                public_payload,
+                masked_payload,
+                ..
+            })
+            | MessageContent::TaggedPacket(TaggedPacket {
+                public_payload,
+                masked_payload,
+                ..
+            }) => (public_payload, masked_payload),
+            _ => return self,
+        };
+        let decompressed = match compression {
+            Some(Compression::None) => return self,
+            Some(compression) => compression
+                .decompress(public_payload, MAX_DECOMPRESSED_SIZE)
+                .and_then(|public| Ok((public, compression.decompress(masked_payload, MAX_DECOMPRESSED_SIZE)?))),
+            None => Err(Error::UnsupportedCompression(Compression::id_of(
+                self.header.encoding(),
+            ))),
+        };
+        match decompressed {
+            Ok((public, masked)) => {
+                *public_payload = public;
+                *masked_payload = masked;
+            }
+            Err(e) => self.decompression_error = Some(e.to_string()),
+        }
+        self
+    }
+
+    /// Decodes the payload of a packet into a typed value, with the codec of this crate recorded in
+    /// the message header. The masked payload is decoded, or the public payload if the masked one
+    /// is empty.
+    ///
+    /// Returns:
+    /// The decoded value, or [`Error::UnsupportedCodec`] if the codec is custom or disabled
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T> {
+        let payload = self.typed_payload()?;
+        codec::decode(self.codec(), payload)
+            .ok_or(Error::UnsupportedCodec(self.codec()))?
+            .map_err(|e| Error::Codec(self.codec(), "decode", e))
+    }
+
+    /// Decodes the payload of a packet into a typed value with the provided [`Codec`], whatever the
+    /// codec recorded in the message header. The masked payload is decoded, or the public payload
+    /// if the masked one is empty.
+    pub fn decode_as<C: Codec, T: DeserializeOwned>(&self) -> Result<T> {
+        C::decode(self.typed_payload()?).map_err(|e| Error::Codec(C::ID, "decode", e))
+    }
+
+    /// Returns the payload of a packet carrying a typed value: the masked payload, or the public
+    /// payload if the masked one is empty
+    fn typed_payload(&self) -> Result<&[u8]> {
+        match (self.public_payload(), self.masked_payload()) {
+            (_, Some(masked)) if !masked.is_empty() => Ok(masked),
+            (Some(public), _) => Ok(public),
+            _ => Err(Error::NoPayload(self.address)),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -236,6 +386,7 @@ pub enum MessageContent {
     TaggedPacket(TaggedPacket),
     Subscription(Subscription),
     Unsubscription(Unsubscription),
+    Custom(Custom),
     Orphan(Orphan),
 }
 
@@ -247,6 +398,8 @@ pub struct Announcement {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct BranchAnnouncement {
     pub topic: Topic,
+    /// The maximum number of packets that may follow a keyload in the branch, if limited
+    pub max_packets_per_keyload: Option<usize>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -276,6 +429,8 @@ pub struct SignedPacket {
     pub masked_payload: Vec<u8>,
     /// A payload that was not encrypted
     pub public_payload: Vec<u8>,
+    /// Attachments that were encrypted
+    pub attachments: Vec<Attachment>,
 }
 
 /// Tagged Packet [`Message`].
@@ -285,6 +440,8 @@ pub struct TaggedPacket {
     pub masked_payload: Vec<u8>,
     /// A payload that was not encrypted
     pub public_payload: Vec<u8>,
+    /// Attachments that were encrypted
+    pub attachments: Vec<Attachment>,
 }
 
 /// Subscription [`Message`].
@@ -315,6 +472,15 @@ impl Unsubscription {
     }
 }
 
+/// Custom [`Message`], of a type defined by the application.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Custom {
+    /// The type of the message, in [`CUSTOM_MESSAGE_TYPES`](crate::CUSTOM_MESSAGE_TYPES)
+    pub message_type: u8,
+    /// The content of the message, as unwrapped by the [`MessageHandler`](crate::MessageHandler)
+    pub content: Vec<u8>,
+}
+
 /// Orphan [`Message`].
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Orphan {
@@ -335,6 +501,7 @@ impl From<announcement::Unwrap> for MessageContent {
 impl<'a> From<branch_announcement::Unwrap<'a>> for MessageContent {
     fn from(branch_announcement: branch_announcement::Unwrap<'a>) -> Self {
         Self::BranchAnnouncement(BranchAnnouncement {
+            max_packets_per_keyload: branch_announcement.max_packets_per_keyload(),
             topic: branch_announcement.into_new_topic(),
         })
     }
@@ -361,10 +528,12 @@ impl<'a> From<signed_packet::Unwrap<'a>> for MessageContent {
     fn from(mut signed_packet: signed_packet::Unwrap<'a>) -> Self {
         let masked_payload = signed_packet.take_masked_payload();
         let public_payload = signed_packet.take_public_payload();
+        let attachments = signed_packet.take_attachments();
         Self::SignedPacket(SignedPacket {
             publisher_identifier: signed_packet.into_publisher_identifier(),
             masked_payload,
             public_payload,
+            attachments,
         })
     }
 }
@@ -374,6 +543,7 @@ impl<'a> From<tagged_packet::Unwrap<'a>> for MessageContent {
         Self::TaggedPacket(TaggedPacket {
             masked_payload: tagged_packet.take_masked_payload(),
             public_payload: tagged_packet.take_public_payload(),
+            attachments: tagged_packet.take_attachments(),
         })
     }
 }
@@ -385,3 +555,12 @@ impl<'a> From<unsubscription::Unwrap<'a>> for MessageContent {
         })
     }
 }
+
+impl<'a> From<custom::Unwrap<'a>> for MessageContent {
+    fn from(custom: custom::Unwrap<'a>) -> Self {
+        Self::Custom(Custom {
+            message_type: custom.message_type(),
+            content: custom.into_content(),
+        })
+    }
+}
@@ -1,6 +1,20 @@
-use crate::{Error, Result, SendResponse, User};
+use alloc::{string::String, vec::Vec};
+
+use serde::Serialize;
+
+#[cfg(feature = "json-codec")]
+use crate::api::codec::Json;
+use crate::{
+    api::{
+        codec::{self, Codec},
+        compression::Compression,
+    },
+    message::attachment::Attachment,
+    Error, Result, SendResponse, User,
+};
 use lets::{
     message::{Topic, TransportMessage},
+    sync::MaybeSend,
     transport::Transport,
 };
 
@@ -16,6 +30,16 @@ pub struct MessageBuilder<'a, P, Trans> {
     topic: Topic,
     /// A payload to be sent to the channel
     payload: P,
+    /// The identifier of the [`Codec`] the payload is encoded with (defaults to raw bytes)
+    codec: u8,
+    /// The [`Compression`] applied to the payloads (defaults to none)
+    compression: Compression,
+    /// A payload to be sent unmasked, regardless of the private flag
+    public_payload: Option<Vec<u8>>,
+    /// A payload to be sent masked, regardless of the private flag
+    masked_payload: Option<Vec<u8>>,
+    /// Named blobs to be sent masked along with the payloads
+    attachments: Vec<Attachment>,
 }
 
 impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
@@ -34,6 +58,11 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
             signed: false,
             topic,
             payload: P::default(),
+            codec: codec::RAW,
+            compression: Compression::None,
+            public_payload: None,
+            masked_payload: None,
+            attachments: Vec::new(),
         }
     }
 
@@ -59,6 +88,17 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
         P: AsRef<[u8]>,
     {
         self.payload = payload;
+        self.codec = codec::RAW;
+        self
+    }
+
+    /// Sets the [`Compression`] applied to the public and masked payloads before they are sent.
+    /// Readers decompress the payloads when they process the message.
+    ///
+    /// # Arguments
+    /// * compression - The compression applied to the payloads
+    pub fn compressed(mut self, compression: Compression) -> Self {
+        self.compression = compression;
         self
     }
 
@@ -74,7 +114,7 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
 
     /// Sends the message payload to the specified branch using the User Client. If the message is
     /// signed, the message will be sent as a Signed Packet, and if not, it will be sent as a
-    /// Tagged Packet.
+    /// Tagged Packet. The message must carry at least a payload or an attachment.
     ///
     ///
     /// # Examples
@@ -108,12 +148,9 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
     pub async fn send<TSR>(self) -> Result<SendResponse<TSR>>
     where
         P: AsRef<[u8]>,
-        Trans: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR>,
+        Trans: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+        TSR: MaybeSend,
     {
-        if self.payload.as_ref().is_empty() {
-            return Err(Error::PayloadEmpty);
-        }
-
         let mut public: &[u8] = &[];
         let mut private: &[u8] = &[];
 
@@ -122,17 +159,161 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
         } else {
             public = self.payload.as_ref()
         }
+        if let Some(public_payload) = &self.public_payload {
+            public = public_payload;
+        }
+        if let Some(masked_payload) = &self.masked_payload {
+            private = masked_payload;
+        }
+
+        if public.is_empty() && private.is_empty() && self.attachments.is_empty() {
+            return Err(Error::PayloadEmpty);
+        }
 
+        let public = self.compression.compress(public)?;
+        let private = self.compression.compress(private)?;
+        let encoding = self.compression.encoding(self.codec);
         if self.signed {
-            self.user.send_signed_packet(self.topic, public, private).await
+            self.user
+                .send_encoded_signed_packet(self.topic, &public, &private, &self.attachments, encoding)
+                .await
         } else {
-            self.user.send_tagged_packet(self.topic, public, private).await
+            self.user
+                .send_encoded_tagged_packet(self.topic, &public, &private, &self.attachments, encoding)
+                .await
         }
     }
 }
 
+impl<'a, Trans> MessageBuilder<'a, Vec<u8>, Trans> {
+    /// Inject a payload that will not be masked in transit, whatever the private flag. It replaces
+    /// the data payload if the message is public, so that a message can carry both a public part
+    /// and a masked part.
+    ///
+    /// # Arguments
+    /// * payload - The payload that will be sent unmasked
+    ///
+    /// # Examples
+    /// ```
+    /// # use streams::{id::Ed25519, transport::bucket, User, Result};
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let mut user = User::builder()
+    /// #    .with_identity(Ed25519::from_seed("cryptographically-secure-random-user-seed"))
+    /// #    .with_transport(bucket::Client::new())
+    /// #    .build();
+    /// # user.create_stream("Branch 1").await?;
+    /// let message = user
+    ///     .message()
+    ///     .with_public_payload("route: warehouse-7")
+    ///     .with_masked_payload("door code: 1234")
+    ///     .with_attachment("floor-plan.png", vec![0x89, 0x50, 0x4e, 0x47])
+    ///     .signed()
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_public_payload<B: Into<Vec<u8>>>(mut self, payload: B) -> Self {
+        self.public_payload = Some(payload.into());
+        self
+    }
+
+    /// Inject a payload that will be masked in transit, whatever the private flag. It replaces the
+    /// data payload if the message is private.
+    ///
+    /// # Arguments
+    /// * payload - The payload that will be sent masked
+    pub fn with_masked_payload<B: Into<Vec<u8>>>(mut self, payload: B) -> Self {
+        self.masked_payload = Some(payload.into());
+        self
+    }
+
+    /// Inject a named blob of bytes that will be sent masked along with the payloads. Attachments
+    /// are kept in the order they are injected.
+    ///
+    /// # Arguments
+    /// * name - The name of the attachment
+    /// * data - The content of the attachment
+    pub fn with_attachment<N, D>(mut self, name: N, data: D) -> Self
+    where
+        N: Into<String>,
+        D: Into<Vec<u8>>,
+    {
+        self.attachments.push(Attachment::new(name, data));
+        self
+    }
+
+    /// Encodes a value with the [`Codec`] and injects it into the builder as the data payload. The
+    /// identifier of the codec is recorded in the message header, so that readers can decode the
+    /// payload with [`Message::decode()`](crate::Message::decode). Fails with
+    /// [`Error::UnsupportedCodec`] if the identifier of the codec is larger than 63.
+    ///
+    /// # Arguments
+    /// * value - The value to be encoded as the data payload
+    pub fn with_typed_as<C, T>(mut self, value: &T) -> Result<Self>
+    where
+        C: Codec,
+        T: Serialize,
+    {
+        if C::ID > codec::MAX_ID {
+            return Err(Error::UnsupportedCodec(C::ID));
+        }
+        self.payload = C::encode(value).map_err(|e| Error::Codec(C::ID, "encode", e))?;
+        self.codec = C::ID;
+        Ok(self)
+    }
+
+    /// Encodes a value as `JSON` and injects it into the builder as the data payload. See
+    /// [`MessageBuilder::with_typed_as()`] to use another [`Codec`].
+    ///
+    /// # Arguments
+    /// * value - The value to be encoded as the data payload
+    ///
+    /// # Examples
+    /// ```
+    /// # use streams::{id::Ed25519, transport::bucket, User, Result};
+    /// #
+    /// #[derive(serde::Serialize)]
+    /// struct Reading {
+    ///     sensor: String,
+    ///     celsius: f32,
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let mut user = User::builder()
+    /// #    .with_identity(Ed25519::from_seed("cryptographically-secure-random-user-seed"))
+    /// #    .with_transport(bucket::Client::new())
+    /// #    .build();
+    /// # user.create_stream("Branch 1").await?;
+    /// let reading = Reading {
+    ///     sensor: "thermometer".to_string(),
+    ///     celsius: 21.5,
+    /// };
+    /// user.message().with_typed(&reading)?.signed().send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json-codec")]
+    pub fn with_typed<T>(self, value: &T) -> Result<Self>
+    where
+        T: Serialize,
+    {
+        self.with_typed_as::<Json, T>(value)
+    }
+}
+
 #[cfg(test)]
 mod message_builder_tests {
+    #[cfg(feature = "send")]
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+
+    #[cfg(feature = "send")]
+    use futures::{lock::Mutex, TryStreamExt};
+
     use crate::{api::message_builder::MessageBuilder, User};
     use lets::{id::Ed25519, message::Topic, transport::bucket};
 
@@ -285,4 +466,184 @@ mod message_builder_tests {
                 .is_empty()
         );
     }
+
+    #[tokio::test]
+    async fn send_public_and_masked_payloads_with_attachments() {
+        let mut user = make_user().await;
+
+        let msg = user
+            .message()
+            .with_public_payload("A Public Payload")
+            .with_masked_payload("A Private Payload")
+            .with_attachment("first", vec![1, 2, 3])
+            .with_attachment("second", "An Attachment")
+            .send()
+            .await
+            .unwrap();
+        let attachment_only_msg = user
+            .message()
+            .with_attachment("only", vec![4])
+            .signed()
+            .send()
+            .await
+            .unwrap();
+
+        let received_msg = user.receive_message(msg.address()).await.unwrap();
+        let received_attachment_only_msg = user.receive_message(attachment_only_msg.address()).await.unwrap();
+
+        assert_eq!(received_msg.public_payload().unwrap(), b"A Public Payload");
+        assert_eq!(received_msg.masked_payload().unwrap(), b"A Private Payload");
+        let names: Vec<&str> = received_msg
+            .attachments()
+            .unwrap()
+            .iter()
+            .map(|attachment| attachment.name())
+            .collect();
+        assert_eq!(names, ["first", "second"]);
+        assert_eq!(received_msg.attachment("first").unwrap().data(), [1, 2, 3]);
+        assert_eq!(received_msg.attachment("second").unwrap().data(), b"An Attachment");
+        assert!(received_msg.attachment("third").is_none());
+
+        assert!(received_attachment_only_msg.is_signed_packet());
+        assert!(received_attachment_only_msg.masked_payload().unwrap().is_empty());
+        assert_eq!(received_attachment_only_msg.attachment("only").unwrap().data(), [4]);
+    }
+
+    #[cfg(feature = "json-codec")]
+    #[tokio::test]
+    async fn send_typed_messages() {
+        use crate::{
+            api::codec::{Codec, Json},
+            Error,
+        };
+
+        #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct Reading {
+            sensor: alloc::string::String,
+            celsius: f32,
+        }
+
+        let mut user = make_user().await;
+        let reading = Reading {
+            sensor: "thermometer".into(),
+            celsius: 21.5,
+        };
+
+        let typed_msg = user.message().with_typed(&reading).unwrap().send().await.unwrap();
+        let raw_msg = user.message().with_payload("A Payload").public().send().await.unwrap();
+
+        let received_typed_msg = user.receive_message(typed_msg.address()).await.unwrap();
+        let received_raw_msg = user.receive_message(raw_msg.address()).await.unwrap();
+
+        assert_eq!(received_typed_msg.codec(), Json::ID);
+        assert_eq!(received_typed_msg.decode::<Reading>().unwrap(), reading);
+        assert_eq!(received_raw_msg.codec(), 0);
+        assert!(matches!(
+            received_raw_msg.decode::<Reading>(),
+            Err(Error::UnsupportedCodec(0))
+        ));
+    }
+
+    #[cfg(feature = "deflate-compression")]
+    #[tokio::test]
+    async fn send_compressed_messages() {
+        use crate::{Compression, Error};
+        use lets::transport::Transport;
+
+        let mut user = make_user().await;
+        let public_payload = "temperature=21.5;".repeat(64);
+        let masked_payload = "humidity=40;".repeat(64);
+
+        let compressed_msg = user
+            .message()
+            .with_public_payload(public_payload.as_str())
+            .with_masked_payload(masked_payload.as_str())
+            .compressed(Compression::Deflate)
+            .send()
+            .await
+            .unwrap();
+        let uncompressed_msg = user
+            .message()
+            .with_public_payload(public_payload.as_str())
+            .with_masked_payload(masked_payload.as_str())
+            .send()
+            .await
+            .unwrap();
+
+        let received_compressed_msg = user.receive_message(compressed_msg.address()).await.unwrap();
+        let received_uncompressed_msg = user.receive_message(uncompressed_msg.address()).await.unwrap();
+
+        assert_eq!(received_compressed_msg.compression(), Some(Compression::Deflate));
+        assert_eq!(
+            received_compressed_msg.public_payload().unwrap(),
+            public_payload.as_bytes()
+        );
+        assert_eq!(
+            received_compressed_msg.masked_payload().unwrap(),
+            masked_payload.as_bytes()
+        );
+        assert_eq!(received_uncompressed_msg.compression(), Some(Compression::None));
+
+        let transport = user.transport_mut();
+        let compressed_size = transport
+            .recv_message(compressed_msg.address())
+            .await
+            .unwrap()
+            .as_ref()
+            .len();
+        let uncompressed_size = transport
+            .recv_message(uncompressed_msg.address())
+            .await
+            .unwrap()
+            .as_ref()
+            .len();
+        assert!(compressed_size < uncompressed_size / 4);
+
+        let compressed = Compression::Deflate.compress(public_payload.as_bytes()).unwrap();
+        assert!(matches!(
+            Compression::Deflate.decompress(&compressed, public_payload.len() - 1),
+            Err(Error::Compression(1, "decompress", _))
+        ));
+    }
+
+    #[cfg(feature = "send")]
+    #[tokio::test]
+    async fn send_and_receive_messages_from_spawned_tasks() {
+        let transport = Arc::new(Mutex::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_transport(transport.clone())
+            .with_identity(Ed25519::from_seed("author seed"))
+            .build();
+        let mut subscriber = User::builder()
+            .with_transport(transport)
+            .with_identity(Ed25519::from_seed("subscriber seed"))
+            .build();
+
+        let announcement = tokio::spawn(async move {
+            let announcement = author.create_stream(BASE_BRANCH).await.unwrap();
+            author
+                .message()
+                .with_payload("A payload")
+                .public()
+                .send()
+                .await
+                .unwrap();
+            announcement.address()
+        })
+        .await
+        .unwrap();
+
+        let messages = tokio::spawn(async move {
+            subscriber.receive_message(announcement).await.unwrap();
+            subscriber.messages().try_collect::<Vec<_>>().await.unwrap()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(
+            messages[0].as_tagged_packet().unwrap().public_payload,
+            "A payload".as_bytes()
+        );
+    }
 }
@@ -0,0 +1,91 @@
+// Rust
+use alloc::{boxed::Box, vec::Vec};
+use core::ops::RangeInclusive;
+
+// 3rd-party
+use async_trait::async_trait;
+
+// IOTA
+
+// Streams
+use lets::sync::{MaybeSend, MaybeSync};
+use spongos::{
+    ddml::commands::{sizeof, unwrap, wrap},
+    error::Result as SpongosResult,
+};
+
+// Local
+
+/// Message types available to the messages defined by applications. The other message types are
+/// reserved for the messages of the protocol.
+pub const CUSTOM_MESSAGE_TYPES: RangeInclusive<u8> = 8..=15;
+
+/// Handler of a message type defined by an application, such as acknowledgements or control
+/// commands.
+///
+/// A handler is registered on a [`User`](crate::User) for a type of [`CUSTOM_MESSAGE_TYPES`] with
+/// [`User::register_message_handler()`](crate::User::register_message_handler), and messages of
+/// that type are sent with [`User::send_custom_message()`](crate::User::send_custom_message).
+/// Like packets, custom messages are linked to the latest message of their branch, are tracked by
+/// the cursor of their publisher and require write permission. The handler wraps the content of a
+/// message once the [`Spongos`](spongos::Spongos) state of the linked message is joined, and the
+/// message is then authenticated with a MAC, so that the content masked by the handler can only
+/// be read by the members of the branch. Readers process the messages of a custom type with the
+/// handler they registered for it, and fail to process them without one.
+///
+/// ```
+/// use async_trait::async_trait;
+/// use spongos::{
+///     ddml::{
+///         commands::{sizeof, unwrap, wrap, Mask},
+///         types::Bytes,
+///     },
+///     error::Result,
+/// };
+/// use streams::MessageHandler;
+///
+/// /// Acknowledgements, whose content is masked
+/// struct Acknowledgement;
+///
+/// #[async_trait(?Send)]
+/// impl MessageHandler for Acknowledgement {
+///     async fn sizeof(&self, ctx: &mut sizeof::Context, content: &[u8]) -> Result<()> {
+///         ctx.mask(Bytes::new(content))?;
+///         Ok(())
+///     }
+///
+///     async fn wrap(&self, ctx: &mut wrap::Context<&mut [u8]>, content: &[u8]) -> Result<()> {
+///         ctx.mask(Bytes::new(content))?;
+///         Ok(())
+///     }
+///
+///     async fn unwrap(&self, ctx: &mut unwrap::Context<&[u8]>) -> Result<Vec<u8>> {
+///         let mut content = Vec::new();
+///         ctx.mask(Bytes::new(&mut content))?;
+///         Ok(content)
+///     }
+/// }
+/// ```
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait MessageHandler: MaybeSend + MaybeSync {
+    /// Accounts for the size of the wrapped content of a message
+    ///
+    /// # Arguments
+    /// * `ctx`: The context measuring the size of the message
+    /// * `content`: The content of the message
+    async fn sizeof(&self, ctx: &mut sizeof::Context, content: &[u8]) -> SpongosResult<()>;
+
+    /// Wraps the content of a message
+    ///
+    /// # Arguments
+    /// * `ctx`: The context wrapping the message, joined to the state of the linked message
+    /// * `content`: The content of the message
+    async fn wrap(&self, ctx: &mut wrap::Context<&mut [u8]>, content: &[u8]) -> SpongosResult<()>;
+
+    /// Unwraps the content of a message, returning it
+    ///
+    /// # Arguments
+    /// * `ctx`: The context unwrapping the message, joined to the state of the linked message
+    async fn unwrap(&self, ctx: &mut unwrap::Context<&[u8]>) -> SpongosResult<Vec<u8>>;
+}
@@ -19,14 +19,20 @@ use lets::{
     address::{Address, MsgId},
     id::{Identifier, Permissioned},
     message::{Topic, TransportMessage, HDF},
+    sync::MaybeSend,
     transport::Transport,
 };
 
 // Local
-use crate::api::{
-    message::{Message, MessageContent, Orphan},
-    selector::Selector,
-    user::User,
+#[cfg(feature = "follow")]
+use crate::api::follow::Following;
+use crate::{
+    api::{
+        message::{Message, MessageContent, Orphan},
+        selector::Selector,
+        user::User,
+    },
+    Error,
 };
 
 /// a [`Stream`] over the messages of the channel pending to be fetch from the transport
@@ -114,10 +120,16 @@ use crate::api::{
 /// until their turn. Therefore, some jitter might be expected, with a worst case of fetching all
 /// the messages before any is yielded.
 ///
+/// By default the next message of each publisher is requested one publisher at a time. Channels
+/// with many publishers can be caught up faster with [`Messages::with_parallelism()`], which
+/// requests the next messages of several publishers in a single [`Transport::recv_batch()`] call.
+/// The preorder guarantee holds regardless of the order in which the transport returns them.
+///
 /// After the last currently available message has been returned, [`Messages::next()`] returns
 /// `None`, at which point the [`StreamExt`] and [`TryStreamExt`] methods will consider the
 /// [`Stream`] finished and stop iterating. It is safe to continue calling [`Messages::next()`] or
-/// any method from [`StreamExt`] and [`TryStreamExt`] polling for new messages.
+/// any method from [`StreamExt`] and [`TryStreamExt`] polling for new messages. With the `follow`
+/// feature, [`Messages::follow()`] turns the stream into one that does the polling and never ends.
 ///
 /// Being a [`futures::Stream`] that fetches data from an external source, it's naturally defined as
 /// a [`futures::TryStream`], which means it returns a [`Result`] wrapping the `UnwrappedMessage`.
@@ -125,16 +137,31 @@ use crate::api::{
 /// suggested that, when suitable, use the methods in [`futures::TryStreamExt`] to make the
 /// error-handling much more ergonomic (with the use of `?`) and shortcircuit the
 /// [`futures::Stream`] on the first error.
-pub struct Messages<'a, T>(PinBoxFut<'a, (MessagesState<'a, T>, Option<Result<Message>>)>);
+pub struct Messages<'a, T> {
+    /// Maximum number of publishers whose next message is requested at once
+    parallelism: usize,
+    /// Traversal state, taken by the future fetching the next message while it is pending
+    state: Option<MessagesState<'a, T>>,
+    /// Future fetching the next message, if being polled
+    fetching: Option<Fetching<'a, T>>,
+}
+
+/// Future fetching the next message, resolving to the traversal state along with the message
+type Fetching<'a, T> = PinBoxFut<'a, (MessagesState<'a, T>, Option<Result<Message>>)>;
 
+#[cfg(not(feature = "send"))]
 type PinBoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+#[cfg(feature = "send")]
+type PinBoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 struct MessagesState<'a, T> {
     user: &'a mut User<T>,
     ids_stack: Vec<(Topic, Permissioned<Identifier>, usize)>,
     msg_queue: HashMap<MsgId, VecDeque<(MsgId, TransportMessage)>>,
     stage: VecDeque<(MsgId, TransportMessage)>,
+    last_round: Option<Vec<(Topic, Permissioned<Identifier>, usize)>>,
     successful_round: bool,
+    parallelism: usize,
 }
 
 impl<'a, T> MessagesState<'a, T> {
@@ -144,17 +171,21 @@ impl<'a, T> MessagesState<'a, T> {
             ids_stack: Vec::new(),
             msg_queue: HashMap::new(),
             stage: VecDeque::new(),
+            last_round: None,
             successful_round: false,
+            parallelism: 1,
         }
     }
 
     /// Fetch the next message of the channel
     ///
     /// See [`Messages`] documentation and examples for more details.
-    #[async_recursion(?Send)]
-    async fn next(&mut self) -> Option<Result<Message>>
+    #[cfg_attr(feature = "send", async_recursion)]
+    #[cfg_attr(not(feature = "send"), async_recursion(?Send))]
+    async fn next<TSR>(&mut self) -> Option<Result<Message>>
     where
-        T: for<'b> Transport<'b, Msg = TransportMessage>,
+        T: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+        TSR: MaybeSend,
     {
         if let Some((relative_address, binary_msg)) = self.stage.pop_front() {
             // Drain stage if not empty...
@@ -201,66 +232,113 @@ impl<'a, T> MessagesState<'a, T> {
             }
         } else {
             // Stage is empty, populate it with some more messages
-            let (topic, publisher, cursor) = match self.ids_stack.pop() {
-                Some(id_cursor) => id_cursor,
-                None => {
-                    // new round
-                    self.successful_round = false;
-                    self.ids_stack = self
-                        .user
-                        .cursors()
-                        .filter(|(_, p, _)| !p.is_readonly())
-                        .map(|(t, p, c)| (t.clone(), p.clone(), c))
-                        .collect();
-                    self.ids_stack.pop()?
+            if self.ids_stack.is_empty() {
+                // new round
+                self.successful_round = false;
+                let ids: Vec<_> = self
+                    .user
+                    .cursors()
+                    .filter(|(_, p, _)| !p.is_readonly())
+                    .map(|(t, p, c)| (t.clone(), p.clone(), c))
+                    .collect();
+                if ids.is_empty() || self.last_round.as_ref() == Some(&ids) {
+                    // The last round found messages, but handling them moved no cursor (spam, for
+                    // instance). Fetching them again would find the same messages, end of stream (for now...)
+                    self.last_round = None;
+                    return None;
                 }
-            };
+                self.ids_stack = ids.clone();
+                self.last_round = Some(ids);
+            }
+            // Take the ids in the order they would have been popped one by one
+            let batch_start = self.ids_stack.len().saturating_sub(self.parallelism.max(1));
+            let mut batch = self.ids_stack.split_off(batch_start);
+            batch.reverse();
             let base_address = self.user.stream_address()?.base();
-            let rel_address = MsgId::gen(base_address, publisher.identifier(), &topic, cursor + 1);
-            let address = Address::new(base_address, rel_address);
-
-            match self.user.transport_mut().recv_message(address).await {
-                Ok(msg) => {
-                    self.stage.push_back((address.relative(), msg));
-                    self.successful_round = true;
-                    self.next().await
-                }
-                Err(_e) => {
-                    // Message not found or network error. Right now we are not distinguishing
-                    // between each case, so we must assume it's message not found.
-                    // When we introduce typed error handling and are able to distinguish,
-                    // Return Err(e) if error is network-related or any other transient error
-                    if self.ids_stack.is_empty() && !self.successful_round {
-                        // After trying all ids, none has produced an existing link, end of stream (for now...)
-                        None
-                    } else {
-                        // At least one id is producing existing links. continue...
-                        self.next().await
+            let addresses: Vec<Address> = batch
+                .iter()
+                .map(|(topic, publisher, cursor)| {
+                    Address::new(
+                        base_address,
+                        MsgId::gen(base_address, publisher.identifier(), topic, cursor + 1),
+                    )
+                })
+                .collect();
+
+            let outcomes = self.user.transport_mut().recv_batch(&addresses).await;
+            let mut failed = Vec::new();
+            let mut first_error = None;
+            for ((id_cursor, address), outcome) in batch.into_iter().zip(addresses).zip(outcomes) {
+                match outcome {
+                    // Anyone can publish at the address of the next message of a publisher, so every
+                    // message found there is a candidate. Spam fails to be handled and is skipped
+                    Ok(msgs) => {
+                        self.successful_round |= !msgs.is_empty();
+                        self.stage.extend(msgs.into_iter().map(|msg| (address.relative(), msg)));
+                    }
+                    Err(e) if !e.is_not_found() => {
+                        failed.push(id_cursor);
+                        first_error.get_or_insert((address, e));
                     }
+                    Err(_not_found) => (),
                 }
             }
+
+            if let Some((address, e)) = first_error {
+                // The transport failed to tell whether some messages exist. Put their publishers back
+                // on the stack so that they are retried on the next call, and surface the error
+                self.ids_stack.extend(failed.into_iter().rev());
+                Some(Err(anyhow::Error::msg(Error::Transport(
+                    address,
+                    "fetch next message",
+                    e,
+                ))))
+            } else if self.stage.is_empty() && self.ids_stack.is_empty() && !self.successful_round {
+                // After trying all ids, none has produced an existing link, end of stream (for now...)
+                self.last_round = None;
+                None
+            } else {
+                // At least one id is producing existing links. continue...
+                self.next().await
+            }
         }
     }
 }
 
-impl<'a, T> Messages<'a, T>
+impl<'a, T, TSR> Messages<'a, T>
 where
-    T: for<'b> Transport<'b, Msg = TransportMessage>,
+    T: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+    TSR: MaybeSend,
 {
     pub(crate) fn new(user: &'a mut User<T>) -> Self {
-        let mut state = MessagesState::new(user);
-        Self(Box::pin(async move {
-            let r = state.next().await;
-            (state, r)
-        }))
+        Self {
+            parallelism: 1,
+            state: Some(MessagesState::new(user)),
+            fetching: None,
+        }
+    }
+
+    /// Sets the maximum number of publishers whose next message is requested from the transport
+    /// at once, in a single [`Transport::recv_batch()`] call. Defaults to 1. Whether the requests
+    /// are actually performed concurrently depends on the transport.
+    ///
+    /// # Arguments
+    /// * `parallelism`: Number of publishers queried at once, at least 1
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
     }
 
     /// "Filter the stream of messages to only those that match the selectors, and return the result
     /// as a vector."
-    /// A message is matched when at least one of the selectors is a match.
+    /// A message is matched when at least one of the selectors is a match, like with a single
+    /// [`Selector::Or`]. Use the [`Selector`] combinators to express more complex queries.
     ///
     /// Important to note is that the stream DISCARDS the messages that dont fit the criteria from
-    /// the selectors.
+    /// the selectors. They are still processed, and advance the state of the user.
+    ///
+    /// The stream stops at the first error, which is returned. The messages processed until then
+    /// advance the state of the user, so calling this method again resumes after them.
     ///
     /// # Arguments
     ///
@@ -269,21 +347,17 @@ where
     /// Returns:
     ///
     /// A vector of Messages.
-    pub async fn from(&mut self, selectors: &[Selector]) -> Vec<Message> {
-        StreamExt::filter(self, |x| match &x {
-            Ok(m) => {
-                for selector in selectors {
-                    if selector.is(m) {
-                        return future::ready(true);
-                    }
-                }
-                future::ready(false)
-            }
-            Err(_) => future::ready(false),
-        })
-        .map(|x| x.unwrap())
-        .collect::<Vec<_>>()
-        .await
+    pub async fn from(&mut self, selectors: &[Selector]) -> Result<Vec<Message>> {
+        TryStreamExt::try_filter(self, |m| future::ready(selectors.iter().any(|selector| selector.is(m))))
+            .try_collect()
+            .await
+    }
+
+    /// Turns the stream into a [`Following`] stream, which keeps polling the channel for new
+    /// messages instead of ending once the currently available messages have been yielded
+    #[cfg(feature = "follow")]
+    pub fn follow(self) -> Following<'a, T> {
+        Following::new(self)
     }
 
     /// `next` is an async function that returns an Option of a Result of a Message
@@ -327,28 +401,41 @@ where
     }
 }
 
-impl<'a, T> From<&'a mut User<T>> for Messages<'a, T>
+impl<'a, T, TSR> From<&'a mut User<T>> for Messages<'a, T>
 where
-    T: for<'b> Transport<'b, Msg = TransportMessage>,
+    T: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+    TSR: MaybeSend,
 {
     fn from(user: &'a mut User<T>) -> Self {
         Self::new(user)
     }
 }
 
-impl<'a, T> Stream for Messages<'a, T>
+impl<'a, T, TSR> Stream for Messages<'a, T>
 where
-    T: for<'b> Transport<'b, Msg = TransportMessage>,
+    T: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+    TSR: MaybeSend,
 {
     type Item = Result<Message>;
 
-    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.0.as_mut().poll(ctx) {
-            Poll::Ready((mut state, result)) => {
-                self.set(Messages(Box::pin(async move {
-                    let r = state.next().await;
-                    (state, r)
-                })));
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let Messages {
+            parallelism,
+            state,
+            fetching,
+        } = self.get_mut();
+        let pending = fetching.get_or_insert_with(|| {
+            let mut state = state.take().expect("the state is only taken while fetching");
+            state.parallelism = *parallelism;
+            Box::pin(async move {
+                let r = state.next().await;
+                (state, r)
+            })
+        });
+        match pending.as_mut().poll(ctx) {
+            Poll::Ready((fetched_state, result)) => {
+                *fetching = None;
+                *state = Some(fetched_state);
                 Poll::Ready(result)
             }
             Poll::Pending => Poll::Pending,
@@ -358,10 +445,18 @@ where
 
 #[cfg(test)]
 mod tests {
-    use alloc::rc::Rc;
-    use core::cell::RefCell;
-
-    use lets::{address::Address, id::Ed25519, transport::bucket};
+    use alloc::{boxed::Box, sync::Arc, vec::Vec};
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use futures::TryStreamExt;
+    use lets::{
+        address::{Address, MsgId},
+        error::{Error as LetsError, Result as LetsResult},
+        id::Ed25519,
+        message::{Topic, TransportMessage},
+        transport::{self, Transport as _},
+    };
 
     use crate::{
         api::{
@@ -369,13 +464,12 @@ mod tests {
                 Message,
                 MessageContent::{BranchAnnouncement, Keyload, SignedPacket},
             },
+            test_transport::{self, Transport},
             user::User,
         },
         Result,
     };
 
-    type Transport = Rc<RefCell<bucket::Client>>;
-
     #[tokio::test]
     async fn messages_awake_pending_messages_link_to_them_even_if_their_content_is_unreadable() -> Result<()> {
         let p = b"payload";
@@ -435,10 +529,164 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn messages_surface_network_failures_and_resume_afterwards() -> Result<()> {
+        let p = b"payload";
+        let bucket = test_transport::transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(bucket.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let subscriber_transport = FlakyTransport {
+            bucket,
+            offline: Arc::new(AtomicBool::new(false)),
+        };
+        let offline = subscriber_transport.offline.clone();
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_transport(subscriber_transport)
+            .build();
+        subscriber.receive_message(announcement.address()).await?;
+        let subscription = subscriber.subscribe().await?;
+        author.receive_message(subscription.address()).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+        author.send_signed_packet("BASE_BRANCH", &p, &p).await?;
+
+        offline.store(true, Ordering::SeqCst);
+        assert!(matches!(subscriber.messages().next().await, Some(Err(_))));
+
+        offline.store(false, Ordering::SeqCst);
+        assert_eq!(2, subscriber.fetch_next_messages().await?.len()); // keyload and signed packet
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn messages_skip_spam_published_at_the_address_of_the_next_message() -> Result<()> {
+        let p = b"payload";
+        let (mut author, mut subscriber, announcement_link, mut transport) = author_subscriber_fixture().await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+        subscriber.sync().await?;
+
+        // Spam published next to a packet is tried along with it and skipped
+        let packet = author.send_signed_packet("BASE_BRANCH", &p, &p).await?;
+        let spam = TransportMessage::new(vec![0; 256]);
+        transport.send_message(packet.address(), spam.clone()).await.unwrap();
+        let msgs = subscriber.fetch_next_messages().await?;
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].address(), packet.address());
+
+        // Spam alone at the address of the next message ends the stream instead of being fetched again
+        let topic = Topic::from("BASE_BRANCH");
+        let (_, publisher, cursor) = author.cursors().find(|(t, ..)| **t == topic).unwrap();
+        let base = announcement_link.base();
+        let next_address = Address::new(base, MsgId::gen(base, publisher.identifier(), &topic, cursor + 1));
+        transport.send_message(next_address, spam).await.unwrap();
+        assert!(subscriber.fetch_next_messages().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn messages_fetched_in_batches_are_yielded_in_preorder() -> Result<()> {
+        let p = b"payload";
+        let (mut author, mut subscriber1, announcement_link, transport) = author_subscriber_fixture().await?;
+        let mut subscriber2 =
+            subscriber_fixture("subscriber2", &mut author, announcement_link, transport.clone()).await?;
+        let largest_batch = Arc::new(AtomicUsize::new(0));
+        let mut reader = User::builder()
+            .with_identity(Ed25519::from_seed("reader"))
+            .with_transport(BatchRecorder {
+                bucket: transport,
+                largest_batch: largest_batch.clone(),
+            })
+            .build();
+        reader.receive_message(announcement_link).await?;
+        let subscription = reader.subscribe().await?;
+        author.receive_message(subscription.address()).await?;
+
+        let keyload = author.send_keyload_for_all_rw("BASE_BRANCH").await?;
+        let mut packets = Vec::new();
+        for _ in 0..2 {
+            for subscriber in [&mut subscriber1, &mut subscriber2] {
+                subscriber.sync().await?;
+                packets.push(subscriber.send_signed_packet("BASE_BRANCH", &p, &p).await?.address());
+            }
+        }
+
+        let msgs: Vec<Message> = reader.messages().with_parallelism(8).try_collect().await?;
+        assert_eq!(msgs.len(), 5); // keyload and packets
+        assert_eq!(msgs[0].address(), keyload.address());
+        let mut yielded = vec![announcement_link.relative()];
+        for msg in &msgs {
+            assert!(yielded.contains(&msg.header().linked_msg_address().unwrap()));
+            yielded.push(msg.address().relative());
+        }
+        assert!(packets.iter().all(|packet| yielded.contains(&packet.relative())));
+        // The next packets of both subscribers are requested together
+        assert!(largest_batch.load(Ordering::SeqCst) > 1);
+        Ok(())
+    }
+
+    /// Bucket transport recording the size of the largest batch it receives
+    struct BatchRecorder {
+        bucket: Transport,
+        largest_batch: Arc<AtomicUsize>,
+    }
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl transport::Transport<'_> for BatchRecorder {
+        type Msg = TransportMessage;
+        type SendResponse = TransportMessage;
+
+        async fn send_message(&mut self, address: Address, msg: TransportMessage) -> LetsResult<TransportMessage>
+        where
+            Self::Msg: 'async_trait,
+        {
+            self.bucket.send_message(address, msg).await
+        }
+
+        async fn recv_messages(&mut self, address: Address) -> LetsResult<Vec<TransportMessage>> {
+            self.bucket.recv_messages(address).await
+        }
+
+        async fn recv_batch(&mut self, addresses: &[Address]) -> Vec<LetsResult<Vec<TransportMessage>>> {
+            self.largest_batch.fetch_max(addresses.len(), Ordering::SeqCst);
+            self.bucket.recv_batch(addresses).await
+        }
+    }
+
+    /// Bucket transport that fails with network errors while offline
+    struct FlakyTransport {
+        bucket: Transport,
+        offline: Arc<AtomicBool>,
+    }
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl transport::Transport<'_> for FlakyTransport {
+        type Msg = TransportMessage;
+        type SendResponse = TransportMessage;
+
+        async fn send_message(&mut self, address: Address, msg: TransportMessage) -> LetsResult<TransportMessage>
+        where
+            Self::Msg: 'async_trait,
+        {
+            self.bucket.send_message(address, msg).await
+        }
+
+        async fn recv_messages(&mut self, address: Address) -> LetsResult<Vec<TransportMessage>> {
+            if self.offline.load(Ordering::SeqCst) {
+                return Err(LetsError::Network("receive messages", anyhow::anyhow!("offline")));
+            }
+            self.bucket.recv_messages(address).await
+        }
+    }
+
     /// Prepare a simple scenario with an author, a subscriber, a channel announcement and a bucket
     /// transport
     async fn author_subscriber_fixture() -> Result<(User<Transport>, User<Transport>, Address, Transport)> {
-        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let transport = test_transport::transport();
         let mut author = User::builder()
             .with_identity(Ed25519::from_seed("author"))
             .with_transport(transport.clone())
@@ -1,17 +1,39 @@
+/// Message Timestamping Clock
+pub(crate) mod clock;
+/// Typed Payload Codecs
+pub(crate) mod codec;
+/// Payload Compression
+pub(crate) mod compression;
 /// Identifier Key storage. Used for keeping track of channel state
 mod cursor_store;
 
+/// Live-following Message Retrieval
+#[cfg(feature = "follow")]
+pub mod follow;
+/// Branch Key Rotation Policy
+pub(crate) mod key_rotation;
 /// Unwrapped Message Types
 pub mod message;
 /// Message builder for sending payloads
 pub mod message_builder;
+/// Custom Message Type Handlers
+pub(crate) mod message_handler;
 /// Message Retrieval
 pub mod messages;
 /// Message Retrieval Filter Selector
 pub(crate) mod selector;
 /// Message Wrapper for Sent Messages
 pub(crate) mod send_response;
+/// Subscription Approval Policy
+pub(crate) mod subscription_policy;
 /// User Client
 pub mod user;
 /// User Client Builder
 pub mod user_builder;
+
+#[cfg(test)]
+mod legacy_stream;
+#[cfg(test)]
+mod test_transport;
+#[cfg(test)]
+mod v1_stream;
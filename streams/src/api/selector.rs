@@ -1,29 +1,107 @@
 // Rust
-use core::{fmt::Formatter, ops::Range};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+use core::{
+    fmt::{Debug, Formatter},
+    ops::{Not, Range},
+};
 
 // IOTA
 
 // Streams
-use lets::{address::Address, id::Identifier, message::TopicHash};
+use lets::{
+    address::Address,
+    id::Identifier,
+    message::{Topic, TopicHash},
+};
 
-use crate::Message;
+use crate::{Message, MessageContent};
 
 /// An enum that is used to select messages from a stream.
+///
+/// Selectors can be combined into queries with [`Selector::and()`], [`Selector::or()`] and the `!`
+/// operator:
+///
+/// ```
+/// use streams::{MessageType, Selector};
+///
+/// let selector = Selector::TopicName("BASE_BRANCH".into())
+///     .and(
+///         Selector::MessageType(MessageType::SignedPacket)
+///             | Selector::MessageType(MessageType::TaggedPacket),
+///     )
+///     .and(!Selector::payload(|payload| payload.starts_with(b"DEBUG")));
+/// ```
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Selector {
+    /// Selects the message at the [`Address`]
     Address(Address),
+    /// Selects the messages published in the branch of the [`TopicHash`]
     Topic(TopicHash),
+    /// Selects the messages published in the branch of the [`Topic`]
+    TopicName(Topic),
+    /// Selects the messages published by the [`Identifier`]
     Identifier(Identifier),
+    /// Selects the messages whose sequence number is in the range
     Level(Range<usize>),
+    /// Selects the messages whose publication timestamp, in `Unix` seconds, is in the range.
+    /// Messages without a timestamp are never matched.
+    Time(Range<u64>),
+    /// Selects the messages of the [`MessageType`]
+    MessageType(MessageType),
+    /// Selects the packets whose public payload satisfies the [`PayloadPredicate`]
+    PublicPayload(PayloadPredicate),
+    /// Selects the messages matched by every one of the selectors
+    And(Vec<Selector>),
+    /// Selects the messages matched by at least one of the selectors
+    Or(Vec<Selector>),
+    /// Selects the messages not matched by the selector
+    Not(Box<Selector>),
 }
 
 impl Selector {
-    /// > If the selector is an address, check if the message address is equal to the selector
-    /// > address.
-    /// If the selector is a topic, check if the message topic is equal to the selector topic. If
-    /// the selector is an identifier, check if the message publisher is equal to the selector
-    /// identifier. If the selector is a level, check if the message sequence is contained in
-    /// the selector level
+    /// Creates a [`Selector`] matching the packets whose public payload satisfies the predicate.
+    /// Messages without a public payload are never matched.
+    ///
+    /// # Arguments
+    /// * `predicate`: The function deciding whether a public payload is selected
+    pub fn payload<F>(predicate: F) -> Self
+    where
+        F: Fn(&[u8]) -> bool + Send + Sync + 'static,
+    {
+        Selector::PublicPayload(PayloadPredicate(Arc::new(predicate)))
+    }
+
+    /// Combines the [`Selector`] with another one, matching the messages matched by both
+    ///
+    /// # Arguments
+    /// * `other`: The selector to combine with
+    pub fn and(self, other: Selector) -> Self {
+        match self {
+            Selector::And(mut selectors) => {
+                selectors.push(other);
+                Selector::And(selectors)
+            }
+            selector => Selector::And(vec![selector, other]),
+        }
+    }
+
+    /// Combines the [`Selector`] with another one, matching the messages matched by either
+    ///
+    /// # Arguments
+    /// * `other`: The selector to combine with
+    pub fn or(self, other: Selector) -> Self {
+        match self {
+            Selector::Or(mut selectors) => {
+                selectors.push(other);
+                Selector::Or(selectors)
+            }
+            selector => Selector::Or(vec![selector, other]),
+        }
+    }
+
+    /// Checks whether the message is matched by the selector. Combinators are evaluated
+    /// recursively: an empty [`Selector::And`] matches every message and an empty [`Selector::Or`]
+    /// matches none.
     ///
     /// # Arguments
     ///
@@ -36,14 +114,178 @@ impl Selector {
         match self {
             Selector::Address(address) => &message.address == address,
             Selector::Topic(topic) => message.header().topic_hash() == topic,
+            Selector::TopicName(topic) => message.header().topic_hash() == &TopicHash::from(topic),
             Selector::Identifier(identifier) => message.header().publisher() == identifier,
             Selector::Level(range) => range.contains(&message.header().sequence()),
+            Selector::Time(range) => matches!(message.timestamp(), Some(timestamp) if range.contains(&timestamp)),
+            Selector::MessageType(message_type) => MessageType::of(message.content()) == *message_type,
+            Selector::PublicPayload(predicate) => matches!(message.public_payload(), Some(p) if (predicate.0)(p)),
+            Selector::And(selectors) => selectors.iter().all(|selector| selector.is(message)),
+            Selector::Or(selectors) => selectors.iter().any(|selector| selector.is(message)),
+            Selector::Not(selector) => !selector.is(message),
+        }
+    }
+}
+
+impl Not for Selector {
+    type Output = Selector;
+
+    fn not(self) -> Selector {
+        match self {
+            Selector::Not(selector) => *selector,
+            selector => Selector::Not(Box::new(selector)),
         }
     }
 }
 
+impl core::ops::BitAnd for Selector {
+    type Output = Selector;
+
+    fn bitand(self, other: Selector) -> Selector {
+        self.and(other)
+    }
+}
+
+impl core::ops::BitOr for Selector {
+    type Output = Selector;
+
+    fn bitor(self, other: Selector) -> Selector {
+        self.or(other)
+    }
+}
+
 impl core::fmt::Display for Selector {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:?}", &self)
     }
 }
+
+/// Type of a [`Message`], as processed by the user
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MessageType {
+    Announcement,
+    BranchAnnouncement,
+    Keyload,
+    SignedPacket,
+    TaggedPacket,
+    Subscription,
+    Unsubscription,
+    /// A message of a type defined by the application
+    Custom(u8),
+    /// A message that could not be processed, whatever its type
+    Orphan,
+}
+
+impl MessageType {
+    /// Returns the [`MessageType`] of the message content
+    fn of(content: &MessageContent) -> Self {
+        match content {
+            MessageContent::Announcement(_) => MessageType::Announcement,
+            MessageContent::BranchAnnouncement(_) => MessageType::BranchAnnouncement,
+            MessageContent::Keyload(_) => MessageType::Keyload,
+            MessageContent::SignedPacket(_) => MessageType::SignedPacket,
+            MessageContent::TaggedPacket(_) => MessageType::TaggedPacket,
+            MessageContent::Subscription(_) => MessageType::Subscription,
+            MessageContent::Unsubscription(_) => MessageType::Unsubscription,
+            MessageContent::Custom(custom) => MessageType::Custom(custom.message_type),
+            MessageContent::Orphan(_) => MessageType::Orphan,
+        }
+    }
+}
+
+/// Predicate on the public payload of a packet, used by [`Selector::PublicPayload`]. Two
+/// predicates are only equal if they are clones of each other.
+#[derive(Clone)]
+pub struct PayloadPredicate(Arc<Predicate>);
+
+/// Function deciding whether a public payload is selected
+type Predicate = dyn Fn(&[u8]) -> bool + Send + Sync;
+
+impl PartialEq for PayloadPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for PayloadPredicate {}
+
+impl Debug for PayloadPredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PayloadPredicate")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use lets::{
+        address::{AppAddr, MsgId},
+        message::HDF,
+    };
+
+    use crate::{
+        api::message::{Keyload, SignedPacket},
+        message::message_types,
+    };
+
+    use super::*;
+
+    fn message(topic: &str, public_payload: Option<&[u8]>) -> Message {
+        let topic = Topic::from(topic);
+        let publisher = Identifier::default();
+        let address = Address::new(
+            AppAddr::default(),
+            MsgId::gen(AppAddr::default(), &publisher, &topic, 2),
+        );
+        match public_payload {
+            Some(payload) => Message {
+                address,
+                header: HDF::new(message_types::SIGNED_PACKET, 2, publisher.clone(), &topic),
+                content: MessageContent::SignedPacket(SignedPacket {
+                    publisher_identifier: publisher,
+                    masked_payload: Vec::new(),
+                    public_payload: payload.to_vec(),
+                    attachments: Vec::new(),
+                }),
+                decompression_error: None,
+            },
+            None => Message {
+                address,
+                header: HDF::new(message_types::KEYLOAD, 2, publisher, &topic),
+                content: MessageContent::Keyload(Keyload {
+                    subscribers: Vec::new(),
+                    psks: Vec::new(),
+                }),
+                decompression_error: None,
+            },
+        }
+    }
+
+    #[test]
+    fn combined_selectors_are_evaluated_recursively() {
+        let packet = message("BASE_BRANCH", Some(b"DEBUG: payload"));
+        let keyload = message("BASE_BRANCH", None);
+        let other_branch = message("OTHER_BRANCH", Some(b"payload"));
+
+        let signed_packets =
+            Selector::TopicName("BASE_BRANCH".into()) & Selector::MessageType(MessageType::SignedPacket);
+        assert!(signed_packets.is(&packet));
+        assert!(!signed_packets.is(&keyload));
+        assert!(!signed_packets.is(&other_branch));
+
+        let not_debug = !Selector::payload(|payload| payload.starts_with(b"DEBUG"));
+        assert!(!not_debug.is(&packet));
+        assert!(not_debug.is(&keyload));
+        assert!(not_debug.is(&other_branch));
+
+        let selector = signed_packets.or(Selector::Level(0..2)) | Selector::Topic(TopicHash::from("OTHER_BRANCH"));
+        assert!(matches!(&selector, Selector::Or(selectors) if selectors.len() == 3));
+        assert!(selector.is(&packet));
+        assert!(!selector.is(&keyload));
+        assert!(selector.is(&other_branch));
+        assert_eq!(!!selector.clone(), selector);
+        assert!(Selector::And(Vec::new()).is(&keyload));
+        assert!(!Selector::Or(Vec::new()).is(&keyload));
+    }
+}
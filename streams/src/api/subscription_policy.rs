@@ -0,0 +1,151 @@
+// Rust
+use alloc::sync::Arc;
+use core::fmt::{Debug, Formatter, Result as FormatResult};
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use lets::id::Identifier;
+
+// Local
+
+/// Decision of a [`SubscriptionPolicy`] on a new subscription
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SubscriptionDecision {
+    /// The subscriber is added to the subscribers of the stream, and included in the next keyloads
+    /// sent for all subscribers
+    Approve,
+    /// The subscription is discarded
+    Reject,
+    /// The subscriber is queued in the pending subscriptions, until approved or rejected with
+    /// [`User::approve_subscription()`](crate::User::approve_subscription) or
+    /// [`User::reject_subscription()`](crate::User::reject_subscription)
+    Pending,
+}
+
+/// Policy deciding what happens to the subscriptions processed by the author of a stream.
+///
+/// By default, every subscription is approved. A policy can instead approve some subscribers
+/// upfront and queue the others for manual vetting:
+///
+/// ```
+/// use streams::{
+///     id::{Ed25519, Identity},
+///     SubscriptionDecision, SubscriptionPolicy, User,
+/// };
+///
+/// let vetted_device = Identity::from(Ed25519::from_seed("vetted device"))
+///     .identifier()
+///     .clone();
+/// let author = User::builder()
+///     .with_identity(Ed25519::from_seed("author"))
+///     .with_subscription_policy(SubscriptionPolicy::new(move |subscriber| {
+///         match subscriber == &vetted_device {
+///             true => SubscriptionDecision::Approve,
+///             false => SubscriptionDecision::Pending,
+///         }
+///     }))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct SubscriptionPolicy(Arc<Decide>);
+
+/// Function deciding on a subscription
+type Decide = dyn Fn(&Identifier) -> SubscriptionDecision + Send + Sync;
+
+impl SubscriptionPolicy {
+    /// Creates a [`SubscriptionPolicy`] deciding on every subscription with the provided callback
+    ///
+    /// # Arguments
+    /// * `decide`: The callback deciding on a subscription from the subscriber [`Identifier`]
+    pub fn new<F>(decide: F) -> Self
+    where
+        F: Fn(&Identifier) -> SubscriptionDecision + Send + Sync + 'static,
+    {
+        Self(Arc::new(decide))
+    }
+
+    /// Creates a [`SubscriptionPolicy`] approving every subscription
+    pub fn approve_all() -> Self {
+        Self::new(|_| SubscriptionDecision::Approve)
+    }
+
+    /// Creates a [`SubscriptionPolicy`] queuing every subscription for manual approval
+    pub fn manual() -> Self {
+        Self::new(|_| SubscriptionDecision::Pending)
+    }
+
+    /// Returns the decision of the policy on the subscription of the [`Identifier`]
+    ///
+    /// # Arguments
+    /// * `subscriber`: The [`Identifier`] of the subscriber
+    pub(crate) fn decide(&self, subscriber: &Identifier) -> SubscriptionDecision {
+        (self.0)(subscriber)
+    }
+}
+
+impl Default for SubscriptionPolicy {
+    fn default() -> Self {
+        Self::approve_all()
+    }
+}
+
+impl Debug for SubscriptionPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
+        write!(f, "SubscriptionPolicy")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use lets::id::Ed25519;
+
+    use anyhow::Result;
+
+    use crate::api::{test_transport::transport, user::User};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn only_approved_subscribers_are_included_in_keyloads() -> Result<()> {
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .with_subscription_policy(SubscriptionPolicy::manual())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+
+        let mut subscribers = Vec::new();
+        for seed in ["subscriber a", "subscriber b"] {
+            let mut subscriber = User::builder()
+                .with_identity(Ed25519::from_seed(seed))
+                .with_transport(transport.clone())
+                .build();
+            subscriber.receive_message(announcement.address()).await?;
+            let subscription = subscriber.subscribe().await?;
+            author.receive_message(subscription.address()).await?;
+            subscribers.push(subscriber);
+        }
+        let a = subscribers[0].identifier().unwrap().clone();
+        let b = subscribers[1].identifier().unwrap().clone();
+        assert_eq!(author.pending_subscriptions().count(), 2);
+        assert_eq!(author.subscribers().count(), 0);
+
+        assert!(author.approve_subscription(&a));
+        assert!(author.reject_subscription(&b));
+        assert!(!author.approve_subscription(&b));
+        assert_eq!(author.pending_subscriptions().count(), 0);
+        assert_eq!(author.subscribers().collect::<Vec<_>>(), [&a]);
+
+        let keyload = author.send_keyload_for_all("BASE_BRANCH").await?;
+        let keyload = subscribers[0].receive_message(keyload.address()).await?;
+        assert!(keyload.as_keyload().unwrap().includes_subscriber(&a));
+        assert!(!keyload.as_keyload().unwrap().includes_subscriber(&b));
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+//! Bucket transport shared by the users of the unit tests, through the wrapper that is a
+//! [`Transport`](lets::transport::Transport) with the enabled features.
+
+// Rust
+#[cfg(not(feature = "send"))]
+use alloc::rc::Rc;
+#[cfg(feature = "send")]
+use alloc::sync::Arc;
+#[cfg(not(feature = "send"))]
+use core::cell::RefCell;
+
+// 3rd-party
+#[cfg(feature = "send")]
+use futures::lock::Mutex;
+
+// IOTA
+
+// Streams
+use lets::transport::bucket;
+
+// Local
+
+/// Bucket transport shared between several users
+#[cfg(not(feature = "send"))]
+pub(crate) type Transport = Rc<RefCell<bucket::Client>>;
+/// Bucket transport shared between several users
+#[cfg(feature = "send")]
+pub(crate) type Transport = Arc<Mutex<bucket::Client>>;
+
+/// Creates an empty [`Transport`]
+#[cfg(not(feature = "send"))]
+pub(crate) fn transport() -> Transport {
+    Rc::new(RefCell::new(bucket::Client::new()))
+}
+
+/// Creates an empty [`Transport`]
+#[cfg(feature = "send")]
+pub(crate) fn transport() -> Transport {
+    Arc::new(Mutex::new(bucket::Client::new()))
+}
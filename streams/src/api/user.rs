@@ -4,9 +4,13 @@ use alloc::{
     boxed::Box,
     format,
     string::{String, ToString},
+    sync::Arc,
     vec::Vec,
 };
-use core::fmt::{Debug, Formatter, Result as FormatResult};
+use core::{
+    fmt::{Debug, Formatter, Result as FormatResult},
+    time::Duration,
+};
 
 // 3rd-party
 use async_trait::async_trait;
@@ -19,18 +23,21 @@ use rand::{rngs::StdRng, Rng, SeedableRng};
 // Streams
 use lets::{
     address::{Address, AppAddr, MsgId},
+    error::{Error as LetsError, Result as LetsResult},
     id::{Identifier, Identity, PermissionDuration, Permissioned, Psk, PskId},
     message::{
-        ContentSizeof, ContentUnwrap, ContentWrap, Message as LetsMessage, PreparsedMessage, Topic, TopicHash,
-        TransportMessage, HDF, PCF,
+        ContentSizeof, ContentUnwrap, ContentWrap, FrameAssembler, Message as LetsMessage, PreparsedMessage, Topic,
+        TopicHash, TransportMessage, DEFAULT_FRAME_SIZE, HDF, PCF, STREAMS_VER, STREAMS_VER_1,
+        STREAMS_VER_2,
     },
+    sync::MaybeSend,
     transport::Transport,
 };
 use spongos::{
     ddml::{
         commands::{sizeof, unwrap, wrap, Absorb, Commit, Mask, Squeeze},
         modifiers::External,
-        types::{Mac, Maybe, NBytes, Size, Uint8},
+        types::{Mac, Maybe, NBytes, Size, Uint64, Uint8},
     },
     error::{Error as SpongosError, Result as SpongosResult},
     KeccakF1600, Spongos, SpongosRng,
@@ -39,12 +46,22 @@ use spongos::{
 // Local
 use crate::{
     api::{
-        cursor_store::CursorStore, message::Message, message_builder::MessageBuilder, messages::Messages,
-        send_response::SendResponse, user_builder::UserBuilder,
+        clock::{Clock, SystemClock},
+        codec,
+        cursor_store::CursorStore,
+        key_rotation::{KeyRotation, RotationSchedule},
+        message::{Keyload, Message},
+        message_builder::MessageBuilder,
+        message_handler::{MessageHandler, CUSTOM_MESSAGE_TYPES},
+        messages::Messages,
+        selector::Selector,
+        send_response::SendResponse,
+        subscription_policy::{SubscriptionDecision, SubscriptionPolicy},
+        user_builder::UserBuilder,
     },
     message::{
-        announcement, branch_announcement, keyload, message_types, signed_packet, subscription, tagged_packet,
-        unsubscription,
+        announcement, attachment::Attachment, branch_announcement, custom, keyload, message_types, signed_packet,
+        subscription, tagged_packet, unsubscription,
     },
     Error, Result,
 };
@@ -52,6 +69,7 @@ use crate::{
 const ANN_MESSAGE_NUM: usize = 0; // Announcement is always the first message of authors
 const SUB_MESSAGE_NUM: usize = 0; // Subscription is always the first message of subscribers
 const INIT_MESSAGE_NUM: usize = 1; // First non-reserved message number
+const BACKUP_VERSION: u8 = 1; // Backups made before the format was versioned carry no version
 
 /// The state of a user, mapping publisher cursors and link states for message processing.
 #[derive(PartialEq, Eq, Default)]
@@ -66,6 +84,12 @@ struct State {
     /// None if channel is not created or user is not subscribed.
     stream_address: Option<Address>,
 
+    /// Streams version of the stream announcement message. Messages published in the stream are
+    /// written with this version.
+    ///
+    /// None if channel is not created or user is not subscribed.
+    stream_version: Option<u8>,
+
     /// [`Identifier`] of the channel author.
     ///
     /// None if channel is not created or user is not subscribed.
@@ -81,6 +105,23 @@ struct State {
     /// List of Subscribed [Identifiers](`Identifier`).
     subscribers: HashSet<Identifier>,
 
+    /// List of [Identifiers](`Identifier`) whose subscription awaits approval.
+    pending_subscriptions: HashSet<Identifier>,
+
+    /// Recipients of the latest keyload of each branch, without the members that left since.
+    keyloads: HashMap<Topic, Keyload>,
+
+    /// Branches administered by the user whose latest keyload was sent to a member that left since,
+    /// and that should be re-keyed.
+    pending_rekeys: HashSet<Topic>,
+
+    /// If true, the branches are re-keyed as soon as an unsubscription is processed, and the
+    /// branches in `pending_rekeys` before the user sends a message.
+    auto_rekey: bool,
+
+    /// Key rotation schedules of the branches with a [`KeyRotation`] policy.
+    key_rotations: HashMap<Topic, RotationSchedule>,
+
     /// Mapping of message links ([`MsgId`]) and [`Spongos`] states. Messages are built from the
     /// [`Spongos`] state of a previous message. If the state for a link is not stored, then a
     /// message cannot be formed or processed.
@@ -105,6 +146,14 @@ pub struct User<T> {
     /// The internal [state](`State`) of the user, containing message state mappings and publisher
     /// cursors for message processing.
     state: State,
+    /// The [`SubscriptionPolicy`] deciding on the subscriptions processed by the user.
+    subscription_policy: SubscriptionPolicy,
+    /// Maximum size of the frames the messages sent by the user are split into.
+    max_frame_size: usize,
+    /// The [`MessageHandler`] registered for each custom message type.
+    message_handlers: HashMap<u8, Arc<dyn MessageHandler>>,
+    /// The [`Clock`] timestamping the messages sent by the user.
+    clock: Arc<dyn Clock>,
 }
 
 impl User<()> {
@@ -122,7 +171,21 @@ impl<T> User<T> {
     /// * `psks`: A list of trusted pre shared keys.
     /// * `transport`: The transport to use for sending and receiving messages.
     /// * `lean`: If true, the client will store only required message states.
-    pub(crate) fn new<Psks>(user_id: Option<Identity>, psks: Psks, transport: T, lean: bool) -> Self
+    /// * `subscription_policy`: The policy deciding on the processed subscriptions.
+    /// * `auto_rekey`: If true, branches are re-keyed automatically when a member leaves.
+    /// * `max_frame_size`: The maximum size of the frames the sent messages are split into.
+    /// * `clock`: The clock timestamping the sent messages.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new<Psks>(
+        user_id: Option<Identity>,
+        psks: Psks,
+        transport: T,
+        lean: bool,
+        subscription_policy: SubscriptionPolicy,
+        auto_rekey: bool,
+        max_frame_size: usize,
+        clock: Arc<dyn Clock>,
+    ) -> Self
     where
         Psks: IntoIterator<Item = (PskId, Psk)>,
     {
@@ -141,13 +204,23 @@ impl<T> User<T> {
                 cursor_store: CursorStore::new(),
                 psk_store,
                 subscribers,
+                pending_subscriptions: Default::default(),
+                keyloads: Default::default(),
+                pending_rekeys: Default::default(),
+                auto_rekey,
+                key_rotations: Default::default(),
                 spongos_store: Default::default(),
                 stream_address: None,
+                stream_version: None,
                 author_identifier: None,
                 base_branch: Default::default(),
                 lean,
                 topics: Default::default(),
             },
+            subscription_policy,
+            max_frame_size,
+            message_handlers: HashMap::new(),
+            clock,
         }
     }
 
@@ -199,6 +272,45 @@ impl<T> User<T> {
         self.state.stream_address
     }
 
+    /// Returns the Streams version the stream is written with, if any. The messages published in
+    /// a stream of version [`STREAMS_VER_2`] are written with that version, and cannot carry the
+    /// features introduced since.
+    pub fn stream_version(&self) -> Option<u8> {
+        self.state.stream_version
+    }
+
+    /// Returns the Streams version of the messages published by the user: the version of the
+    /// stream, or [`STREAMS_VER`] if not connected to one yet
+    fn write_version(&self) -> u8 {
+        self.state.stream_version.unwrap_or(STREAMS_VER)
+    }
+
+    /// Checks that the user can publish in the stream, which is the case if the stream is written
+    /// with [`STREAMS_VER`] or [`STREAMS_VER_2`]
+    ///
+    /// # Arguments
+    /// * `action`: The action that requires publishing
+    fn check_writable(&self, action: &'static str) -> Result<()> {
+        match self.state.stream_version {
+            Some(version) if version != STREAMS_VER && version != STREAMS_VER_2 => {
+                Err(Error::ReadOnlyStream(version, action))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the messages published in the stream can carry a feature introduced with
+    /// [`STREAMS_VER`]
+    ///
+    /// # Arguments
+    /// * `feature`: The feature carried by the message being published
+    fn check_version_supports(&self, feature: &'static str) -> Result<()> {
+        match self.write_version() {
+            STREAMS_VER => Ok(()),
+            version => Err(Error::UnsupportedByStream(version, feature)),
+        }
+    }
+
     /// Returns a reference to the [`User`] transport client.
     pub fn transport(&self) -> &T {
         &self.transport
@@ -252,6 +364,163 @@ impl<T> User<T> {
         self.state.subscribers.iter()
     }
 
+    /// Returns an iterator over the subscriber [identifiers](`Identifier`) whose subscription
+    /// awaits approval
+    pub fn pending_subscriptions(&self) -> impl Iterator<Item = &Identifier> + Clone + '_ {
+        self.state.pending_subscriptions.iter()
+    }
+
+    /// Returns an iterator over the branch [topics](`Topic`) administered by the [`User`] that have
+    /// to be re-keyed, because a member of their latest keyload left since
+    pub fn pending_rekeys(&self) -> impl Iterator<Item = &Topic> + Clone + '_ {
+        self.state.pending_rekeys.iter()
+    }
+
+    /// Sets whether the branches are re-keyed automatically when a member leaves. If true, a new
+    /// keyload excluding the departed members is published on every affected branch as soon as
+    /// their unsubscription is processed. Members removed with [`User::remove_subscriber()`] or
+    /// [`User::remove_psk()`] are re-keyed out before the [`User`] sends its next message, or when
+    /// calling [`User::rekey()`].
+    ///
+    /// # Arguments
+    /// * `auto_rekey`: Whether to re-key the branches automatically
+    pub fn set_auto_rekey(&mut self, auto_rekey: bool) {
+        self.state.auto_rekey = auto_rekey;
+    }
+
+    /// Sets the [`KeyRotation`] policy of a branch, or removes it. The rotation schedule starts
+    /// from the moment the policy is set.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `rotation`: The rotation policy, or `None` to stop rotating the key of the branch
+    pub fn set_key_rotation<Top>(&mut self, topic: Top, rotation: Option<KeyRotation>)
+    where
+        Top: Into<Topic>,
+    {
+        let topic = topic.into();
+        match rotation {
+            Some(rotation) => {
+                let schedule = RotationSchedule::new(rotation, self.clock.now());
+                self.state.key_rotations.insert(topic, schedule);
+            }
+            None => {
+                self.state.key_rotations.remove(&topic);
+            }
+        }
+    }
+
+    /// Requests the rotation of the key of a branch administered by the [`User`], performed before
+    /// the next packet it sends in the branch. Branches without a [`KeyRotation`] policy get a
+    /// [`KeyRotation::OnDemand`] policy.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    pub fn request_key_rotation<Top>(&mut self, topic: Top)
+    where
+        Top: Into<Topic>,
+    {
+        let now = self.clock.now();
+        self.state
+            .key_rotations
+            .entry(topic.into())
+            .or_insert_with(|| RotationSchedule::new(KeyRotation::OnDemand, now))
+            .request();
+    }
+
+    /// Counts a packet published in a branch under its current key, and in its key rotation
+    /// schedule, if any
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `key_sequence`: The number of packets published under the key before the packet
+    fn record_key_usage(&mut self, topic: &Topic, key_sequence: usize) {
+        let packets = self.state.cursor_store.packets_under_key(topic);
+        self.state
+            .cursor_store
+            .set_packets_under_key(topic, packets.max(key_sequence + 1));
+        if let Some(schedule) = self.state.key_rotations.get_mut(topic) {
+            schedule.record_message();
+        }
+    }
+
+    /// Returns true if the key of a branch administered by the [`User`] is due for rotation, either
+    /// by its rotation schedule or because the key limit of the branch has been reached
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    fn key_rotation_due(&self, topic: &Topic) -> bool {
+        let is_admin = self.permission(topic).map_or(false, |permission| permission.is_admin());
+        is_admin
+            && self.state.keyloads.contains_key(topic)
+            && (self.state.cursor_store.key_exhausted(topic)
+                || self
+                    .state
+                    .key_rotations
+                    .get(topic)
+                    .map_or(false, |schedule| schedule.is_due(self.clock.now())))
+    }
+
+    /// Returns [`Error::KeyExhausted`] if a packet at the given position under the key of the
+    /// branch exceeds its key limit
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `key_sequence`: The number of packets published under the key before the packet
+    fn check_key_limit(&self, topic: &Topic, key_sequence: usize) -> Result<()> {
+        match self.state.cursor_store.key_limit(topic) {
+            Some(key_limit) if key_sequence >= key_limit => Err(Error::KeyExhausted(topic.clone(), key_limit)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replaces the [`SubscriptionPolicy`] deciding on the subscriptions processed from now on.
+    /// Subscriptions that are already pending remain pending.
+    ///
+    /// # Arguments
+    /// * `subscription_policy`: The new policy
+    pub fn set_subscription_policy(&mut self, subscription_policy: SubscriptionPolicy) {
+        self.subscription_policy = subscription_policy;
+    }
+
+    /// Replaces the maximum size of the frames the messages sent from now on are split into.
+    ///
+    /// # Arguments
+    /// * `max_frame_size`: The new maximum frame size, in bytes
+    pub fn set_max_frame_size(&mut self, max_frame_size: usize) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// Replaces the [`Clock`] timestamping the messages sent from now on, and against which
+    /// time-bounded permissions and key rotations are evaluated.
+    ///
+    /// # Arguments
+    /// * `clock`: The new clock
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+    }
+
+    /// Registers the [`MessageHandler`] wrapping and unwrapping the messages of a custom message
+    /// type, replacing the handler previously registered for the type, if any. Returns
+    /// [`Error::MessageTypeReserved`] if the type is not in [`CUSTOM_MESSAGE_TYPES`].
+    ///
+    /// # Arguments
+    /// * `message_type`: The custom message type
+    /// * `handler`: The handler of the messages of the type
+    pub fn register_message_handler<H>(&mut self, message_type: u8, handler: H) -> Result<()>
+    where
+        H: MessageHandler + 'static,
+    {
+        if !CUSTOM_MESSAGE_TYPES.contains(&message_type) {
+            return Err(Error::MessageTypeReserved(message_type));
+        }
+        self.message_handlers.insert(message_type, Arc::new(handler));
+        Ok(())
+    }
+
     /// If the subscriber is not readonly and the [`Permissioned`] is not tracked or the
     /// [`Permissioned`] is tracked and not equal to the provided subscriber [`Permissioned`],
     /// then the cursor should be stored.
@@ -291,10 +560,34 @@ impl<T> User<T> {
     }
 
     /// Remove a subscriber [`Identifier`] from state. Returns true if the subscriber was present.
+    ///
+    /// The branches administered by the [`User`] whose latest keyload includes the subscriber are
+    /// marked for re-keying (see [`User::pending_rekeys()`]). Use [`User::revoke_subscriber()`] to
+    /// re-key them right away.
     pub fn remove_subscriber(&mut self, id: &Identifier) -> bool {
+        self.revoke_keyloads(|keyload| {
+            let before = keyload.subscribers.len();
+            keyload.subscribers.retain(|s| s.identifier() != id);
+            keyload.subscribers.len() != before
+        });
         self.state.subscribers.remove(id)
     }
 
+    /// Approve a pending subscription, storing the subscriber [`Identifier`] in state. Returns true
+    /// if the subscription was pending.
+    pub fn approve_subscription(&mut self, id: &Identifier) -> bool {
+        let pending = self.state.pending_subscriptions.remove(id);
+        if pending {
+            self.add_subscriber(id.clone());
+        }
+        pending
+    }
+
+    /// Reject a pending subscription, discarding it. Returns true if the subscription was pending.
+    pub fn reject_subscription(&mut self, id: &Identifier) -> bool {
+        self.state.pending_subscriptions.remove(id)
+    }
+
     /// Store a new [Pre-Shared Key](`Psk`) in state. Returns true if [`Psk`] was not present.
     pub fn add_psk(&mut self, psk: Psk) -> bool {
         self.state.psk_store.insert(psk.to_pskid(), psk).is_none()
@@ -302,10 +595,116 @@ impl<T> User<T> {
 
     /// Remove a [`Psk`] from state by its [identifier](`PskId`). Returns true if the [`Psk`] was
     /// present.
+    ///
+    /// The branches administered by the [`User`] whose latest keyload includes the [`Psk`] are
+    /// marked for re-keying (see [`User::pending_rekeys()`]). Use [`User::revoke_psk()`] to re-key
+    /// them right away.
     pub fn remove_psk(&mut self, pskid: PskId) -> bool {
+        self.revoke_keyloads(|keyload| {
+            let before = keyload.psks.len();
+            keyload.psks.retain(|id| id != &pskid);
+            keyload.psks.len() != before
+        });
         self.state.psk_store.remove(&pskid).is_some()
     }
 
+    /// Removes a departed member from the latest keyload of every branch, marking the branches
+    /// administered by the [`User`] it was removed from for re-keying.
+    ///
+    /// # Arguments
+    /// * `revoke`: Removes the member from a keyload, returning true if it was present
+    fn revoke_keyloads<F>(&mut self, mut revoke: F)
+    where
+        F: FnMut(&mut Keyload) -> bool,
+    {
+        let State {
+            user_id,
+            cursor_store,
+            keyloads,
+            pending_rekeys,
+            ..
+        } = &mut self.state;
+        for (topic, keyload) in keyloads.iter_mut() {
+            let is_admin = user_id
+                .as_ref()
+                .and_then(|id| cursor_store.get_permission(topic, id.identifier()))
+                .map_or(false, |permission| permission.is_admin());
+            if revoke(keyload) && is_admin {
+                pending_rekeys.insert(topic.clone());
+            }
+        }
+    }
+
+    /// Downgrades the `ReadWrite` permission of the [`User`] in a branch to `Read` if its `Unix`
+    /// timestamp has elapsed according to its [`Clock`]. The permissions of other publishers are
+    /// checked against the timestamps of their messages instead (see [`User::could_publish()`]).
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    fn expire_permission(&mut self, topic: &Topic) {
+        if let (Some(now), Some(id)) = (self.clock.now(), self.identifier().cloned()) {
+            self.state.cursor_store.expire_permission(topic, &id, now);
+        }
+    }
+
+    /// Returns whether the publisher of a received message was allowed to publish it. `Unix`
+    /// durations are checked against the timestamp of the message rather than the clock of the
+    /// reader, so that messages read after the permission of their publisher has expired are still
+    /// accepted. Messages without a timestamp are rejected from publishers with a `Unix` duration.
+    /// The timestamps declared by publishers are bounded by the keyloads of the branch, which
+    /// downgrade the permissions expired at their publication (see
+    /// [`User::expire_permissions_at_keyload()`]).
+    ///
+    /// # Arguments
+    /// * `permission`: The permission of the publisher in the branch of the message
+    /// * `header`: The [`HDF`] of the message
+    fn could_publish(permission: &Permissioned<Identifier>, header: &HDF) -> bool {
+        match (permission, header.timestamp()) {
+            (Permissioned::Read(_), _) => false,
+            (Permissioned::ReadWrite(_, PermissionDuration::Unix(_)), None) => false,
+            (permission, Some(timestamp)) => !permission.is_expired(timestamp),
+            (_, None) => true,
+        }
+    }
+
+    /// Downgrades the `ReadWrite` permissions of a branch whose `Unix` timestamp had elapsed when
+    /// a keyload was published to `Read`, so that publishers cannot publish past the first keyload
+    /// following the expiry of their permission, whatever the timestamp they declare.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch of the keyload
+    /// * `timestamp`: The timestamp of the keyload, if any
+    fn expire_permissions_at_keyload(&mut self, topic: &Topic, timestamp: Option<u64>) -> Result<()> {
+        if let Some(timestamp) = timestamp {
+            let identifiers: Vec<Identifier> = self
+                .cursors_by_topic(topic)?
+                .map(|(permission, _)| permission.identifier().clone())
+                .collect();
+            for identifier in identifiers {
+                self.state.cursor_store.expire_permission(topic, &identifier, timestamp);
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the [`HDF`] of a message sent by the user, written with the Streams version of the
+    /// stream. Headers of [`STREAMS_VER`] are timestamped with the current time of its [`Clock`] if
+    /// known.
+    ///
+    /// # Arguments
+    /// * `message_type`: Message type identifier
+    /// * `sequence`: Publisher sequence number
+    /// * `publisher`: Publisher [`Identifier`]
+    /// * `topic`: Reference to branch [`Topic`]
+    fn message_header(&self, message_type: u8, sequence: usize, publisher: Identifier, topic: &Topic) -> HDF {
+        let mut header = HDF::new(message_type, sequence, publisher, topic);
+        header.version = self.write_version();
+        match self.clock.now() {
+            Some(now) if header.version == STREAMS_VER => header.with_timestamp(now),
+            _ => header,
+        }
+    }
+
     /// Sets the latest message link for a specified branch. If the branch does not exist, it is
     /// created.
     ///
@@ -324,29 +723,6 @@ impl<T> User<T> {
         self.state.cursor_store.get_latest_link(topic)
     }
 
-    /// Parse and process a [`TransportMessage`] dependent on its type.
-    ///
-    /// # Arguments
-    /// * `address`: The [`Address`] of the message to process
-    /// * `msg`: The raw [`TransportMessage`]
-    pub(crate) async fn handle_message(&mut self, address: Address, msg: TransportMessage) -> Result<Message> {
-        let preparsed = msg
-            .parse_header()
-            .await
-            .map_err(|e| Error::Unwrapping("header", address, e))?;
-
-        match preparsed.header().message_type() {
-            message_types::ANNOUNCEMENT => self.handle_announcement(address, preparsed).await,
-            message_types::BRANCH_ANNOUNCEMENT => self.handle_branch_announcement(address, preparsed).await,
-            message_types::SUBSCRIPTION => self.handle_subscription(address, preparsed).await,
-            message_types::UNSUBSCRIPTION => self.handle_unsubscription(address, preparsed).await,
-            message_types::KEYLOAD => self.handle_keyload(address, preparsed).await,
-            message_types::SIGNED_PACKET => self.handle_signed_packet(address, preparsed).await,
-            message_types::TAGGED_PACKET => self.handle_tagged_packet(address, preparsed).await,
-            unknown => Err(Error::MessageTypeUnknown(unknown)),
-        }
-    }
-
     /// Processes an announcement message, binding a [`User`] to the stream announced in the
     /// message.
     ///
@@ -354,17 +730,19 @@ impl<T> User<T> {
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
     async fn handle_announcement(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
-        // Check Topic
-        let publisher = preparsed.header().publisher().clone();
+        let version = preparsed.header().version;
 
         // Unwrap message
-        let announcement = announcement::Unwrap::default();
+        let announcement = announcement::Unwrap::new(version);
         let (message, spongos) = preparsed
             .unwrap(announcement)
             .await
             .map_err(|e| Error::Unwrapping("announcement", address, e))?;
 
         let topic = message.payload().content().topic();
+        // The author is the one the announcement is signed by, as headers of Streams version 1 do
+        // not identify their publisher
+        let author_id = message.payload().content().author_id().clone();
         // Insert new branch into store
         self.state.cursor_store.new_branch(topic.clone());
         self.state.topics.insert(topic.clone());
@@ -373,19 +751,19 @@ impl<T> User<T> {
         // known yet. The message must be unwrapped to retrieve the initial topic before storing cursors
         self.state
             .cursor_store
-            .insert_cursor(topic, Permissioned::Admin(publisher), INIT_MESSAGE_NUM);
+            .insert_cursor(topic, Permissioned::Admin(author_id.clone()), INIT_MESSAGE_NUM);
 
         // Store spongos
         self.state.spongos_store.insert(address.relative(), spongos);
 
         // Store message content into stores
-        let author_id = message.payload().content().author_id().clone();
 
         // Update branch links
         self.set_latest_link(topic.clone(), address.relative());
         self.state.author_identifier = Some(author_id);
         self.state.base_branch = topic.clone();
         self.state.stream_address = Some(address);
+        self.state.stream_version = Some(version);
 
         Ok(Message::from_lets_message(address, message))
     }
@@ -429,17 +807,21 @@ impl<T> User<T> {
                 return Ok(Message::orphan(address, preparsed));
             }
         };
-        let branch_announcement = branch_announcement::Unwrap::new(&mut linked_msg_spongos);
+        let branch_announcement = branch_announcement::Unwrap::new(&mut linked_msg_spongos, preparsed.header().version);
         let (message, spongos) = preparsed
             .unwrap(branch_announcement)
             .await
             .map_err(|e| Error::Unwrapping("branch announcement", address, e))?;
 
         let new_topic = message.payload().content().new_topic();
+        let max_packets_per_keyload = message.payload().content().max_packets_per_keyload();
         // Store spongos
         self.store_spongos(address.relative(), spongos, linked_msg_address);
         // Insert new branch into store
         self.state.cursor_store.new_branch(new_topic.clone());
+        self.state
+            .cursor_store
+            .set_key_limit(new_topic, max_packets_per_keyload);
         self.state.topics.insert(new_topic.clone());
         // Collect permissions from previous branch and clone them into new branch
         let prev_permissions = self
@@ -456,7 +838,8 @@ impl<T> User<T> {
         Ok(Message::from_lets_message(address, message))
     }
 
-    /// Processes a [`User`] subscription message, storing the subscriber [`Identifier`].
+    /// Processes a [`User`] subscription message, storing the subscriber [`Identifier`] as a
+    /// subscriber or as a pending subscription depending on the [`SubscriptionPolicy`].
     ///
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
@@ -495,7 +878,17 @@ impl<T> User<T> {
 
         // Store message content into stores
         let subscriber_identifier = message.payload().content().subscriber_identifier();
-        self.add_subscriber(subscriber_identifier.clone());
+        if !self.state.subscribers.contains(subscriber_identifier) {
+            match self.subscription_policy.decide(subscriber_identifier) {
+                SubscriptionDecision::Approve => {
+                    self.add_subscriber(subscriber_identifier.clone());
+                }
+                SubscriptionDecision::Pending => {
+                    self.state.pending_subscriptions.insert(subscriber_identifier.clone());
+                }
+                SubscriptionDecision::Reject => (),
+            }
+        }
 
         Ok(Message::from_lets_message(address, message))
     }
@@ -532,7 +925,9 @@ impl<T> User<T> {
         self.store_spongos(address.relative(), spongos, linked_msg_address);
 
         // Store message content into stores
-        self.remove_subscriber(message.payload().content().subscriber_identifier());
+        let subscriber_identifier = message.payload().content().subscriber_identifier();
+        self.remove_subscriber(subscriber_identifier);
+        self.state.pending_subscriptions.remove(subscriber_identifier);
 
         Ok(Message::from_lets_message(address, message))
     }
@@ -551,6 +946,7 @@ impl<T> User<T> {
             .topic_by_hash(preparsed.header().topic_hash())
             .ok_or(Error::UnknownTopic(*preparsed.header().topic_hash()))?;
         let publisher = preparsed.header().publisher().clone();
+        let timestamp = preparsed.header().timestamp();
         // Confirm keyload came from administrator
         if !self
             .state
@@ -584,6 +980,7 @@ impl<T> User<T> {
             self.state.user_id.as_ref(),
             author_identifier,
             &self.state.psk_store,
+            preparsed.header().version,
         );
         let (message, spongos) = preparsed
             .unwrap(keyload)
@@ -623,6 +1020,15 @@ impl<T> User<T> {
 
         // Have to make message before setting branch links due to immutable borrow in keyload::unwrap
         let final_message = Message::from_lets_message(address, message);
+        self.expire_permissions_at_keyload(&topic, timestamp)?;
+        if let Some(keyload) = final_message.as_keyload() {
+            self.state.keyloads.insert(topic.clone(), keyload.clone());
+            self.state.pending_rekeys.remove(&topic);
+        }
+        if let Some(schedule) = self.state.key_rotations.get_mut(&topic) {
+            schedule.reset(self.clock.now());
+        }
+        self.state.cursor_store.set_packets_under_key(&topic, 0);
         // Update branch links
         self.set_latest_link(topic, address.relative());
         Ok(final_message)
@@ -638,19 +1044,24 @@ impl<T> User<T> {
         let topic = self
             .topic_by_hash(preparsed.header().topic_hash())
             .ok_or(Error::UnknownTopic(*preparsed.header().topic_hash()))?;
-        let publisher = preparsed.header().publisher();
+        let publisher = preparsed.header().publisher().clone();
         let permission = self
             .state
             .cursor_store
-            .get_permission(&topic, publisher)
+            .get_permission(&topic, &publisher)
             .ok_or(Error::NoCursor(topic.clone()))?
             .clone();
+        let is_readonly = !Self::could_publish(&permission, preparsed.header());
         // From the point of view of cursor tracking, the message exists, regardless of the validity or
         // accessibility to its content. Therefore we must update the cursor of the publisher before
         // handling the message
         self.state
             .cursor_store
             .insert_cursor(&topic, permission, preparsed.header().sequence());
+        // Messages published once the write permission of their publisher had expired are rejected
+        if is_readonly {
+            return Err(Error::WrongRole("ReadWrite", publisher, "publish a signed packet"));
+        }
 
         // Unwrap message
         let linked_msg_address = preparsed
@@ -665,18 +1076,35 @@ impl<T> User<T> {
                 return Ok(Message::orphan(address, preparsed));
             }
         };
-        let signed_packet = signed_packet::Unwrap::new(&mut linked_msg_spongos);
+        let signed_packet = signed_packet::Unwrap::new(&mut linked_msg_spongos, preparsed.header().version);
         let (message, spongos) = preparsed
             .unwrap(signed_packet)
             .await
             .map_err(|e| Error::Unwrapping("signed packet", address, e))?;
+        // Packets published past the key limit of the branch are not accepted. Their position under
+        // the key is the one authenticated with them, unless the reader has already seen more packets
+        // under the key, or older versions that do not declare it
+        let packets_under_key = self.state.cursor_store.packets_under_key(&topic);
+        let key_sequence = message
+            .payload()
+            .content()
+            .key_sequence()
+            .map_or(packets_under_key, |key_sequence| key_sequence.max(packets_under_key));
+        self.check_key_limit(&topic, key_sequence)?;
+        let message = Message::from_lets_message(address, message);
 
         // Store spongos
         self.store_spongos(address.relative(), spongos, linked_msg_address);
 
+        // Account for the packet in the message budgets and key rotation schedule of the branch
+        self.state.cursor_store.spend_message_budgets(&topic, &publisher);
+        self.record_key_usage(&topic, key_sequence);
+
         // Store message content into stores
         self.set_latest_link(topic, address.relative());
-        Ok(Message::from_lets_message(address, message))
+
+        // Payloads that cannot be decompressed are left as received, with the error in the message
+        Ok(message.decompress_payloads())
     }
 
     /// Processes a tagged packet message, retrieving the public and masked payloads.
@@ -688,19 +1116,24 @@ impl<T> User<T> {
         let topic = self
             .topic_by_hash(preparsed.header().topic_hash())
             .ok_or(Error::UnknownTopic(*preparsed.header().topic_hash()))?;
-        let publisher = preparsed.header().publisher();
+        let publisher = preparsed.header().publisher().clone();
         let permission = self
             .state
             .cursor_store
-            .get_permission(&topic, publisher)
+            .get_permission(&topic, &publisher)
             .ok_or(Error::NoCursor(topic.clone()))?
             .clone();
+        let is_readonly = !Self::could_publish(&permission, preparsed.header());
         // From the point of view of cursor tracking, the message exists, regardless of the validity or
         // accessibility to its content. Therefore we must update the cursor of the publisher before
         // handling the message
         self.state
             .cursor_store
             .insert_cursor(&topic, permission, preparsed.header().sequence());
+        // Messages published once the write permission of their publisher had expired are rejected
+        if is_readonly {
+            return Err(Error::WrongRole("ReadWrite", publisher, "publish a tagged packet"));
+        }
 
         // Unwrap message
         let linked_msg_address = preparsed
@@ -715,23 +1148,116 @@ impl<T> User<T> {
                 return Ok(Message::orphan(address, preparsed));
             }
         };
-        let tagged_packet = tagged_packet::Unwrap::new(&mut linked_msg_spongos);
+        let tagged_packet = tagged_packet::Unwrap::new(&mut linked_msg_spongos, preparsed.header().version);
         let (message, spongos) = preparsed
             .unwrap(tagged_packet)
             .await
             .map_err(|e| Error::Unwrapping("tagged packet", address, e))?;
+        // Packets published past the key limit of the branch are not accepted. Their position under
+        // the key is the one authenticated with them, unless the reader has already seen more packets
+        // under the key, or older versions that do not declare it
+        let packets_under_key = self.state.cursor_store.packets_under_key(&topic);
+        let key_sequence = message
+            .payload()
+            .content()
+            .key_sequence()
+            .map_or(packets_under_key, |key_sequence| key_sequence.max(packets_under_key));
+        self.check_key_limit(&topic, key_sequence)?;
+        let message = Message::from_lets_message(address, message);
 
         // Store spongos
         self.store_spongos(address.relative(), spongos, linked_msg_address);
 
+        // Account for the packet in the message budgets and key rotation schedule of the branch
+        self.state.cursor_store.spend_message_budgets(&topic, &publisher);
+        self.record_key_usage(&topic, key_sequence);
+
         // Store message content into stores
         self.set_latest_link(topic, address.relative());
 
+        // Payloads that cannot be decompressed are left as received, with the error in the message
+        Ok(message.decompress_payloads())
+    }
+
+    /// Processes a custom message with the [`MessageHandler`] registered for its type, retrieving
+    /// its content. Messages of a type without a handler are tracked in the cursor of their
+    /// publisher, but fail with [`Error::MessageTypeUnknown`].
+    ///
+    /// # Arguments:
+    /// * `address`: The [`Address`] of the message to be processed
+    /// * `preparsed`: The [`PreparsedMessage`] to be processed
+    /// * `handler`: The [`MessageHandler`] of the message type, if any
+    async fn handle_custom_message(
+        &mut self,
+        address: Address,
+        preparsed: PreparsedMessage,
+        handler: Option<Arc<dyn MessageHandler>>,
+    ) -> Result<Message> {
+        let topic = self
+            .topic_by_hash(preparsed.header().topic_hash())
+            .ok_or(Error::UnknownTopic(*preparsed.header().topic_hash()))?;
+        let publisher = preparsed.header().publisher().clone();
+        let permission = self
+            .state
+            .cursor_store
+            .get_permission(&topic, &publisher)
+            .ok_or(Error::NoCursor(topic.clone()))?
+            .clone();
+        let is_readonly = !Self::could_publish(&permission, preparsed.header());
+        // From the point of view of cursor tracking, the message exists, regardless of the validity or
+        // accessibility to its content. Therefore we must update the cursor of the publisher before
+        // handling the message
+        self.state
+            .cursor_store
+            .insert_cursor(&topic, permission, preparsed.header().sequence());
+        // Messages published once the write permission of their publisher had expired are rejected
+        if is_readonly {
+            return Err(Error::WrongRole("ReadWrite", publisher, "publish a custom message"));
+        }
+        let message_type = preparsed.header().message_type();
+        let handler = handler.ok_or(Error::MessageTypeUnknown(message_type))?;
+
+        // Unwrap message
+        let linked_msg_address = preparsed
+            .header()
+            .linked_msg_address()
+            .ok_or(Error::NotLinked("custom", address))?;
+        let mut linked_msg_spongos = {
+            if let Some(spongos) = self.state.spongos_store.get(&linked_msg_address).copied() {
+                // Spongos must be copied because wrapping mutates it
+                spongos
+            } else {
+                return Ok(Message::orphan(address, preparsed));
+            }
+        };
+        let custom = custom::Unwrap::new(&mut linked_msg_spongos, handler.as_ref(), message_type);
+        let (message, spongos) = preparsed
+            .unwrap(custom)
+            .await
+            .map_err(|e| Error::Unwrapping("custom message", address, e))?;
+        // Custom messages published past the key limit of the branch are not accepted, counting the
+        // packets the reader has already seen under the key if the publisher declares fewer
+        let key_sequence = message
+            .payload()
+            .content()
+            .key_sequence()
+            .max(self.state.cursor_store.packets_under_key(&topic));
+        self.check_key_limit(&topic, key_sequence)?;
+
+        // Store spongos
+        self.store_spongos(address.relative(), spongos, linked_msg_address);
+
+        // Account for the message in the message budgets and key rotation schedule of the branch
+        self.state.cursor_store.spend_message_budgets(&topic, &publisher);
+        self.record_key_usage(&topic, key_sequence);
+
+        // Custom messages do not become the latest link of the branch, so that readers without a
+        // handler for their type can still process the messages that follow them
         Ok(Message::from_lets_message(address, message))
     }
 
     /// Creates an encrypted, serialised representation of a [`User`] `State` for backup and
-    /// recovery.
+    /// recovery. The backup starts with the version of its format, in clear.
     ///
     /// # Arguments
     /// * `pwd`: The password to encrypt the `State` with
@@ -740,14 +1266,17 @@ impl<T> User<T> {
         P: AsRef<[u8]>,
     {
         let mut ctx = sizeof::Context::new();
+        ctx.absorb(Uint8::new(BACKUP_VERSION)).map_err(Error::Spongos)?;
         ctx.sizeof(&self.state).await.map_err(Error::Spongos)?;
-        let buf_size = ctx.finalize() + 32; // State + Mac Size
+        let buf_size = ctx.finalize() + 32; // Version + State + Mac Size
 
         let mut buf = vec![0; buf_size];
 
         let mut ctx = wrap::Context::new(&mut buf[..]);
         let key: [u8; 32] = SpongosRng::<KeccakF1600>::new(pwd).gen();
-        ctx.absorb(External::new(&NBytes::new(key)))
+        ctx.absorb(Uint8::new(BACKUP_VERSION))
+            .map_err(Error::Spongos)?
+            .absorb(External::new(&NBytes::new(key)))
             .map_err(Error::Spongos)?
             .commit()
             .map_err(Error::Spongos)?
@@ -766,6 +1295,19 @@ impl<T> User<T> {
     /// Restore a [`User`] from an encrypted binary stream using the provided password and transport
     /// client.
     ///
+    /// Pending subscriptions are restored, but the [`SubscriptionPolicy`] is not part of the
+    /// backup: the restored user approves every subscription until
+    /// [`User::set_subscription_policy()`] is called. Likewise, the restored user splits its
+    /// messages into frames of [`DEFAULT_FRAME_SIZE`] bytes until [`User::set_max_frame_size()`] is
+    /// called, processes no custom message type until handlers are registered with
+    /// [`User::register_message_handler()`], and reads the time from the [`SystemClock`] until
+    /// [`User::set_clock()`] is called.
+    ///
+    /// Backups made before their format was versioned are migrated: the state they predate, such as
+    /// the pending subscriptions, the latest keyloads and the key rotation and packet limits of the
+    /// branches, is left empty, and their stream, written with Streams version 2, keeps being
+    /// published in with messages of that version.
+    ///
     /// # Arguments
     /// * `backup`: Encrypted binary stream of backed up `State`.
     /// * `pwd`: The decryption password.
@@ -775,32 +1317,34 @@ impl<T> User<T> {
         P: AsRef<[u8]>,
         B: AsRef<[u8]>,
     {
-        let mut ctx = unwrap::Context::new(backup.as_ref());
-        let key: [u8; 32] = SpongosRng::<KeccakF1600>::new(pwd).gen();
-        ctx.absorb(External::new(&NBytes::new(key)))
-            .map_err(Error::Spongos)?
-            .commit()
-            .map_err(Error::Spongos)?
-            .squeeze(&Mac::new(32))
-            .map_err(Error::Spongos)?;
-        let mut state = State::default();
-        ctx.unwrap(&mut state).await.map_err(Error::Spongos)?;
-        Ok(User { transport, state })
+        let state = match State::from_backup(backup.as_ref(), pwd.as_ref()).await {
+            Ok(state) => state,
+            // Unversioned backups start with the password MAC instead
+            Err(e) => State::from_unversioned_backup(backup.as_ref(), pwd.as_ref())
+                .await
+                .map_err(|_| e)?,
+        };
+        Ok(User {
+            transport,
+            state,
+            subscription_policy: SubscriptionPolicy::approve_all(),
+            max_frame_size: DEFAULT_FRAME_SIZE,
+            message_handlers: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        })
     }
 }
 
-impl<T> User<T>
+impl<T, TSR> User<T>
 where
-    T: for<'a> Transport<'a, Msg = TransportMessage>,
+    T: for<'a> Transport<'a, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+    TSR: MaybeSend,
 {
     /// Receive a raw message packet using the internal [`Transport`] client
     ///
     /// # Arguments
     /// * `address`: The [`Address`] of the message to be retrieved.
-    pub async fn receive_message(&mut self, address: Address) -> Result<Message>
-    where
-        T: for<'a> Transport<'a, Msg = TransportMessage>,
-    {
+    pub async fn receive_message(&mut self, address: Address) -> Result<Message> {
         let msg = self
             .transport
             .recv_message(address)
@@ -809,18 +1353,219 @@ where
         self.handle_message(address, msg).await
     }
 
-    /// Start a [`Messages`] stream to traverse the channel messages
+    /// Parse and process a [`TransportMessage`] dependent on its type. The remaining frames of a
+    /// message split into several frames are fetched and the message is reassembled first.
     ///
-    /// See the documentation in [`Messages`] for more details and examples.
-    pub fn messages(&mut self) -> Messages<T> {
-        Messages::new(self)
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message to process
+    /// * `msg`: The raw [`TransportMessage`]
+    pub(crate) async fn handle_message(&mut self, address: Address, msg: TransportMessage) -> Result<Message> {
+        let preparsed = msg
+            .parse_header()
+            .await
+            .map_err(|e| Error::Unwrapping("header", address, e))?;
+        match preparsed.header().payload_frame_count() > 1 {
+            true => self.handle_framed_message(address, preparsed).await,
+            false => self.dispatch_message(address, preparsed).await,
+        }
     }
 
-    /// Iteratively fetches all the next messages until internal state has caught up
+    /// Process a [`PreparsedMessage`] dependent on its type
     ///
-    /// If succeeded, returns the number of messages advanced.
-    pub async fn sync(&mut self) -> Result<usize> {
-        // ignoring the result is sound as Drain::Error is Infallible
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message to process
+    /// * `preparsed`: The [`PreparsedMessage`] to process
+    async fn dispatch_message(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+        if preparsed.header().version == STREAMS_VER_1 {
+            return self.dispatch_v1_message(address, preparsed).await;
+        }
+        match preparsed.header().message_type() {
+            message_types::ANNOUNCEMENT => self.handle_announcement(address, preparsed).await,
+            message_types::BRANCH_ANNOUNCEMENT => self.handle_branch_announcement(address, preparsed).await,
+            message_types::SUBSCRIPTION => self.handle_subscription(address, preparsed).await,
+            message_types::UNSUBSCRIPTION => {
+                let message = self.handle_unsubscription(address, preparsed).await?;
+                if self.state.auto_rekey {
+                    self.rekey().await?;
+                }
+                Ok(message)
+            }
+            message_types::KEYLOAD => self.handle_keyload(address, preparsed).await,
+            message_types::SIGNED_PACKET => self.handle_signed_packet(address, preparsed).await,
+            message_types::TAGGED_PACKET => self.handle_tagged_packet(address, preparsed).await,
+            other => {
+                let handler = self.message_handlers.get(&other).cloned();
+                self.handle_custom_message(address, preparsed, handler).await
+            }
+        }
+    }
+
+    /// Process a [`PreparsedMessage`] of [`STREAMS_VER_1`] dependent on its type. Streams of this
+    /// version are read-only: their subscriptions cannot be processed, as only their author could
+    /// read them.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message to process
+    /// * `preparsed`: The [`PreparsedMessage`] to process
+    async fn dispatch_v1_message(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+        match preparsed.header().message_type() {
+            message_types::v1::ANNOUNCEMENT => self.handle_announcement(address, preparsed).await,
+            message_types::v1::KEYLOAD | message_types::v1::SIGNED_PACKET | message_types::v1::TAGGED_PACKET => {
+                self.handle_v1_message(address, preparsed).await
+            }
+            message_types::v1::SUBSCRIPTION | message_types::v1::UNSUBSCRIPTION => {
+                Err(Error::ReadOnlyStream(STREAMS_VER_1, "process subscriptions"))
+            }
+            other => Err(Error::MessageTypeUnknown(other)),
+        }
+    }
+
+    /// Processes a keyload, signed packet or tagged packet of [`STREAMS_VER_1`]. Their headers
+    /// carry neither the branch nor the publisher of the message, so they are read in the base
+    /// branch of the stream, and their signed packets are verified against the author of the
+    /// stream. Neither cursors nor permissions are tracked: streams of this version are read by
+    /// [`Address`] with [`User::receive_message()`].
+    ///
+    /// # Arguments:
+    /// * `address`: The [`Address`] of the message to be processed
+    /// * `preparsed`: The [`PreparsedMessage`] to be processed
+    async fn handle_v1_message(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+        let linked_msg_address = preparsed
+            .header()
+            .linked_msg_address()
+            .ok_or(Error::NotLinked("version 1", address))?;
+        let mut linked_msg_spongos = {
+            if let Some(spongos) = self.state.spongos_store.get(&linked_msg_address).copied() {
+                // Spongos must be copied because wrapping mutates it
+                spongos
+            } else {
+                return Ok(Message::orphan(address, preparsed));
+            }
+        };
+        let author_identifier = self
+            .state
+            .author_identifier
+            .as_ref()
+            .ok_or(Error::NoStream("handling a message"))?;
+
+        let (message, spongos) = match preparsed.header().message_type() {
+            message_types::v1::KEYLOAD => {
+                let keyload = keyload::Unwrap::new(
+                    &mut linked_msg_spongos,
+                    self.state.user_id.as_ref(),
+                    author_identifier,
+                    &self.state.psk_store,
+                    STREAMS_VER_1,
+                );
+                let (message, spongos) = preparsed
+                    .unwrap(keyload)
+                    .await
+                    .map_err(|e| Error::Unwrapping("keyload", address, e))?;
+                (Message::from_lets_message(address, message), spongos)
+            }
+            message_types::v1::SIGNED_PACKET => {
+                let signed_packet = signed_packet::Unwrap::new(&mut linked_msg_spongos, STREAMS_VER_1)
+                    .with_publisher_identifier(author_identifier.clone());
+                let (message, spongos) = preparsed
+                    .unwrap(signed_packet)
+                    .await
+                    .map_err(|e| Error::Unwrapping("signed packet", address, e))?;
+                (Message::from_lets_message(address, message), spongos)
+            }
+            _ => {
+                let tagged_packet = tagged_packet::Unwrap::new(&mut linked_msg_spongos, STREAMS_VER_1);
+                let (message, spongos) = preparsed
+                    .unwrap(tagged_packet)
+                    .await
+                    .map_err(|e| Error::Unwrapping("tagged packet", address, e))?;
+                (Message::from_lets_message(address, message), spongos)
+            }
+        };
+
+        // Store spongos
+        self.store_spongos(address.relative(), spongos, linked_msg_address);
+        self.set_latest_link(self.state.base_branch.clone(), address.relative());
+        Ok(message)
+    }
+
+    /// Fetches the remaining frames of a message split into several frames, reassembles the
+    /// message and processes it. Frames are chained to the first frame of the message, which is
+    /// public, so anyone can publish frames chained to it at the address of a frame. Every chain
+    /// of frames found is therefore reassembled in turn until the message is processed, and the
+    /// error of the last chain is returned if none is.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message, where its first frame is published
+    /// * `preparsed`: The [`PreparsedMessage`] of the first frame
+    async fn handle_framed_message(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+        let frame_count = preparsed.header().payload_frame_count();
+        let (_, first_frame, _, _) = preparsed.into_parts();
+        // Chains are explored depth first, in the order their frames are found
+        let mut chains = vec![FrameAssembler::<KeccakF1600>::new(first_frame, frame_count)];
+        let mut error = None;
+        while let Some(assembler) = chains.pop() {
+            match assembler.next_frame_num() {
+                Some(frame_num) => {
+                    let frame_address = address.frame(frame_num);
+                    let frames = match self.transport.recv_messages(frame_address).await {
+                        Ok(frames) => frames,
+                        Err(e) if e.is_not_found() => Vec::new(),
+                        Err(e) => return Err(Error::Transport(frame_address, "receive message frame", e)),
+                    };
+                    let mut chained = Vec::new();
+                    for frame in &frames {
+                        let mut chain = assembler.clone();
+                        if chain.push(frame).await.is_ok() {
+                            chained.push(chain);
+                        }
+                    }
+                    if chained.is_empty() {
+                        error = Some(Error::FrameMissing(address, frame_num));
+                    }
+                    chains.extend(chained.into_iter().rev());
+                }
+                None => {
+                    let preparsed = assembler
+                        .finish()
+                        .map_err(|e| Error::Unwrapping("frames", address, e))?
+                        .parse_header()
+                        .await
+                        .map_err(|e| Error::Unwrapping("header", address, e))?;
+                    match self.dispatch_message(address, preparsed).await {
+                        Ok(message) => return Ok(message),
+                        Err(e) => error = Some(e),
+                    }
+                }
+            }
+        }
+        Err(error.unwrap_or(Error::FrameMissing(address, 2)))
+    }
+
+    /// Checks whether a message has already been published at the [`Address`]. Several messages
+    /// found at the address, possibly spam, also count as the address being in use.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] to check
+    async fn is_address_used(&mut self, address: Address) -> Result<bool> {
+        match self.transport.recv_message(address).await {
+            Ok(_) | Err(LetsError::Spam(_)) => Ok(true),
+            Err(e) if e.is_not_found() => Ok(false),
+            Err(e) => Err(Error::Transport(address, "check if the address is in use", e)),
+        }
+    }
+
+    /// Start a [`Messages`] stream to traverse the channel messages
+    ///
+    /// See the documentation in [`Messages`] for more details and examples.
+    pub fn messages(&mut self) -> Messages<T> {
+        Messages::new(self)
+    }
+
+    /// Iteratively fetches all the next messages until internal state has caught up
+    ///
+    /// If succeeded, returns the number of messages advanced.
+    pub async fn sync(&mut self) -> Result<usize> {
+        // ignoring the result is sound as Drain::Error is Infallible
         self.messages()
             .try_fold(0, |n, _| future::ok(n + 1))
             .await
@@ -835,12 +1580,45 @@ where
     pub async fn fetch_next_messages(&mut self) -> Result<Vec<Message>> {
         self.messages().try_collect().await.map_err(Error::Messages)
     }
+
+    /// Iteratively fetches all the pending messages from the transport, returning only the ones
+    /// matched by the [`Selector`]. The other messages are still processed, and advance the state
+    /// of the [`User`].
+    ///
+    /// # Arguments
+    /// * `selector`: The selector the returned messages are matched by
+    pub async fn fetch_next_messages_matching(&mut self, selector: &Selector) -> Result<Vec<Message>> {
+        self.messages()
+            .try_filter(|message| future::ready(selector.is(message)))
+            .try_collect()
+            .await
+            .map_err(Error::Messages)
+    }
 }
 
 impl<T, TSR> User<T>
 where
-    T: for<'a> Transport<'a, Msg = TransportMessage, SendResponse = TSR>,
+    T: for<'a> Transport<'a, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+    TSR: MaybeSend,
 {
+    /// Sends the frames of a message. The frames following the first one are sent first, at the
+    /// addresses derived from the address of the message, so that the message is complete once
+    /// its first frame is published at its address.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message
+    /// * `frames`: The frames of the message, as returned by [`LetsMessage::wrap_frames()`]
+    ///
+    /// Returns:
+    /// The transport response of the first frame
+    async fn send_frames(&mut self, address: Address, mut frames: Vec<TransportMessage>) -> LetsResult<TSR> {
+        let first_frame = frames.remove(0);
+        for (frame, frame_num) in frames.into_iter().zip(2..) {
+            self.transport.send_message(address.frame(frame_num), frame).await?;
+        }
+        self.transport.send_message(address, first_frame).await
+    }
+
     /// Create and send a stream Announcement message, anchoring the stream for others to attach to.
     /// Errors if the [`User`] is already attached to a stream, or if the message already exists in
     /// the transport layer.
@@ -864,23 +1642,22 @@ where
         let stream_address = Address::new(stream_base_address, stream_rel_address);
 
         // Prepare HDF and PCF
-        let header = HDF::new(message_types::ANNOUNCEMENT, ANN_MESSAGE_NUM, identifier.clone(), &topic);
+        let header = self.message_header(message_types::ANNOUNCEMENT, ANN_MESSAGE_NUM, identifier.clone(), &topic);
         let content = PCF::new_final_frame().with_content(announcement::Wrap::new(self.identity().unwrap(), &topic));
 
         // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
+        let (frames, spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
             .await
             .map_err(|e| Error::Wrapped("wrap announce", e))?;
 
         // Attempt to send message
-        if !self.transport.recv_message(stream_address).await.is_err() {
+        if self.is_address_used(stream_address).await? {
             return Err(Error::Setup("Cannot create a channel, announce address already in use"));
         }
 
         let send_response = self
-            .transport
-            .send_message(stream_address, transport_msg)
+            .send_frames(stream_address, frames)
             .await
             .map_err(|e| Error::Transport(stream_address, "send announce message", e))?;
 
@@ -898,6 +1675,7 @@ where
 
         // Commit Author Identifier and Stream Address to store
         self.state.stream_address = Some(stream_address);
+        self.state.stream_version = Some(STREAMS_VER);
         self.state.author_identifier = Some(identifier);
         self.state.base_branch = topic;
 
@@ -915,15 +1693,57 @@ where
         from_topic: impl Into<Topic>,
         to_topic: impl Into<Topic>,
     ) -> Result<SendResponse<TSR>> {
+        self.announce_branch(from_topic.into(), to_topic.into(), None).await
+    }
+
+    /// Create and send a new Branch Announcement message, like [`User::new_branch()`], declaring
+    /// the maximum number of packets that may be published in the new branch after each keyload.
+    ///
+    /// Packets published past the limit are rejected by every reader of the branch with
+    /// [`Error::KeyExhausted`], until a new keyload is published. If the [`User`] administers the
+    /// branch, the key is rotated before the packet that would exceed the limit, as with a
+    /// [`KeyRotation`] policy.
+    ///
+    /// # Arguments
+    /// * `from_topic`: The [`Topic`] of the branch to generate the new branch from.
+    /// * `to_topic`: The [`Topic`] of the new branch being created.
+    /// * `max_packets_per_keyload`: The maximum number of packets that may follow a keyload.
+    pub async fn new_branch_with_key_limit(
+        &mut self,
+        from_topic: impl Into<Topic>,
+        to_topic: impl Into<Topic>,
+        max_packets_per_keyload: usize,
+    ) -> Result<SendResponse<TSR>> {
+        self.announce_branch(from_topic.into(), to_topic.into(), Some(max_packets_per_keyload))
+            .await
+    }
+
+    /// Sends the Branch Announcement message of a new branch, optionally limiting the number of
+    /// packets that may follow a keyload in it
+    ///
+    /// # Arguments
+    /// * `prev_topic`: The [`Topic`] of the branch to generate the new branch from.
+    /// * `topic`: The [`Topic`] of the new branch being created.
+    /// * `max_packets_per_keyload`: The maximum number of packets per keyload, if limited.
+    async fn announce_branch(
+        &mut self,
+        prev_topic: Topic,
+        topic: Topic,
+        max_packets_per_keyload: Option<usize>,
+    ) -> Result<SendResponse<TSR>> {
+        self.check_writable("create a branch")?;
+        if max_packets_per_keyload.is_some() {
+            self.check_version_supports("packet limits")?;
+        }
+        if self.state.auto_rekey {
+            self.rekey().await?;
+        }
         // Check conditions
         let stream_address = self
             .stream_address()
             .ok_or(Error::Setup("before starting a new branch, the stream must be created"))?;
         // Confirm user has identity
         let identifier = self.identifier().ok_or(Error::NoIdentity("create a branch"))?.clone();
-        // Check Topic
-        let topic: Topic = to_topic.into();
-        let prev_topic: Topic = from_topic.into();
         // Check Permission
         let permission = self
             .state
@@ -952,37 +1772,40 @@ where
             .get(&link_to)
             .copied()
             .ok_or(Error::MessageMissing(link_to, "spongos store"))?;
-        let header = HDF::new(
-            message_types::BRANCH_ANNOUNCEMENT,
-            user_cursor,
-            identifier.clone(),
-            &prev_topic,
-        )
-        .with_linked_msg_address(link_to);
+        let header = self
+            .message_header(
+                message_types::BRANCH_ANNOUNCEMENT,
+                user_cursor,
+                identifier.clone(),
+                &prev_topic,
+            )
+            .with_linked_msg_address(link_to);
         let content = PCF::new_final_frame().with_content(branch_announcement::Wrap::new(
             &mut linked_msg_spongos,
             self.identity().unwrap(),
             &topic,
+            max_packets_per_keyload,
+            self.write_version(),
         ));
 
         // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
+        let (frames, spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
             .await
             .map_err(|e| Error::Wrapped("wrap new branch", e))?;
 
-        if !self.transport.recv_message(address).await.is_err() {
+        if self.is_address_used(address).await? {
             return Err(Error::AddressUsed("new branch", address));
         }
 
         let send_response = self
-            .transport
-            .send_message(address, transport_msg)
+            .send_frames(address, frames)
             .await
             .map_err(|e| Error::Transport(stream_address, "send new branch message", e))?;
 
         // If message has been sent successfully, create the new branch in store
         self.state.cursor_store.new_branch(topic.clone());
+        self.state.cursor_store.set_key_limit(&topic, max_packets_per_keyload);
         self.state.topics.insert(topic.clone());
         // Commit message to stores and update cursors
         self.state.cursor_store.insert_cursor(
@@ -1012,6 +1835,7 @@ where
         let stream_address = self
             .stream_address()
             .ok_or(Error::Setup("before starting a new branch, the stream must be created"))?;
+        self.check_writable("subscribe")?;
         // Confirm user has identity
         let user_id = self.identity().ok_or(Error::NoIdentity("subscribe"))?;
         let identifier = user_id.identifier();
@@ -1045,17 +1869,18 @@ where
             user_id,
             &author_ke_pk,
         ));
-        let header = HDF::new(
-            message_types::SUBSCRIPTION,
-            SUB_MESSAGE_NUM,
-            identifier.clone(),
-            base_branch,
-        )
-        .with_linked_msg_address(link_to);
+        let header = self
+            .message_header(
+                message_types::SUBSCRIPTION,
+                SUB_MESSAGE_NUM,
+                identifier.clone(),
+                base_branch,
+            )
+            .with_linked_msg_address(link_to);
 
         // Wrap message
-        let (transport_msg, _spongos) = LetsMessage::new(header, content)
-            .wrap()
+        let (frames, _spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
             .await
             .map_err(|e| Error::Wrapped("subscribe", e))?;
 
@@ -1063,14 +1888,12 @@ where
         let message_address = Address::new(stream_address.base(), rel_address);
 
         // Attempt to send message
-        let has_msg = self.transport.recv_message(message_address).await;
-        if !has_msg.is_err() {
+        if self.is_address_used(message_address).await? {
             return Err(Error::AddressUsed("subscribe", message_address));
         }
 
         let send_response = self
-            .transport
-            .send_message(message_address, transport_msg)
+            .send_frames(message_address, frames)
             .await
             .map_err(|e| Error::Transport(message_address, "send subscribe message", e))?;
 
@@ -1088,19 +1911,20 @@ where
         let stream_address = self
             .stream_address()
             .ok_or(Error::Setup("before unsubscribing, the stream must be created"))?;
+        self.check_writable("unsubscribe")?;
         // Confirm user has identity
         let user_id = self.identity().ok_or(Error::NoIdentity("unsubscribe"))?;
         let identifier = user_id.identifier().clone();
         // Get base branch topic
-        let base_branch = &self.state.base_branch;
+        let base_branch = self.state.base_branch.clone();
         // Link message to channel announcement
         let link_to = self
-            .get_latest_link(base_branch)
+            .get_latest_link(&base_branch)
             .ok_or_else(|| Error::TopicNotFound(base_branch.clone()))?;
 
         // Update own's cursor
-        let new_cursor = self.next_cursor(base_branch)?;
-        let rel_address = MsgId::gen(stream_address.base(), &identifier, base_branch, new_cursor);
+        let new_cursor = self.next_cursor(&base_branch)?;
+        let rel_address = MsgId::gen(stream_address.base(), &identifier, &base_branch, new_cursor);
 
         // Prepare HDF and PCF
         // Spongos must be copied because wrapping mutates it
@@ -1111,29 +1935,29 @@ where
             .copied()
             .ok_or(Error::MessageMissing(link_to, "spongos store"))?;
         let content = PCF::new_final_frame().with_content(unsubscription::Wrap::new(&mut linked_msg_spongos, user_id));
-        let header = HDF::new(
-            message_types::UNSUBSCRIPTION,
-            new_cursor,
-            identifier.clone(),
-            base_branch,
-        )
-        .with_linked_msg_address(link_to);
+        let header = self
+            .message_header(
+                message_types::UNSUBSCRIPTION,
+                new_cursor,
+                identifier.clone(),
+                &base_branch,
+            )
+            .with_linked_msg_address(link_to);
 
         // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
+        let (frames, spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
             .await
             .map_err(|e| Error::Wrapped("unsubscribe", e))?;
 
         // Attempt to send message
         let message_address = Address::new(stream_address.base(), rel_address);
-        if self.transport.recv_message(message_address).await.is_err() {
+        if self.is_address_used(message_address).await? {
             return Err(Error::AddressUsed("unsubscribe", message_address));
         }
 
         let send_response = self
-            .transport
-            .send_message(message_address, transport_msg)
+            .send_frames(message_address, frames)
             .await
             .map_err(|e| Error::Transport(stream_address, "send unsubscribe message", e))?;
 
@@ -1141,7 +1965,7 @@ where
         let permission = Permissioned::Read(identifier);
         self.state
             .cursor_store
-            .insert_cursor(base_branch, permission, new_cursor);
+            .insert_cursor(&base_branch, permission, new_cursor);
         self.store_spongos(rel_address, spongos, link_to);
         Ok(SendResponse::new(message_address, send_response))
     }
@@ -1170,6 +1994,7 @@ where
         let stream_address = self
             .stream_address()
             .ok_or(Error::Setup("before sending a keyload, the stream must be created"))?;
+        self.check_writable("send a keyload")?;
         // Confirm user has identity
         let user_id = self.identity().ok_or(Error::NoIdentity("send keyload"))?;
         let identifier = user_id.identifier().clone();
@@ -1205,6 +2030,18 @@ where
             .into_iter()
             .map(|pskid| Ok((pskid, self.state.psk_store.get(&pskid).ok_or(Error::UnknownPsk(pskid))?)))
             .collect::<Result<Vec<(_, _)>>>()?; // collect to handle possible error
+        let recipients = Keyload {
+            subscribers: subscribers.clone().into_iter().map(Into::into).collect(),
+            psks: psk_ids_with_psks.iter().map(|(pskid, _)| *pskid).collect(),
+        };
+        // Without timestamps, publishers holding a `Unix` permission could not publish at all
+        if recipients
+            .subscribers
+            .iter()
+            .any(|subscriber| matches!(subscriber, Permissioned::ReadWrite(_, PermissionDuration::Unix(_))))
+        {
+            self.check_version_supports("Unix permission durations")?;
+        }
         let content = PCF::new_final_frame().with_content(keyload::Wrap::new(
             &mut announcement_msg_spongos,
             subscribers.clone().into_iter().collect::<Vec<_>>(),
@@ -1213,28 +2050,35 @@ where
             nonce,
             user_id,
         ));
-        let header =
-            HDF::new(message_types::KEYLOAD, new_cursor, identifier.clone(), &topic).with_linked_msg_address(link_to);
+        let header = self
+            .message_header(message_types::KEYLOAD, new_cursor, identifier.clone(), &topic)
+            .with_linked_msg_address(link_to);
+        let timestamp = header.timestamp();
 
         // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
+        let (frames, spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
             .await
             .map_err(|e| Error::Wrapped("send keyload", e))?;
 
         // Attempt to send message
         let message_address = Address::new(stream_address.base(), rel_address);
-        if !self.transport.recv_message(message_address).await.is_err() {
+        if self.is_address_used(message_address).await? {
             return Err(Error::AddressUsed("keyload", message_address));
         }
 
         let send_response = self
-            .transport
-            .send_message(message_address, transport_msg)
+            .send_frames(message_address, frames)
             .await
             .map_err(|e| Error::Transport(stream_address, "send keyload message", e))?;
 
         // If message has been sent successfully, commit message to stores
+        self.state.keyloads.insert(topic.clone(), recipients);
+        self.state.pending_rekeys.remove(&topic);
+        if let Some(schedule) = self.state.key_rotations.get_mut(&topic) {
+            schedule.reset(self.clock.now());
+        }
+        self.state.cursor_store.set_packets_under_key(&topic, 0);
         for subscriber in subscribers {
             if self.should_store_cursor(&topic, subscriber) {
                 self.state
@@ -1242,6 +2086,7 @@ where
                     .insert_cursor(&topic, subscriber.into(), INIT_MESSAGE_NUM);
             }
         }
+        self.expire_permissions_at_keyload(&topic, timestamp)?;
         self.state
             .cursor_store
             .insert_cursor(&topic, Permissioned::Admin(identifier), new_cursor);
@@ -1327,6 +2172,109 @@ where
         .await
     }
 
+    /// Re-keys every branch administered by the [`User`] whose latest keyload was sent to a member
+    /// that left since (see [`User::pending_rekeys()`]), publishing a new keyload for the remaining
+    /// members. Departed members cannot read the messages published in the branch afterwards.
+    ///
+    /// This is done automatically when processing an unsubscription, and before sending any packet
+    /// or branch announcement, if the [`User`] is configured to [re-key
+    /// automatically](`User::set_auto_rekey`).
+    ///
+    /// Returns:
+    /// The responses of the published keyloads
+    pub async fn rekey(&mut self) -> Result<Vec<SendResponse<TSR>>> {
+        let topics: Vec<Topic> = self.pending_rekeys().cloned().collect();
+        let mut responses = Vec::with_capacity(topics.len());
+        for topic in topics {
+            if let Some(response) = self.rekey_branch(topic).await? {
+                responses.push(response);
+            }
+        }
+        Ok(responses)
+    }
+
+    /// Removes a subscriber from state like [`User::remove_subscriber()`], and re-keys right away
+    /// the branches pending a re-key, including the ones whose latest keyload included the
+    /// subscriber (see [`User::rekey()`]).
+    ///
+    /// # Arguments
+    /// * `id`: The [`Identifier`] of the subscriber
+    ///
+    /// Returns:
+    /// The responses of the published keyloads
+    pub async fn revoke_subscriber(&mut self, id: &Identifier) -> Result<Vec<SendResponse<TSR>>> {
+        self.remove_subscriber(id);
+        self.rekey().await
+    }
+
+    /// Removes a [`Psk`] from state like [`User::remove_psk()`], and re-keys right away the
+    /// branches pending a re-key, including the ones whose latest keyload included the [`Psk`]
+    /// (see [`User::rekey()`]).
+    ///
+    /// # Arguments
+    /// * `pskid`: The [identifier](`PskId`) of the [`Psk`]
+    ///
+    /// Returns:
+    /// The responses of the published keyloads
+    pub async fn revoke_psk(&mut self, pskid: PskId) -> Result<Vec<SendResponse<TSR>>> {
+        self.remove_psk(pskid);
+        self.rekey().await
+    }
+
+    /// Publishes a new keyload in a branch for the recipients of its latest keyload, if known. The
+    /// recipients are granted their current permission in the branch rather than the one they were
+    /// granted by the latest keyload, whose duration or message budget may have run out since.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    async fn rekey_branch(&mut self, topic: Topic) -> Result<Option<SendResponse<TSR>>> {
+        let keyload = match self.state.keyloads.get(&topic) {
+            Some(keyload) => keyload.clone(),
+            None => {
+                self.state.pending_rekeys.remove(&topic);
+                return Ok(None);
+            }
+        };
+        // Readers are not tracked by the cursor store, and expired permissions are not granted again
+        let now = self.clock.now();
+        let subscribers: Vec<Permissioned<Identifier>> = keyload
+            .subscribers
+            .iter()
+            .map(|subscriber| {
+                let id = subscriber.identifier();
+                match self.state.cursor_store.get_permission(&topic, id) {
+                    Some(permission) if !matches!(now, Some(now) if permission.is_expired(now)) => permission.clone(),
+                    _ => Permissioned::Read(id.clone()),
+                }
+            })
+            .collect();
+        // Removed PSKs cannot be included anymore
+        let psks: Vec<PskId> = keyload
+            .psks
+            .into_iter()
+            .filter(|pskid| self.state.psk_store.contains_key(pskid))
+            .collect();
+        self.send_keyload(topic, subscribers.iter().map(Permissioned::as_ref), psks)
+            .await
+            .map(Some)
+    }
+
+    /// Re-keys the branches before the [`User`] publishes a packet in a branch: the branch itself
+    /// if its key is due for rotation, and every branch pending a re-key if re-keying
+    /// automatically.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch the packet is published in
+    async fn rekey_before_packet(&mut self, topic: &Topic) -> Result<()> {
+        if self.key_rotation_due(topic) {
+            self.rekey_branch(topic.clone()).await?;
+        }
+        if self.state.auto_rekey {
+            self.rekey().await?;
+        }
+        Ok(())
+    }
+
     /// Create a new [`MessageBuilder`] instance.
     pub fn message<P: Default>(&mut self) -> MessageBuilder<P, T> {
         MessageBuilder::new(self)
@@ -1351,20 +2299,56 @@ where
         P: AsRef<[u8]>,
         Top: Into<Topic>,
     {
+        self.send_encoded_signed_packet(
+            topic.into(),
+            public_payload.as_ref(),
+            masked_payload.as_ref(),
+            &[],
+            codec::RAW,
+        )
+        .await
+    }
+
+    /// Create and send a new Signed Packet message to the specified branch, carrying attachments
+    /// along with its payloads and recording in its header the encoding of its payloads: the
+    /// [`Codec`](crate::Codec) they are encoded with and the [`Compression`](crate::Compression)
+    /// they are compressed with.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch to send the message to.
+    /// * `public_payload`: The unmasked payload of the message.
+    /// * `masked_payload`: The masked payload of the message.
+    /// * `attachments`: The masked attachments of the message.
+    /// * `encoding`: The encoding of the payloads, identifying their codec and compression.
+    pub(crate) async fn send_encoded_signed_packet(
+        &mut self,
+        topic: Topic,
+        public_payload: &[u8],
+        masked_payload: &[u8],
+        attachments: &[Attachment],
+        encoding: u8,
+    ) -> Result<SendResponse<TSR>> {
         // Check conditions
         let stream_address = self.stream_address().ok_or(Error::Setup(
             "before sending a signed packet, the stream must be created",
         ))?;
+        self.check_writable("send a signed packet")?;
+        if !attachments.is_empty() {
+            self.check_version_supports("attachments")?;
+        }
+        self.rekey_before_packet(&topic).await?;
+        let key_sequence = self.state.cursor_store.packets_under_key(&topic);
+        self.check_key_limit(&topic, key_sequence)?;
+        self.expire_permission(&topic);
         let user_id = self.identity().ok_or(Error::NoIdentity("send signed packet"))?;
         let identifier = user_id.identifier().clone();
-        // Check Topic
-        let topic = topic.into();
         // Check Permission
         let permission = self
             .state
             .cursor_store
             .get_permission(&topic, &identifier)
-            .ok_or(Error::NoCursor(topic.clone()))?;
+            .ok_or(Error::NoCursor(topic.clone()))?
+            .clone();
         if permission.is_readonly() {
             return Err(Error::WrongRole(
                 "ReadWrite",
@@ -1392,33 +2376,37 @@ where
         let content = PCF::new_final_frame().with_content(signed_packet::Wrap::new(
             &mut linked_msg_spongos,
             &(*user_id),
-            public_payload.as_ref(),
-            masked_payload.as_ref(),
+            public_payload,
+            masked_payload,
+            attachments,
+            key_sequence,
+            self.write_version(),
         ));
-        let header = HDF::new(message_types::SIGNED_PACKET, new_cursor, identifier.clone(), &topic)
-            .with_linked_msg_address(link_to);
+        let header = self
+            .message_header(message_types::SIGNED_PACKET, new_cursor, identifier.clone(), &topic)
+            .with_linked_msg_address(link_to)
+            .with_encoding(encoding);
 
         // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
+        let (frames, spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
             .await
             .map_err(|e| Error::Wrapped("send signed packet", e))?;
 
         // Attempt to send message
         let message_address = Address::new(stream_address.base(), rel_address);
-        if !self.transport.recv_message(message_address).await.is_err() {
+        if self.is_address_used(message_address).await? {
             return Err(Error::AddressUsed("signed packet", message_address));
         }
         let send_response = self
-            .transport
-            .send_message(message_address, transport_msg)
+            .send_frames(message_address, frames)
             .await
             .map_err(|e| Error::Transport(stream_address, "send signed packet", e))?;
 
         // If message has been sent successfully, commit message to stores
-        self.state
-            .cursor_store
-            .insert_cursor(&topic, permission.clone(), new_cursor);
+        self.state.cursor_store.insert_cursor(&topic, permission, new_cursor);
+        self.state.cursor_store.spend_message_budgets(&topic, &identifier);
+        self.record_key_usage(&topic, key_sequence);
         self.store_spongos(rel_address, spongos, link_to);
         // Update Branch Links
         self.set_latest_link(topic, message_address.relative());
@@ -1443,20 +2431,56 @@ where
         P: AsRef<[u8]>,
         Top: Into<Topic>,
     {
+        self.send_encoded_tagged_packet(
+            topic.into(),
+            public_payload.as_ref(),
+            masked_payload.as_ref(),
+            &[],
+            codec::RAW,
+        )
+        .await
+    }
+
+    /// Create and send a new Tagged Packet message to the specified branch, carrying attachments
+    /// along with its payloads and recording in its header the encoding of its payloads: the
+    /// [`Codec`](crate::Codec) they are encoded with and the [`Compression`](crate::Compression)
+    /// they are compressed with.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch to send the message to.
+    /// * `public_payload`: The unmasked payload of the message.
+    /// * `masked_payload`: The masked payload of the message.
+    /// * `attachments`: The masked attachments of the message.
+    /// * `encoding`: The encoding of the payloads, identifying their codec and compression.
+    pub(crate) async fn send_encoded_tagged_packet(
+        &mut self,
+        topic: Topic,
+        public_payload: &[u8],
+        masked_payload: &[u8],
+        attachments: &[Attachment],
+        encoding: u8,
+    ) -> Result<SendResponse<TSR>> {
         // Check conditions
         let stream_address = self.stream_address().ok_or(Error::Setup(
             "before sending a tagged packet, the stream must be created",
         ))?;
+        self.check_writable("send a tagged packet")?;
+        if !attachments.is_empty() {
+            self.check_version_supports("attachments")?;
+        }
+        self.rekey_before_packet(&topic).await?;
+        let key_sequence = self.state.cursor_store.packets_under_key(&topic);
+        self.check_key_limit(&topic, key_sequence)?;
+        self.expire_permission(&topic);
         let user_id = self.identity().ok_or(Error::NoIdentity("send tagged packet"))?;
         let identifier = user_id.identifier().clone();
-        // Check Topic
-        let topic = topic.into();
         // Check Permission
         let permission = self
             .state
             .cursor_store
             .get_permission(&topic, &identifier)
-            .ok_or(Error::NoCursor(topic.clone()))?;
+            .ok_or(Error::NoCursor(topic.clone()))?
+            .clone();
         if permission.is_readonly() {
             return Err(Error::WrongRole(
                 "ReadWrite",
@@ -1483,55 +2507,217 @@ where
             .ok_or(Error::MessageMissing(link_to, "spongos store"))?;
         let content = PCF::new_final_frame().with_content(tagged_packet::Wrap::new(
             &mut linked_msg_spongos,
-            public_payload.as_ref(),
-            masked_payload.as_ref(),
+            public_payload,
+            masked_payload,
+            attachments,
+            key_sequence,
+            self.write_version(),
         ));
-        let header = HDF::new(message_types::TAGGED_PACKET, new_cursor, identifier.clone(), &topic)
-            .with_linked_msg_address(link_to);
+        let header = self
+            .message_header(message_types::TAGGED_PACKET, new_cursor, identifier.clone(), &topic)
+            .with_linked_msg_address(link_to)
+            .with_encoding(encoding);
 
         // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
+        let (frames, spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
             .await
             .map_err(|e| Error::Wrapped("send tagged packet", e))?;
 
         // Attempt to send message
         let message_address = Address::new(stream_address.base(), rel_address);
-        if !self.transport.recv_message(message_address).await.is_err() {
+        if self.is_address_used(message_address).await? {
             return Err(Error::AddressUsed("tagged packet", message_address));
         }
         let send_response = self
-            .transport
-            .send_message(message_address, transport_msg)
+            .send_frames(message_address, frames)
             .await
             .map_err(|e| Error::Transport(stream_address, "send tagged packet", e))?;
 
         // If message has been sent successfully, commit message to stores
-        self.state
-            .cursor_store
-            .insert_cursor(&topic, permission.clone(), new_cursor);
+        self.state.cursor_store.insert_cursor(&topic, permission, new_cursor);
+        self.state.cursor_store.spend_message_budgets(&topic, &identifier);
+        self.record_key_usage(&topic, key_sequence);
         self.store_spongos(rel_address, spongos, link_to);
         // Update Branch Links
         self.set_latest_link(topic, rel_address);
         Ok(SendResponse::new(message_address, send_response))
     }
-}
-
-#[async_trait(?Send)]
-impl ContentSizeof<State> for sizeof::Context {
-    async fn sizeof(&mut self, user_state: &State) -> SpongosResult<&mut Self> {
-        self.mask(Maybe::new(user_state.user_id.as_ref()))?
-            .mask(Maybe::new(user_state.stream_address.as_ref()))?
-            .mask(Maybe::new(user_state.author_identifier.as_ref()))?
-            .mask(&user_state.base_branch)?;
 
-        let amount_spongos = user_state.spongos_store.len();
-        self.mask(Size::new(amount_spongos))?;
-        for (address, spongos) in &user_state.spongos_store {
-            self.mask(address)?.mask(spongos)?;
+    /// Create and send a message of a custom type, whose content is wrapped by the
+    /// [`MessageHandler`] registered for the type. The message is linked to the latest message of
+    /// the branch and counts towards the key limit of the branch like a packet, but it does not
+    /// become the latest message of the branch, so that readers without a handler for its type can
+    /// still read the messages that follow it.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch the message will be sent to
+    /// * `message_type`: The custom message type, with a registered handler
+    /// * `content`: The content of the message
+    pub async fn send_custom_message<Top>(
+        &mut self,
+        topic: Top,
+        message_type: u8,
+        content: &[u8],
+    ) -> Result<SendResponse<TSR>>
+    where
+        Top: Into<Topic>,
+    {
+        // Check conditions
+        let stream_address = self.stream_address().ok_or(Error::Setup(
+            "before sending a custom message, the stream must be created",
+        ))?;
+        self.check_writable("send a custom message")?;
+        self.check_version_supports("custom messages")?;
+        let handler = self
+            .message_handlers
+            .get(&message_type)
+            .cloned()
+            .ok_or(Error::MessageTypeUnknown(message_type))?;
+        let topic = topic.into();
+        self.rekey_before_packet(&topic).await?;
+        let key_sequence = self.state.cursor_store.packets_under_key(&topic);
+        self.check_key_limit(&topic, key_sequence)?;
+        self.expire_permission(&topic);
+        let user_id = self.identity().ok_or(Error::NoIdentity("send custom message"))?;
+        let identifier = user_id.identifier().clone();
+        // Check Permission
+        let permission = self
+            .state
+            .cursor_store
+            .get_permission(&topic, &identifier)
+            .ok_or(Error::NoCursor(topic.clone()))?
+            .clone();
+        if permission.is_readonly() {
+            return Err(Error::WrongRole(
+                "ReadWrite",
+                permission.identifier().clone(),
+                "send a custom message",
+            ));
         }
-
-        // Only keep topics that exist in cursor store, any others serve no purpose
+        // Link message to latest message in branch
+        let link_to = self
+            .get_latest_link(&topic)
+            .ok_or_else(|| Error::TopicNotFound(topic.clone()))?;
+
+        // Update own's cursor
+        let new_cursor = self.next_cursor(&topic)?;
+        let rel_address = MsgId::gen(stream_address.base(), &identifier, &topic, new_cursor);
+
+        // Prepare HDF and PCF
+        // Spongos must be copied because wrapping mutates it
+        let mut linked_msg_spongos = self
+            .state
+            .spongos_store
+            .get(&link_to)
+            .copied()
+            .ok_or(Error::MessageMissing(link_to, "spongos store"))?;
+        let content = PCF::new_final_frame().with_content(custom::Wrap::new(
+            &mut linked_msg_spongos,
+            handler.as_ref(),
+            content,
+            key_sequence,
+        ));
+        let header = self
+            .message_header(message_type, new_cursor, identifier.clone(), &topic)
+            .with_linked_msg_address(link_to);
+
+        // Wrap message
+        let (frames, spongos) = LetsMessage::new(header, content)
+            .wrap_frames(self.max_frame_size)
+            .await
+            .map_err(|e| Error::Wrapped("send custom message", e))?;
+
+        // Attempt to send message
+        let message_address = Address::new(stream_address.base(), rel_address);
+        if self.is_address_used(message_address).await? {
+            return Err(Error::AddressUsed("custom message", message_address));
+        }
+        let send_response = self
+            .send_frames(message_address, frames)
+            .await
+            .map_err(|e| Error::Transport(stream_address, "send custom message", e))?;
+
+        // If message has been sent successfully, commit message to stores. The latest link of the
+        // branch is left to the previous message
+        self.state.cursor_store.insert_cursor(&topic, permission, new_cursor);
+        self.state.cursor_store.spend_message_budgets(&topic, &identifier);
+        self.record_key_usage(&topic, key_sequence);
+        self.store_spongos(rel_address, spongos, link_to);
+        Ok(SendResponse::new(message_address, send_response))
+    }
+}
+
+impl State {
+    /// Decrypts and deserialises a `State` from a backup made with [`User::backup()`]
+    ///
+    /// # Arguments
+    /// * `backup`: Encrypted binary stream of backed up `State`.
+    /// * `pwd`: The decryption password.
+    async fn from_backup(backup: &[u8], pwd: &[u8]) -> Result<Self> {
+        let mut ctx = unwrap::Context::new(backup);
+        let mut version = Uint8::default();
+        ctx.absorb(&mut version).map_err(Error::Spongos)?;
+        if version.inner() != BACKUP_VERSION {
+            return Err(Error::Spongos(SpongosError::InvalidOption(
+                "backup version",
+                version.inner(),
+            )));
+        }
+        let key: [u8; 32] = SpongosRng::<KeccakF1600>::new(pwd).gen();
+        ctx.absorb(External::new(&NBytes::new(key)))
+            .map_err(Error::Spongos)?
+            .commit()
+            .map_err(Error::Spongos)?
+            .squeeze(&Mac::new(32))
+            .map_err(Error::Spongos)?;
+        let mut state = State::default();
+        ctx.unwrap(&mut state).await.map_err(Error::Spongos)?;
+        Ok(state)
+    }
+
+    /// Decrypts and deserialises a `State` from a backup made before the backup format was
+    /// versioned, which carries neither a version nor the state introduced since.
+    ///
+    /// # Arguments
+    /// * `backup`: Encrypted binary stream of backed up `State`.
+    /// * `pwd`: The decryption password.
+    async fn from_unversioned_backup(backup: &[u8], pwd: &[u8]) -> Result<Self> {
+        let mut ctx = unwrap::Context::new(backup);
+        let key: [u8; 32] = SpongosRng::<KeccakF1600>::new(pwd).gen();
+        ctx.absorb(External::new(&NBytes::new(key)))
+            .map_err(Error::Spongos)?
+            .commit()
+            .map_err(Error::Spongos)?
+            .squeeze(&Mac::new(32))
+            .map_err(Error::Spongos)?;
+        let mut state = UnversionedState::default();
+        ctx.unwrap(&mut state).await.map_err(Error::Spongos)?;
+        Ok(state.0)
+    }
+}
+
+/// `State` backed up before the backup format was versioned
+#[derive(Default)]
+struct UnversionedState(State);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl ContentSizeof<State> for sizeof::Context {
+    async fn sizeof(&mut self, user_state: &State) -> SpongosResult<&mut Self> {
+        self.mask(Maybe::new(user_state.user_id.as_ref()))?
+            .mask(Maybe::new(user_state.stream_address.as_ref()))?
+            .mask(Maybe::new(user_state.stream_version.map(Uint8::new)))?
+            .mask(Maybe::new(user_state.author_identifier.as_ref()))?
+            .mask(&user_state.base_branch)?;
+
+        let amount_spongos = user_state.spongos_store.len();
+        self.mask(Size::new(amount_spongos))?;
+        for (address, spongos) in &user_state.spongos_store {
+            self.mask(address)?.mask(spongos)?;
+        }
+
+        // Only keep topics that exist in cursor store, any others serve no purpose
         let topics = user_state
             .topics
             .iter()
@@ -1550,6 +2736,8 @@ impl ContentSizeof<State> for sizeof::Context {
                     "No Cursor".to_owned(),
                 ))?;
             self.mask(&latest_link)?;
+            self.mask(Maybe::new(user_state.cursor_store.key_limit(topic).map(Size::new)))?
+                .mask(Size::new(user_state.cursor_store.packets_under_key(topic)))?;
 
             let cursors: Vec<(&Permissioned<Identifier>, &usize)> = user_state
                 .cursor_store
@@ -1574,6 +2762,48 @@ impl ContentSizeof<State> for sizeof::Context {
             self.mask(subscriber)?;
         }
 
+        let pending_subs = &user_state.pending_subscriptions;
+        self.mask(Size::new(pending_subs.len()))?;
+        for subscriber in pending_subs {
+            self.mask(subscriber)?;
+        }
+
+        let keyloads = &user_state.keyloads;
+        self.mask(Size::new(keyloads.len()))?;
+        for (topic, keyload) in keyloads {
+            self.mask(topic)?.mask(Size::new(keyload.subscribers.len()))?;
+            for subscriber in &keyload.subscribers {
+                self.mask(subscriber)?;
+            }
+            self.mask(Size::new(keyload.psks.len()))?;
+            for pskid in &keyload.psks {
+                self.mask(pskid)?;
+            }
+        }
+
+        let pending_rekeys = &user_state.pending_rekeys;
+        self.mask(Size::new(pending_rekeys.len()))?;
+        for topic in pending_rekeys {
+            self.mask(topic)?;
+        }
+
+        let key_rotations = &user_state.key_rotations;
+        self.mask(Size::new(key_rotations.len()))?;
+        for (topic, schedule) in key_rotations {
+            let (rotation, messages, since, requested) = schedule.parts();
+            let (kind, value) = match rotation {
+                KeyRotation::Messages(messages) => (0, messages),
+                KeyRotation::Interval(interval) => (1, interval.as_secs() as usize),
+                KeyRotation::OnDemand => (2, 0),
+            };
+            self.mask(topic)?
+                .mask(Uint8::new(kind))?
+                .mask(Size::new(value))?
+                .mask(Size::new(messages))?
+                .mask(Maybe::new(since.map(Uint64::new)))?
+                .mask(Uint8::new(requested as u8))?;
+        }
+
         let psks = user_state.psk_store.iter();
         let amount_psks = psks.len();
         self.mask(Size::new(amount_psks))?;
@@ -1583,16 +2813,20 @@ impl ContentSizeof<State> for sizeof::Context {
 
         let lean = if user_state.lean { 1 } else { 0 };
         self.mask(Uint8::new(lean))?;
+        let auto_rekey = if user_state.auto_rekey { 1 } else { 0 };
+        self.mask(Uint8::new(auto_rekey))?;
 
         self.commit()?.squeeze(Mac::new(32))
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a> ContentWrap<State> for wrap::Context<&'a mut [u8]> {
     async fn wrap(&mut self, user_state: &mut State) -> SpongosResult<&mut Self> {
         self.mask(Maybe::new(user_state.user_id.as_ref()))?
             .mask(Maybe::new(user_state.stream_address.as_ref()))?
+            .mask(Maybe::new(user_state.stream_version.map(Uint8::new)))?
             .mask(Maybe::new(user_state.author_identifier.as_ref()))?
             .mask(&user_state.base_branch)?;
 
@@ -1621,6 +2855,8 @@ impl<'a> ContentWrap<State> for wrap::Context<&'a mut [u8]> {
                     "No latest link".to_owned(),
                 ))?;
             self.mask(&latest_link)?;
+            self.mask(Maybe::new(user_state.cursor_store.key_limit(topic).map(Size::new)))?
+                .mask(Size::new(user_state.cursor_store.packets_under_key(topic)))?;
 
             let cursors: Vec<(&Permissioned<Identifier>, &usize)> = user_state
                 .cursor_store
@@ -1645,6 +2881,48 @@ impl<'a> ContentWrap<State> for wrap::Context<&'a mut [u8]> {
             self.mask(subscriber)?;
         }
 
+        let pending_subs = &user_state.pending_subscriptions;
+        self.mask(Size::new(pending_subs.len()))?;
+        for subscriber in pending_subs {
+            self.mask(subscriber)?;
+        }
+
+        let keyloads = &user_state.keyloads;
+        self.mask(Size::new(keyloads.len()))?;
+        for (topic, keyload) in keyloads {
+            self.mask(topic)?.mask(Size::new(keyload.subscribers.len()))?;
+            for subscriber in &keyload.subscribers {
+                self.mask(subscriber)?;
+            }
+            self.mask(Size::new(keyload.psks.len()))?;
+            for pskid in &keyload.psks {
+                self.mask(pskid)?;
+            }
+        }
+
+        let pending_rekeys = &user_state.pending_rekeys;
+        self.mask(Size::new(pending_rekeys.len()))?;
+        for topic in pending_rekeys {
+            self.mask(topic)?;
+        }
+
+        let key_rotations = &user_state.key_rotations;
+        self.mask(Size::new(key_rotations.len()))?;
+        for (topic, schedule) in key_rotations {
+            let (rotation, messages, since, requested) = schedule.parts();
+            let (kind, value) = match rotation {
+                KeyRotation::Messages(messages) => (0, messages),
+                KeyRotation::Interval(interval) => (1, interval.as_secs() as usize),
+                KeyRotation::OnDemand => (2, 0),
+            };
+            self.mask(topic)?
+                .mask(Uint8::new(kind))?
+                .mask(Size::new(value))?
+                .mask(Size::new(messages))?
+                .mask(Maybe::new(since.map(Uint64::new)))?
+                .mask(Uint8::new(requested as u8))?;
+        }
+
         let psks = user_state.psk_store.iter();
         let amount_psks = psks.len();
         self.mask(Size::new(amount_psks))?;
@@ -1654,18 +2932,24 @@ impl<'a> ContentWrap<State> for wrap::Context<&'a mut [u8]> {
 
         let lean = if user_state.lean { 1 } else { 0 };
         self.mask(Uint8::new(lean))?;
+        let auto_rekey = if user_state.auto_rekey { 1 } else { 0 };
+        self.mask(Uint8::new(auto_rekey))?;
 
         self.commit()?.squeeze(Mac::new(32))
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a> ContentUnwrap<State> for unwrap::Context<&'a [u8]> {
     async fn unwrap(&mut self, user_state: &mut State) -> SpongosResult<&mut Self> {
+        let mut stream_version: Option<Uint8> = None;
         self.mask(Maybe::new(&mut user_state.user_id))?
             .mask(Maybe::new(&mut user_state.stream_address))?
+            .mask(Maybe::new(&mut stream_version))?
             .mask(Maybe::new(&mut user_state.author_identifier))?
             .mask(&mut user_state.base_branch)?;
+        user_state.stream_version = stream_version.map(|version| version.inner());
 
         let mut amount_spongos = Size::default();
         self.mask(&mut amount_spongos)?;
@@ -1679,6 +2963,156 @@ impl<'a> ContentUnwrap<State> for unwrap::Context<&'a [u8]> {
         let mut amount_topics = Size::default();
         self.mask(&mut amount_topics)?;
 
+        for _ in 0..amount_topics.inner() {
+            let mut topic = Topic::default();
+            self.mask(&mut topic)?;
+            let mut latest_link = MsgId::default();
+            self.mask(&mut latest_link)?;
+
+            let mut key_limit: Option<Size> = None;
+            let mut packets_under_key = Size::default();
+            self.mask(Maybe::new(&mut key_limit))?.mask(&mut packets_under_key)?;
+
+            user_state.topics.insert(topic.clone());
+            user_state.cursor_store.set_latest_link(topic.clone(), latest_link);
+            user_state
+                .cursor_store
+                .set_key_limit(&topic, key_limit.map(|key_limit| key_limit.inner()));
+            user_state
+                .cursor_store
+                .set_packets_under_key(&topic, packets_under_key.inner());
+
+            let mut amount_cursors = Size::default();
+            self.mask(&mut amount_cursors)?;
+            for _ in 0..amount_cursors.inner() {
+                let mut subscriber = Permissioned::default();
+                let mut cursor = Size::default();
+                self.mask(&mut subscriber)?.mask(&mut cursor)?;
+                user_state
+                    .cursor_store
+                    .insert_cursor(&topic, subscriber, cursor.inner());
+            }
+        }
+
+        let mut amount_subs = Size::default();
+        self.mask(&mut amount_subs)?;
+        for _ in 0..amount_subs.inner() {
+            let mut subscriber = Identifier::default();
+            self.mask(&mut subscriber)?;
+            user_state.subscribers.insert(subscriber);
+        }
+
+        let mut amount_pending_subs = Size::default();
+        self.mask(&mut amount_pending_subs)?;
+        for _ in 0..amount_pending_subs.inner() {
+            let mut subscriber = Identifier::default();
+            self.mask(&mut subscriber)?;
+            user_state.pending_subscriptions.insert(subscriber);
+        }
+
+        let mut amount_keyloads = Size::default();
+        self.mask(&mut amount_keyloads)?;
+        for _ in 0..amount_keyloads.inner() {
+            let mut topic = Topic::default();
+            let mut amount_subs = Size::default();
+            self.mask(&mut topic)?.mask(&mut amount_subs)?;
+            let mut subscribers = Vec::with_capacity(amount_subs.inner());
+            for _ in 0..amount_subs.inner() {
+                let mut subscriber = Permissioned::default();
+                self.mask(&mut subscriber)?;
+                subscribers.push(subscriber);
+            }
+            let mut amount_psks = Size::default();
+            self.mask(&mut amount_psks)?;
+            let mut psks = Vec::with_capacity(amount_psks.inner());
+            for _ in 0..amount_psks.inner() {
+                let mut pskid = PskId::default();
+                self.mask(&mut pskid)?;
+                psks.push(pskid);
+            }
+            user_state.keyloads.insert(topic, Keyload { subscribers, psks });
+        }
+
+        let mut amount_pending_rekeys = Size::default();
+        self.mask(&mut amount_pending_rekeys)?;
+        for _ in 0..amount_pending_rekeys.inner() {
+            let mut topic = Topic::default();
+            self.mask(&mut topic)?;
+            user_state.pending_rekeys.insert(topic);
+        }
+
+        let mut amount_key_rotations = Size::default();
+        self.mask(&mut amount_key_rotations)?;
+        for _ in 0..amount_key_rotations.inner() {
+            let mut topic = Topic::default();
+            let mut kind = Uint8::default();
+            let mut value = Size::default();
+            let mut messages = Size::default();
+            let mut since: Option<Uint64> = None;
+            let mut requested = Uint8::default();
+            self.mask(&mut topic)?
+                .mask(&mut kind)?
+                .mask(&mut value)?
+                .mask(&mut messages)?
+                .mask(Maybe::new(&mut since))?
+                .mask(&mut requested)?;
+            let rotation = match kind.inner() {
+                0 => KeyRotation::Messages(value.inner()),
+                1 => KeyRotation::Interval(Duration::from_secs(value.inner() as u64)),
+                _ => KeyRotation::OnDemand,
+            };
+            let schedule = RotationSchedule::from_parts(
+                rotation,
+                messages.inner(),
+                since.map(|since| since.inner()),
+                requested.inner() == 1,
+            );
+            user_state.key_rotations.insert(topic, schedule);
+        }
+
+        let mut amount_psks = Size::default();
+        self.mask(&mut amount_psks)?;
+        for _ in 0..amount_psks.inner() {
+            let mut pskid = PskId::default();
+            let mut psk = Psk::default();
+            self.mask(&mut pskid)?.mask(&mut psk)?;
+            user_state.psk_store.insert(pskid, psk);
+        }
+
+        let mut lean = Uint8::new(0);
+        self.mask(&mut lean)?;
+        user_state.lean = lean.inner() == 1;
+
+        let mut auto_rekey = Uint8::new(0);
+        self.mask(&mut auto_rekey)?;
+        user_state.auto_rekey = auto_rekey.inner() == 1;
+
+        self.commit()?.squeeze(Mac::new(32))
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl ContentUnwrap<UnversionedState> for unwrap::Context<&[u8]> {
+    async fn unwrap(&mut self, UnversionedState(user_state): &mut UnversionedState) -> SpongosResult<&mut Self> {
+        self.mask(Maybe::new(&mut user_state.user_id))?
+            .mask(Maybe::new(&mut user_state.stream_address))?
+            .mask(Maybe::new(&mut user_state.author_identifier))?
+            .mask(&mut user_state.base_branch)?;
+        // Streams were written with version 2 of the protocol
+        user_state.stream_version = user_state.stream_address.map(|_| STREAMS_VER_2);
+
+        let mut amount_spongos = Size::default();
+        self.mask(&mut amount_spongos)?;
+        for _ in 0..amount_spongos.inner() {
+            let mut address = MsgId::default();
+            let mut spongos = Spongos::default();
+            self.mask(&mut address)?.mask(&mut spongos)?;
+            user_state.spongos_store.insert(address, spongos);
+        }
+
+        let mut amount_topics = Size::default();
+        self.mask(&mut amount_topics)?;
         for _ in 0..amount_topics.inner() {
             let mut topic = Topic::default();
             self.mask(&mut topic)?;
@@ -1761,3 +3195,688 @@ impl<T> PartialEq for User<T> {
 /// this fact is that two users with the same identity but different transport configurations are
 /// considered equal
 impl<T> Eq for User<T> {}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use lets::{id::Ed25519, message::STREAMS_VER_2, transport::Transport as _};
+    use spongos::ddml::{commands::Join, types::Bytes};
+
+    use crate::api::{
+        clock::ManualClock,
+        compression::CODEC_BITS,
+        legacy_stream,
+        message::MessageContent,
+        test_transport::{transport, Transport},
+        v1_stream,
+    };
+
+    use super::*;
+
+    async fn subscriber(seed: &str, transport: &Transport, author: &mut User<Transport>) -> Result<User<Transport>> {
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed(seed))
+            .with_transport(transport.clone())
+            .build();
+        subscriber.receive_message(author.stream_address().unwrap()).await?;
+        let subscription = subscriber.subscribe().await?;
+        author.receive_message(subscription.address()).await?;
+        Ok(subscriber)
+    }
+
+    /// Acknowledgements, whose content is masked
+    struct Acknowledgement;
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl MessageHandler for Acknowledgement {
+        async fn sizeof(&self, ctx: &mut sizeof::Context, content: &[u8]) -> SpongosResult<()> {
+            ctx.mask(Bytes::new(content))?;
+            Ok(())
+        }
+
+        async fn wrap(&self, ctx: &mut wrap::Context<&mut [u8]>, content: &[u8]) -> SpongosResult<()> {
+            ctx.mask(Bytes::new(content))?;
+            Ok(())
+        }
+
+        async fn unwrap(&self, ctx: &mut unwrap::Context<&[u8]>) -> SpongosResult<Vec<u8>> {
+            let mut content = Vec::new();
+            ctx.mask(Bytes::new(&mut content))?;
+            Ok(content)
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_messages_are_processed_by_their_registered_handler() -> Result<()> {
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let mut bystander = subscriber("bystander", &transport, &mut author).await?;
+        let mut subscriber = subscriber("subscriber", &transport, &mut author).await?;
+        let mut reader = User::builder().with_transport(transport.clone()).build();
+        reader.receive_message(announcement.address()).await?;
+        assert!(matches!(
+            author.register_message_handler(message_types::TAGGED_PACKET, Acknowledgement),
+            Err(Error::MessageTypeReserved(message_types::TAGGED_PACKET))
+        ));
+        author.register_message_handler(8, Acknowledgement)?;
+        subscriber.register_message_handler(8, Acknowledgement)?;
+        assert!(matches!(
+            author.send_custom_message("BASE_BRANCH", 9, b"ack").await,
+            Err(Error::MessageTypeUnknown(9))
+        ));
+
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+        let ack = author.send_custom_message("BASE_BRANCH", 8, b"ack").await?;
+        author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+
+        // The custom message is linked and tracked like a packet
+        let received = subscriber.fetch_next_messages().await?;
+        assert_eq!(received.len(), 3);
+        assert_eq!(received[1].address(), ack.address());
+        assert!(matches!(
+            received[1].as_custom(),
+            Some(custom) if custom.message_type == 8 && custom.content == b"ack"
+        ));
+        assert!(received[2].is_tagged_packet());
+
+        // Readers without a handler for the type cannot process the message, but still read the
+        // messages that follow it
+        assert!(matches!(
+            reader.receive_message(ack.address()).await,
+            Err(Error::MessageTypeUnknown(8))
+        ));
+        let received = bystander.fetch_next_messages().await?;
+        assert_eq!(received.len(), 2);
+        assert!(received[0].is_keyload());
+        assert!(received[1].is_tagged_packet());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn packets_that_cannot_be_decompressed_are_returned_with_the_error() -> Result<()> {
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        author.create_stream("BASE_BRANCH").await?;
+        let mut subscriber = subscriber("subscriber", &transport, &mut author).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+        // The compression recorded in the encoding of the packet is unknown
+        let unknown_compression = 3 << CODEC_BITS;
+        author
+            .send_encoded_tagged_packet("BASE_BRANCH".into(), b"public", b"masked", &[], unknown_compression)
+            .await?;
+        author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+
+        let received = subscriber.fetch_next_messages().await?;
+        assert_eq!(received.len(), 3);
+        assert!(received[1].decompression_error().is_some());
+        assert_eq!(received[1].masked_payload(), Some(&b"masked"[..]));
+        // The message is still linked, so the packets that follow it can be read
+        assert!(received[2].is_tagged_packet());
+        assert!(received[2].decompression_error().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn messages_are_timestamped_by_the_clock_of_their_publisher() -> Result<()> {
+        let transport = transport();
+        let clock = ManualClock::new(1_000);
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .with_clock(clock.clone())
+            .build();
+        author.create_stream("BASE_BRANCH").await?;
+        let mut subscriber = subscriber("subscriber", &transport, &mut author).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+        author.send_tagged_packet("BASE_BRANCH", b"early", b"").await?;
+        clock.advance(Duration::from_secs(3_600));
+        author.send_tagged_packet("BASE_BRANCH", b"late", b"").await?;
+
+        let received = subscriber
+            .fetch_next_messages_matching(&Selector::Time(2_000..5_000))
+            .await?;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].public_payload(), Some(&b"late"[..]));
+        assert_eq!(received[0].timestamp(), Some(4_600));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn permissions_expire_at_the_timestamp_of_messages() -> Result<()> {
+        let transport = transport();
+        let author_clock = ManualClock::new(1_000);
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .with_clock(author_clock.clone())
+            .build();
+        author.create_stream("BASE_BRANCH").await?;
+        let publisher_clock = ManualClock::new(1_000);
+        let mut publisher = User::builder()
+            .with_identity(Ed25519::from_seed("publisher"))
+            .with_transport(transport.clone())
+            .with_clock(publisher_clock.clone())
+            .build();
+        publisher.receive_message(author.stream_address().unwrap()).await?;
+        let subscription = publisher.subscribe().await?;
+        author.receive_message(subscription.address()).await?;
+        let publisher_id = publisher.identifier().unwrap().clone();
+        let permission = Permissioned::ReadWrite(&publisher_id, PermissionDuration::Unix(2_000));
+        author.send_keyload("BASE_BRANCH", [permission], []).await?;
+        publisher.sync().await?;
+        publisher.send_tagged_packet("BASE_BRANCH", b"in time", b"").await?;
+
+        // The packet is read once the permission has expired, but was published before
+        author_clock.set(5_000);
+        let received = author.fetch_next_messages().await?;
+        assert_eq!(received.last().unwrap().public_payload(), Some(&b"in time"[..]));
+
+        // Publishers cannot send past the expiry of their permission according to their clock
+        publisher_clock.set(2_000);
+        assert!(matches!(
+            publisher.send_tagged_packet("BASE_BRANCH", b"late", b"").await,
+            Err(Error::WrongRole("ReadWrite", _, "send a tagged packet"))
+        ));
+        // Nor are packets timestamped past the expiry accepted by readers
+        let topic = Topic::from("BASE_BRANCH");
+        let cursor = publisher.state.cursor_store.get_cursor(&topic, &publisher_id).unwrap();
+        publisher.state.cursor_store.insert_cursor(
+            &topic,
+            Permissioned::ReadWrite(publisher_id.clone(), PermissionDuration::Perpetual),
+            cursor,
+        );
+        let late = publisher.send_tagged_packet("BASE_BRANCH", b"late", b"").await?;
+        assert!(matches!(
+            author.receive_message(late.address()).await,
+            Err(Error::WrongRole("ReadWrite", _, "publish a tagged packet"))
+        ));
+        // Nor packets without a timestamp
+        publisher.set_clock(UnknownTime);
+        let untimestamped = publisher.send_tagged_packet("BASE_BRANCH", b"untimestamped", b"").await?;
+        assert!(matches!(
+            author.receive_message(untimestamped.address()).await,
+            Err(Error::WrongRole("ReadWrite", _, "publish a tagged packet"))
+        ));
+
+        // Nor packets backdated before the expiry once a keyload is published past it, even if the
+        // keyload carries the expired permission over
+        author.send_keyload("BASE_BRANCH", [permission], []).await?;
+        publisher.set_clock(publisher_clock.clone());
+        publisher_clock.set(1_500);
+        publisher.sync().await?;
+        assert_eq!(
+            publisher.permission(&topic),
+            Some(&Permissioned::Read(publisher_id.clone()))
+        );
+        let cursor = publisher.state.cursor_store.get_cursor(&topic, &publisher_id).unwrap();
+        publisher.state.cursor_store.insert_cursor(
+            &topic,
+            Permissioned::ReadWrite(publisher_id.clone(), PermissionDuration::Perpetual),
+            cursor,
+        );
+        let backdated = publisher.send_tagged_packet("BASE_BRANCH", b"backdated", b"").await?;
+        assert!(matches!(
+            author.receive_message(backdated.address()).await,
+            Err(Error::WrongRole("ReadWrite", _, "publish a tagged packet"))
+        ));
+        Ok(())
+    }
+
+    /// [`Clock`] of a user that does not know the time
+    struct UnknownTime;
+
+    impl Clock for UnknownTime {
+        fn now(&self) -> Option<u64> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_of_the_previous_version_remain_writable() -> Result<()> {
+        let mut transport = transport();
+        let announcement = legacy_stream::publish(&mut transport).await?;
+        let mut reader = User::builder()
+            .with_identity(Ed25519::from_seed(legacy_stream::SUBSCRIBER_SEED))
+            .with_transport(transport.clone())
+            .build();
+
+        let received_announcement = reader.receive_message(announcement).await?;
+        assert_eq!(received_announcement.version(), STREAMS_VER_2);
+        assert_eq!(reader.stream_version(), Some(STREAMS_VER_2));
+
+        let received = reader.fetch_next_messages().await?;
+        assert_eq!(received.len(), 5);
+        assert!(received.iter().all(|message| message.version() == STREAMS_VER_2));
+        assert!(received.iter().all(|message| message.timestamp().is_none()));
+        assert!(received[0].is_keyload());
+        assert_eq!(received[1].public_payload(), Some(&b"archived public"[..]));
+        assert_eq!(received[1].masked_payload(), Some(&b"archived masked"[..]));
+        assert!(received[1].attachments().unwrap().is_empty());
+        let branch_announcement = received[2].as_branch_announcement().unwrap();
+        assert_eq!(branch_announcement.topic, Topic::from("ARCHIVE"));
+        assert_eq!(branch_announcement.max_packets_per_keyload, None);
+        assert!(received[3].is_keyload());
+        assert_eq!(received[4].public_payload(), Some(&b"archived tagged"[..]));
+        assert_eq!(received[4].masked_payload(), Some(&b"archived secret"[..]));
+        assert!(received[4].attachments().unwrap().is_empty());
+
+        // The stream is still published in, with messages of its version
+        let subscription = reader.subscribe().await?;
+        let backup = hex::decode(legacy_stream::AUTHOR_BACKUP)?;
+        let mut author = User::restore(&backup, legacy_stream::BACKUP_PASSWORD, transport.clone()).await?;
+        author.sync().await?;
+        author.receive_message(subscription.address()).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+        author.send_signed_packet("BASE_BRANCH", b"public", b"masked").await?;
+        let received = reader.fetch_next_messages().await?;
+        assert_eq!(received.len(), 2);
+        assert!(received.iter().all(|message| message.version() == STREAMS_VER_2));
+        assert!(received.iter().all(|message| message.timestamp().is_none()));
+        assert!(received[0].is_keyload());
+        assert_eq!(received[1].masked_payload(), Some(&b"masked"[..]));
+
+        // Features introduced since cannot be published in the stream
+        assert!(matches!(
+            author.message().with_attachment("attachment", "data").send().await,
+            Err(Error::UnsupportedByStream(STREAMS_VER_2, "attachments"))
+        ));
+        assert!(matches!(
+            author.new_branch_with_key_limit("BASE_BRANCH", "LIMITED", 1).await,
+            Err(Error::UnsupportedByStream(STREAMS_VER_2, "packet limits"))
+        ));
+
+        // Messages of unknown versions are rejected before their header is unwrapped
+        let mut future_message: Vec<u8> = transport.recv_message(announcement).await?.into();
+        future_message[1] = STREAMS_VER + 1;
+        assert!(matches!(
+            TransportMessage::new(future_message).parse_header::<KeccakF1600>().await,
+            Err(LetsError::UnsupportedVersion(version)) if version == STREAMS_VER + 1
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn streams_of_the_first_version_are_read_only() -> Result<()> {
+        let mut transport = transport();
+        let addresses = v1_stream::publish(&mut transport).await?;
+        let mut reader = User::builder()
+            .with_identity(Ed25519::from_seed(v1_stream::SUBSCRIBER_SEED))
+            .with_transport(transport.clone())
+            .build();
+        let mut received = Vec::new();
+        for address in &addresses {
+            received.push(reader.receive_message(*address).await?);
+        }
+        assert!(received.iter().all(|message| message.version() == STREAMS_VER_1));
+        assert_eq!(reader.stream_version(), Some(STREAMS_VER_1));
+        assert_eq!(
+            received[0].as_announcement().unwrap().author_identifier,
+            Identity::from(Ed25519::from_seed("v1 author")).identifier().clone()
+        );
+        let keyload = received[1].as_keyload().unwrap();
+        assert!(keyload.includes_subscriber(reader.identifier().unwrap()));
+        assert!(keyload.psks.is_empty());
+        assert_eq!(received[2].public_payload(), Some(&b"archived public"[..]));
+        assert_eq!(received[2].masked_payload(), Some(&b"archived masked"[..]));
+        assert_eq!(received[3].public_payload(), Some(&b""[..]));
+        assert_eq!(received[3].masked_payload(), Some(&b"archived tagged"[..]));
+
+        // Holders of a pre-shared key included in the keyload read the stream as well
+        let psk = Psk::from_seed(v1_stream::PSK_SEED);
+        let mut psk_reader = User::builder()
+            .with_psk(psk.to_pskid(), psk)
+            .with_transport(transport.clone())
+            .build();
+        for address in &addresses[..3] {
+            psk_reader.receive_message(*address).await?;
+        }
+        let tagged_packet = psk_reader.receive_message(addresses[3]).await?;
+        assert_eq!(tagged_packet.masked_payload(), Some(&b"archived tagged"[..]));
+
+        // The stream cannot be published in
+        assert!(matches!(
+            reader.subscribe().await,
+            Err(Error::ReadOnlyStream(STREAMS_VER_1, "subscribe"))
+        ));
+
+        // Tampered messages of the version are rejected
+        let mut tampered: Vec<u8> = transport.recv_message(addresses[2]).await?.into();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        let mut tampered_transport = transport.clone();
+        let tampered_address = Address::new(addresses[2].base(), MsgId::from([0xee; 12]));
+        tampered_transport
+            .send_message(tampered_address, TransportMessage::new(tampered))
+            .await?;
+        assert!(reader.receive_message(tampered_address).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rekeyed_branches_grant_the_current_permissions() -> Result<()> {
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        author.create_stream("BASE_BRANCH").await?;
+        let mut staying = subscriber("staying subscriber", &transport, &mut author).await?;
+        let mut leaving = subscriber("leaving subscriber", &transport, &mut author).await?;
+        let staying_id = staying.identifier().unwrap().clone();
+        let leaving_id = leaving.identifier().unwrap().clone();
+        author
+            .send_keyload(
+                "BASE_BRANCH",
+                [
+                    Permissioned::ReadWrite(&staying_id, PermissionDuration::NumPublishedmsgs(1)),
+                    Permissioned::ReadWrite(&leaving_id, PermissionDuration::Perpetual),
+                ],
+                [],
+            )
+            .await?;
+        staying.sync().await?;
+        leaving.sync().await?;
+
+        // The staying subscriber spends its message budget
+        staying.send_signed_packet("BASE_BRANCH", b"public", b"masked").await?;
+        author.sync().await?;
+        leaving.sync().await?;
+        let unsubscription = leaving.unsubscribe().await?;
+        author.receive_message(unsubscription.address()).await?;
+        assert_eq!(author.rekey().await?.len(), 1);
+
+        let received = staying.fetch_next_messages().await?;
+        let keyload = received.iter().rev().find_map(Message::as_keyload).unwrap();
+        assert!(keyload.subscribers.contains(&Permissioned::Read(staying_id)));
+        assert!(!keyload.includes_subscriber(&leaving_id));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unversioned_backups_are_migrated_on_restore() -> Result<()> {
+        let mut transport = transport();
+        let announcement = legacy_stream::publish(&mut transport).await?;
+        let backup = hex::decode(legacy_stream::AUTHOR_BACKUP)?;
+        let mut author = User::restore(&backup, legacy_stream::BACKUP_PASSWORD, transport.clone()).await?;
+        assert_eq!(
+            author.identifier(),
+            Some(Identity::from(Ed25519::from_seed("legacy author")).identifier())
+        );
+        assert_eq!(author.stream_address(), Some(announcement));
+        assert_eq!(author.stream_version(), Some(STREAMS_VER_2));
+
+        // The migrated state is backed up in the current format
+        let backup = author.backup("password").await?;
+        assert_eq!(backup[0], BACKUP_VERSION);
+        assert_eq!(User::restore(&backup, "password", transport.clone()).await?, author);
+        assert!(User::restore(&backup, "wrong password", transport).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn departed_subscribers_are_rekeyed_out_of_the_branch() -> Result<()> {
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .auto_rekey()
+            .build();
+        author.create_stream("BASE_BRANCH").await?;
+        let mut staying = subscriber("staying subscriber", &transport, &mut author).await?;
+        let mut leaving = subscriber("leaving subscriber", &transport, &mut author).await?;
+        // Unsubscribing requires write permission
+        author.send_keyload_for_all_rw("BASE_BRANCH").await?;
+        staying.sync().await?;
+        leaving.sync().await?;
+
+        // The branch is re-keyed as soon as the unsubscription is processed
+        let unsubscription = leaving.unsubscribe().await?;
+        author.receive_message(unsubscription.address()).await?;
+        assert_eq!(author.pending_rekeys().count(), 0);
+
+        // The departed subscriber cannot read the packets of the other members, even if the author
+        // publishes nothing else
+        staying.sync().await?;
+        staying.send_signed_packet("BASE_BRANCH", b"public", b"masked").await?;
+        let received = leaving.fetch_next_messages().await?;
+        let keyload = received.iter().rev().find_map(Message::as_keyload).unwrap();
+        assert!(!keyload.includes_subscriber(leaving.identifier().unwrap()));
+        assert!(!received.iter().any(|message| message.is_signed_packet()));
+
+        // Subscribers removed by hand are re-keyed out right away when revoked
+        let staying_id = staying.identifier().unwrap().clone();
+        assert_eq!(author.revoke_subscriber(&staying_id).await?.len(), 1);
+        assert_eq!(author.pending_rekeys().count(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn branch_keys_are_rotated_before_the_packets_that_exhaust_them() -> Result<()> {
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        author.create_stream("BASE_BRANCH").await?;
+        let mut subscriber = subscriber("subscriber", &transport, &mut author).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+        author.set_key_rotation("BASE_BRANCH", Some(KeyRotation::Messages(2)));
+
+        for _ in 0..3 {
+            author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+        }
+        author.request_key_rotation("BASE_BRANCH");
+        author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+
+        let received: Vec<bool> = subscriber
+            .fetch_next_messages()
+            .await?
+            .iter()
+            .map(Message::is_keyload)
+            .collect();
+        assert_eq!(received, [true, false, false, true, false, true, false]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn packets_published_past_the_key_limit_are_rejected() -> Result<()> {
+        let transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        author.create_stream("BASE_BRANCH").await?;
+        let mut publisher = subscriber("publisher", &transport, &mut author).await?;
+        author.new_branch_with_key_limit("BASE_BRANCH", "LIMITED", 2).await?;
+        author.send_keyload_for_all_rw("LIMITED").await?;
+
+        let received = publisher.fetch_next_messages().await?;
+        assert!(matches!(
+            &received[0].content,
+            MessageContent::BranchAnnouncement(announcement) if announcement.max_packets_per_keyload == Some(2)
+        ));
+        publisher.send_signed_packet("LIMITED", b"public", b"masked").await?;
+        publisher.send_signed_packet("LIMITED", b"public", b"masked").await?;
+        assert!(matches!(
+            publisher.send_signed_packet("LIMITED", b"public", b"masked").await,
+            Err(Error::KeyExhausted(_, 2))
+        ));
+
+        // Once synchronized, the author counts the packets of the publisher under the key from the
+        // position they declare, and rotates the key before exceeding the limit
+        author.sync().await?;
+        author.send_tagged_packet("LIMITED", b"public", b"masked").await?;
+        let received: Vec<bool> = publisher
+            .fetch_next_messages()
+            .await?
+            .iter()
+            .map(Message::is_keyload)
+            .collect();
+        assert_eq!(received, [true, false]);
+
+        // Readers reject the packets past the limit of a publisher that ignores it, even if it
+        // declares fewer packets under the key than the reader has seen
+        let limited = Topic::from("LIMITED");
+        author.state.cursor_store.set_key_limit(&limited, None);
+        author.state.cursor_store.set_packets_under_key(&limited, 0);
+        let packet = author.send_tagged_packet("LIMITED", b"public", b"masked").await?;
+        publisher.receive_message(packet.address()).await?;
+        let packet = author.send_tagged_packet("LIMITED", b"public", b"masked").await?;
+        assert!(matches!(
+            publisher.receive_message(packet.address()).await,
+            Err(Error::KeyExhausted(_, 2))
+        ));
+        Ok(())
+    }
+
+    /// Transport returning the messages of an address latest first, so that frames published after
+    /// the genuine ones are tried first
+    struct LatestFirst(Transport);
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl<'a> lets::transport::Transport<'a> for LatestFirst {
+        type Msg = TransportMessage;
+        type SendResponse = TransportMessage;
+
+        async fn send_message(&mut self, address: Address, msg: TransportMessage) -> LetsResult<TransportMessage> {
+            self.0.send_message(address, msg).await
+        }
+
+        async fn recv_messages(&mut self, address: Address) -> LetsResult<Vec<TransportMessage>> {
+            let mut msgs = self.0.recv_messages(address).await?;
+            msgs.reverse();
+            Ok(msgs)
+        }
+    }
+
+    /// Continuation frame chained to the [`Spongos`] state of the previous frame, which anyone can
+    /// compute from the published frames
+    struct ForgedChunk(Spongos, Vec<u8>);
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl ContentSizeof<ForgedChunk> for sizeof::Context {
+        async fn sizeof(&mut self, chunk: &ForgedChunk) -> SpongosResult<&mut Self> {
+            self.absorb(Bytes::new(&chunk.1))?.commit()?.squeeze(&Mac::new(32))?;
+            Ok(self)
+        }
+    }
+
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
+    impl ContentWrap<ForgedChunk> for wrap::Context<&mut [u8]> {
+        async fn wrap(&mut self, chunk: &mut ForgedChunk) -> SpongosResult<&mut Self> {
+            self.join(&mut chunk.0)?
+                .absorb(Bytes::new(&chunk.1))?
+                .commit()?
+                .squeeze(&Mac::new(32))?;
+            Ok(self)
+        }
+    }
+
+    /// Publishes continuation frames chained to the first frame of a message, carrying zeroes
+    /// instead of the rest of the message
+    async fn forge_frames(transport: &mut Transport, address: Address, frame_count: u32) -> Result<()> {
+        let first_frame = transport.recv_message(address).await?;
+        let mut previous = Spongos::init();
+        previous.absorb(first_frame.as_ref());
+        previous.commit();
+        for frame_num in 2..=frame_count {
+            let pcf = match frame_num == frame_count {
+                true => PCF::new_final_frame(),
+                false => PCF::new_inter_frame(),
+            };
+            let mut pcf = pcf.with_content(ForgedChunk(previous, vec![0; 256]));
+            pcf.with_payload_frame_num(frame_num)?;
+            let mut ctx = sizeof::Context::new();
+            ctx.sizeof(&pcf).await.map_err(Error::from)?;
+            let mut buf = vec![0; ctx.finalize()];
+            let mut ctx = wrap::Context::new(&mut buf[..]);
+            ctx.wrap(&mut pcf).await.map_err(Error::from)?;
+            previous = ctx.finalize();
+            transport
+                .send_message(address.frame(frame_num), TransportMessage::new(buf))
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn messages_larger_than_a_frame_are_reassembled_by_readers() -> Result<()> {
+        let mut transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .with_max_frame_size(1024)
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let mut subscriber = subscriber("subscriber", &transport, &mut author).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+
+        // Spam published at the address of the second frame of the next packet is ignored by readers
+        let topic = Topic::from("BASE_BRANCH");
+        let base = announcement.address().base();
+        let cursor = author.next_cursor(&topic)?;
+        let packet_address = Address::new(base, MsgId::gen(base, author.identifier().unwrap(), &topic, cursor));
+        transport
+            .send_message(packet_address.frame(2), TransportMessage::new(vec![0; 512]))
+            .await?;
+
+        let firmware: Vec<u8> = (0..5000).map(|i| i as u8).collect();
+        let packet = author.send_signed_packet("BASE_BRANCH", b"firmware", &firmware).await?;
+        assert_eq!(packet.address(), packet_address);
+        let first_frame = transport.recv_message(packet.address()).await?;
+        assert!(first_frame.as_ref().len() <= 1024);
+        let first_frame: PreparsedMessage = first_frame.parse_header().await?;
+        assert!(first_frame.header().payload_frame_count() > 1);
+
+        let received = subscriber.fetch_next_messages().await?;
+        assert!(matches!(
+            &received.last().unwrap().content,
+            MessageContent::SignedPacket(packet) if packet.masked_payload == firmware
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn forged_frames_chained_to_a_message_do_not_hide_it_from_readers() -> Result<()> {
+        let mut transport = transport();
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .with_max_frame_size(1024)
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_transport(LatestFirst(transport.clone()))
+            .build();
+        subscriber.receive_message(announcement.address()).await?;
+        let subscription = subscriber.subscribe().await?;
+        author.receive_message(subscription.address()).await?;
+        author.send_keyload_for_all("BASE_BRANCH").await?;
+
+        let firmware: Vec<u8> = (0..3000).map(|i| i as u8).collect();
+        let packet = author.send_signed_packet("BASE_BRANCH", b"firmware", &firmware).await?;
+        let first_frame: PreparsedMessage = transport.recv_message(packet.address()).await?.parse_header().await?;
+        let frame_count = first_frame.header().payload_frame_count();
+        assert!(frame_count > 2);
+        forge_frames(&mut transport, packet.address(), frame_count).await?;
+
+        let received = subscriber.fetch_next_messages().await?;
+        assert!(matches!(
+            &received.last().unwrap().content,
+            MessageContent::SignedPacket(packet) if packet.masked_payload == firmware
+        ));
+        Ok(())
+    }
+}
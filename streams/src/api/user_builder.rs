@@ -1,5 +1,5 @@
 // Rust
-use alloc::vec::Vec;
+use alloc::{sync::Arc, vec::Vec};
 
 // IOTA
 
@@ -7,7 +7,8 @@ use alloc::vec::Vec;
 use lets::{
     address::Address,
     id::{Identity, Psk, PskId},
-    message::TransportMessage,
+    message::{TransportMessage, DEFAULT_FRAME_SIZE},
+    sync::MaybeSend,
     transport::Transport,
 };
 
@@ -15,7 +16,14 @@ use lets::{
 use lets::transport::utangle;
 
 // Local
-use crate::{api::user::User, Result};
+use crate::{
+    api::{
+        clock::{Clock, SystemClock},
+        subscription_policy::SubscriptionPolicy,
+        user::User,
+    },
+    Result,
+};
 
 /// Builder instance for a Streams [`User`].
 pub struct UserBuilder<T> {
@@ -27,6 +35,14 @@ pub struct UserBuilder<T> {
     psks: Vec<(PskId, Psk)>,
     /// Spongos Storage Type.
     lean: bool,
+    /// Policy deciding on the processed subscriptions.
+    subscription_policy: SubscriptionPolicy,
+    /// Automatic re-keying of the branches when a member leaves.
+    auto_rekey: bool,
+    /// Maximum size of the frames the sent messages are split into.
+    max_frame_size: usize,
+    /// Clock timestamping the sent messages.
+    clock: Arc<dyn Clock>,
 }
 
 impl Default for UserBuilder<()> {
@@ -36,6 +52,10 @@ impl Default for UserBuilder<()> {
             transport: (),
             psks: Default::default(),
             lean: false,
+            subscription_policy: Default::default(),
+            auto_rekey: false,
+            max_frame_size: DEFAULT_FRAME_SIZE,
+            clock: Arc::new(SystemClock),
         }
     }
 }
@@ -66,6 +86,14 @@ impl<T> UserBuilder<T> {
         self
     }
 
+    /// Set the User Builder automatic re-keying to true: when a subscriber unsubscribes, a new
+    /// keyload excluding them is published on the affected branches right away. When a subscriber
+    /// or a PSK is removed, it is published before the next message
+    pub fn auto_rekey(mut self) -> Self {
+        self.auto_rekey = true;
+        self
+    }
+
     /// Inject [`Transport`] Client instance into the User Builder
     ///
     /// # Arguments
@@ -79,6 +107,10 @@ impl<T> UserBuilder<T> {
             id: self.id,
             psks: self.psks,
             lean: self.lean,
+            subscription_policy: self.subscription_policy,
+            auto_rekey: self.auto_rekey,
+            max_frame_size: self.max_frame_size,
+            clock: self.clock,
         }
     }
 
@@ -109,6 +141,40 @@ impl<T> UserBuilder<T> {
         self.psks.push((pskid, psk));
         self
     }
+
+    /// Inject the [`SubscriptionPolicy`] deciding whether the subscriptions processed by the
+    /// [`User`] are approved, rejected or left pending. Defaults to approving every subscription.
+    ///
+    /// # Arguments
+    /// * `subscription_policy` - Policy deciding on the processed subscriptions
+    pub fn with_subscription_policy(mut self, subscription_policy: SubscriptionPolicy) -> Self {
+        self.subscription_policy = subscription_policy;
+        self
+    }
+
+    /// Inject the maximum size of the frames the messages sent by the [`User`] are split into.
+    /// Messages larger than a frame are published as several frames, and reassembled by their
+    /// readers. Defaults to [`DEFAULT_FRAME_SIZE`], fitting in a Tangle block.
+    ///
+    /// # Arguments
+    /// * `max_frame_size` - Maximum size of a frame, in bytes
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Inject the [`Clock`] timestamping the messages sent by the [`User`], and against which
+    /// time-bounded permissions and key rotations are evaluated. Defaults to the [`SystemClock`].
+    ///
+    /// # Arguments
+    /// * `clock` - Source of the current time
+    pub fn with_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
 }
 
 impl<T> UserBuilder<T> {
@@ -140,7 +206,16 @@ impl<T> UserBuilder<T> {
         T: IntoTransport<Trans>,
         Trans: for<'a> Transport<'a>,
     {
-        User::new(self.id, self.psks, self.transport.into(), self.lean)
+        User::new(
+            self.id,
+            self.psks,
+            self.transport.into(),
+            self.lean,
+            self.subscription_policy,
+            self.auto_rekey,
+            self.max_frame_size,
+            self.clock,
+        )
     }
 
     /// Recover a user instance from the builder parameters.
@@ -191,10 +266,11 @@ impl<T> UserBuilder<T> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn recover<Trans>(self, announcement: Address) -> Result<User<Trans>>
+    pub async fn recover<Trans, TSR>(self, announcement: Address) -> Result<User<Trans>>
     where
         T: IntoTransport<Trans>,
-        Trans: for<'a> Transport<'a, Msg = TransportMessage>,
+        Trans: for<'a> Transport<'a, Msg = TransportMessage, SendResponse = TSR> + MaybeSend,
+        TSR: MaybeSend,
     {
         let mut user = self.build();
         user.receive_message(announcement).await?;
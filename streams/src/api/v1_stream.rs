@@ -0,0 +1,85 @@
+//! Stream of Streams version 1, the first generation of the protocol, encoded following the
+//! Channels protocol of the Streams 1.0 specification. The author, seeded with `"v1 author"`,
+//! announced the stream and published a keyload sharing its key with the subscriber seeded with
+//! `"v1 subscriber"`, with another subscriber and with the holders of the pre-shared key seeded
+//! with `"v1 psk"`. It then published a signed packet and a tagged packet,
+//! each linked to the message before it.
+
+// Rust
+use alloc::vec::Vec;
+
+// 3rd-party
+use anyhow::Result;
+
+// IOTA
+
+// Streams
+use lets::{address::Address, message::TransportMessage, transport::Transport as _};
+
+// Local
+use crate::api::test_transport::Transport;
+
+/// Seed of the identity of the subscriber of the stream
+pub(crate) const SUBSCRIBER_SEED: &str = "v1 subscriber";
+
+/// Seed of the pre-shared key included in the keyload of the stream
+pub(crate) const PSK_SEED: &str = "v1 psk";
+
+/// Addresses and hexadecimal bodies of the messages of the stream, in order of publication
+const MESSAGES: &[(&str, &str)] = &[
+    // Announcement
+    (
+        "7928daa9b4c05978b8a507366f9acfc839733c57983ac9656385f6bc20df05be5840fc84b75d3c8e:36500abd72a65e4eefe522d1",
+        concat!(
+            "000100810400000100000000000000000000000000000000000000000e000001ab46b77cbe797414d77456ac4431e26a",
+            "e80c314d5749ba2bfa50b263d6690257f1ddd5072e9934fbfbbfe9c25a45124a3d8443235e2c2b4fb714c711871cf62a",
+            "00c9ff1c8d9ee7dda73549a639317589653b292d45294aaa44464d2cc42973023ad62c855dc352fb5c3d400420765a40",
+            "9064bc4665b808adab8585f1ee48857500",
+        ),
+    ),
+    // Keyload
+    (
+        "7928daa9b4c05978b8a507366f9acfc839733c57983ac9656385f6bc20df05be5840fc84b75d3c8e:2bb2e54201432e63024d5a02",
+        concat!(
+            "000110f40400000136500abd72a65e4eefe522d100000000000000010e0000016e6e6e6e6e6e6e6e6e6e6e6e6e6e6e6e",
+            "6e6e6e6e6e6e6e6e6e6e6e6e6e6e6e6e010125ede2596cf10999ce41b02548862ed9e882f5463fc0a7d1c026d219c1a6",
+            "7fc6bba8784d667fc638b303624b923ce2f0010214069f0ef9a70dc9dcf75e24c67e739028075d236b53bd90c870e8b5",
+            "05a91dd6e527d348f6b5f7842b181d79ded69d4a0d37dcbe5ac80b2b8e75bb2a1b8166c4c7ee7ad4d124f1a78c6fc440",
+            "10867b7b307de25194caa610c602e8a1b86755d293e81a81cc2af1e31d433d5306c367fc734cfada48bcb1771322fe3a",
+            "563bba3e89fde065f501c14d82f5f1c9d70346f439295e97dfd9617b37b49a14c799de7e",
+        ),
+    ),
+    // Signed packet
+    (
+        "7928daa9b4c05978b8a507366f9acfc839733c57983ac9656385f6bc20df05be5840fc84b75d3c8e:6f2ade304e7590eb233d7dad",
+        concat!(
+            "00012062040000012bb2e54201432e63024d5a0200000000000000020e000001010f6172636869766564207075626c69",
+            "63010f6172636869766564206d61736b65642b03b90cfa5ada539a13d47198ea4560f74d3aacfc74083d34951311632d",
+            "2dfddcb068305e730923bc21b8dab88c71a3014da21015cce5f18bbfa337a6b2330d",
+        ),
+    ),
+    // Tagged packet
+    (
+        "7928daa9b4c05978b8a507366f9acfc839733c57983ac9656385f6bc20df05be5840fc84b75d3c8e:a1114ea81b18ba414f0d37a1",
+        concat!(
+            "00013032040000016f2ade304e7590eb233d7dad00000000000000030e00000100010f61726368697665642074616767",
+            "65642b730f496407b1a8dce62a57df5a1b98723526e5cdf62209e0a62c0174b8e9e4",
+        ),
+    ),
+];
+
+/// Publishes the messages of the stream in the transport, returning their addresses in order of
+/// publication
+///
+/// # Arguments
+/// * `transport`: The transport to publish the messages in
+pub(crate) async fn publish(transport: &mut Transport) -> Result<Vec<Address>> {
+    let mut addresses = Vec::with_capacity(MESSAGES.len());
+    for (address, body) in MESSAGES {
+        let address: Address = address.parse()?;
+        let body: Vec<u8> = hex::decode(body)?;
+        transport.send_message(address, TransportMessage::new(body)).await?;
+        addresses.push(address);
+    }
+    Ok(addresses)
+}
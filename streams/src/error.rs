@@ -31,6 +31,29 @@ pub enum Error {
     )]
     AddressUsed(&'static str, Address),
 
+    #[error("Failed to {1} the payload with codec {0}: {2}")]
+    Codec(u8, &'static str, anyhow::Error),
+
+    #[error("Failed to {1} the payload with compression {0}: {2}")]
+    Compression(u8, &'static str, anyhow::Error),
+
+    #[error(
+        "Frame {1} of the message at address '{0}' is missing: no message chained to the previous frames was found at the frame address"
+    )]
+    FrameMissing(Address, u32),
+
+    #[error(
+        "Key exhausted. Branch '{0}' allows at most {1} packets after each keyload, a new keyload must be published before the next packet"
+    )]
+    KeyExhausted(Topic, usize),
+
+    #[error(
+        "Message type {0} is reserved. Custom message types must be in the range {start}..={end}",
+        start = crate::CUSTOM_MESSAGE_TYPES.start(),
+        end = crate::CUSTOM_MESSAGE_TYPES.end()
+    )]
+    MessageTypeReserved(u8),
+
     #[error("Unexpected message type {0}")]
     MessageTypeUnknown(u8),
 
@@ -48,6 +71,9 @@ pub enum Error {
     #[error("User does not have an identity, but needs one to {0}")]
     NoIdentity(&'static str),
 
+    #[error("The message at address '{0}' does not carry a payload")]
+    NoPayload(Address),
+
     #[error("User identity contains no secret key")]
     NoSecretKey,
 
@@ -65,6 +91,11 @@ Any {0} message must be linked to a previous message by including the address of
     #[error("A payload must be specified in order to send a message")]
     PayloadEmpty,
 
+    #[error(
+        "Stream is read-only. The stream is written with Streams version {0}, whose messages can be read but not published, cannot {1}"
+    )]
+    ReadOnlyStream(u8, &'static str),
+
     #[error("Setup error: {0}")]
     Setup(&'static str),
 
@@ -80,6 +111,17 @@ Any {0} message must be linked to a previous message by including the address of
     #[error("Topic by hash {0} is not known")]
     UnknownTopic(TopicHash),
 
+    #[error(
+        "Unsupported by the stream. The stream is written with Streams version {0}, whose messages cannot carry {1}"
+    )]
+    UnsupportedByStream(u8, &'static str),
+
+    #[error("Payload codec {0} is not supported. The codec may be disabled, or custom")]
+    UnsupportedCodec(u8),
+
+    #[error("Payload compression {0} is not supported. The compression may be disabled, or unknown")]
+    UnsupportedCompression(u8),
+
     #[error("Error unwrapping the message {0}. The message at address '{1:#?}' could not be unwrapped: {2}")]
     Unwrapping(&'static str, Address, LetsError),
 
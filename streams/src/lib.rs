@@ -39,16 +39,31 @@ extern crate alloc;
 
 /// Protocol message types and encodings
 mod message;
+pub use message::attachment::Attachment;
 
 /// [`User`] API.
 mod api;
 
+#[cfg(feature = "cbor-codec")]
+pub use api::codec::Cbor;
+#[cfg(feature = "json-codec")]
+pub use api::codec::Json;
+#[cfg(feature = "postcard-codec")]
+pub use api::codec::Postcard;
+#[cfg(feature = "follow")]
+pub use api::follow::{CancelHandle, FollowEvent, Following};
 pub use api::{
+    clock::{Clock, ManualClock, SystemClock},
+    codec::Codec,
+    compression::{Compression, MAX_DECOMPRESSED_SIZE},
+    key_rotation::KeyRotation,
     message::{Message, MessageContent},
     message_builder::MessageBuilder,
+    message_handler::{MessageHandler, CUSTOM_MESSAGE_TYPES},
     messages::Messages,
-    selector::Selector,
+    selector::{MessageType, PayloadPredicate, Selector},
     send_response::SendResponse,
+    subscription_policy::{SubscriptionDecision, SubscriptionPolicy},
     user::User,
     user_builder::UserBuilder,
 };
@@ -14,6 +14,19 @@
 //!     ed25519(hash)           sig;
 //! }
 //! ```
+//!
+//! Announcements of Streams version 1 carry the public keys of the author instead, and no topic:
+//!
+//! ```ddml
+//! message Announcement {
+//!     absorb           u8     ed25519_pubkey[32];
+//!     absorb           u8     x25519_pubkey[32];
+//!     absorb           u8     flags;
+//!     commit;
+//!     squeeze external u8     hash[64];
+//!     ed25519(hash)           sig;
+//! }
+//! ```
 
 // Rust
 use alloc::boxed::Box;
@@ -22,16 +35,22 @@ use alloc::boxed::Box;
 use async_trait::async_trait;
 
 // IOTA
+use crypto::{keys::x25519, signatures::ed25519};
 
 // Streams
 use lets::{
     id::{Identifier, Identity},
-    message::{ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap, Topic},
+    message::{
+        ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap, Topic, STREAMS_VER_1,
+    },
+    sync::MaybeSend,
 };
 use spongos::{
     ddml::{
-        commands::{sizeof, unwrap, wrap, Commit, Mask},
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Ed25519, Mask, Squeeze},
         io,
+        modifiers::External,
+        types::{NBytes, Uint8},
     },
     error::Result,
     PRP,
@@ -58,7 +77,8 @@ impl<'a> Wrap<'a> {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
     async fn sizeof(&mut self, announcement: &Wrap<'a>) -> Result<&mut Self> {
         self.mask(announcement.user_id.identifier())?
@@ -70,10 +90,11 @@ impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
 where
-    OS: io::OStream,
+    OS: io::OStream + MaybeSend,
 {
     async fn wrap(&mut self, announcement: &mut Wrap<'a>) -> Result<&mut Self> {
         self.mask(announcement.user_id.identifier())?
@@ -92,17 +113,23 @@ pub(crate) struct Unwrap {
     author_id: Identifier,
     /// The base branch [`Topic`] of the stream
     topic: Topic,
+    /// The Streams version of the message
+    version: u8,
 }
 
-impl Default for Unwrap {
-    fn default() -> Self {
-        let author_id = Default::default();
-        let topic = Default::default();
-        Self { author_id, topic }
+impl Unwrap {
+    /// Creates a new [`Unwrap`] struct for an announcement message
+    ///
+    /// # Arguments
+    /// * `version`: The Streams version of the message, as found in its header
+    pub(crate) fn new(version: u8) -> Self {
+        Self {
+            author_id: Default::default(),
+            topic: Default::default(),
+            version,
+        }
     }
-}
 
-impl Unwrap {
     /// Returns a reference to the [`Identifier`] of the author.
     pub(crate) fn author_id(&self) -> &Identifier {
         &self.author_id
@@ -117,13 +144,29 @@ impl Unwrap {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<IS, F> ContentUnwrap<Unwrap> for unwrap::Context<IS, F>
 where
-    F: PRP,
-    IS: io::IStream,
+    F: PRP + MaybeSend,
+    IS: io::IStream + MaybeSend,
 {
     async fn unwrap(&mut self, announcement: &mut Unwrap) -> Result<&mut Self> {
+        if announcement.version == STREAMS_VER_1 {
+            // The stream is announced in its default topic, and the key exchange key of the author
+            // and the flags of the stream are not needed to read it
+            let mut public_key = ed25519::PublicKey::try_from_bytes([0; ed25519::PUBLIC_KEY_LENGTH]).unwrap();
+            let mut key_exchange_key = x25519::PublicKey::from([0; x25519::PUBLIC_KEY_LENGTH]);
+            let mut hash = External::new(NBytes::new([0; 64]));
+            self.absorb(&mut public_key)?
+                .absorb(&mut key_exchange_key)?
+                .absorb(&mut Uint8::default())?
+                .commit()?
+                .squeeze(hash.as_mut())?
+                .ed25519(&public_key, hash.as_ref())?;
+            announcement.author_id = Identifier::from(public_key);
+            return Ok(self);
+        }
         self.mask(&mut announcement.author_id)?
             .mask(&mut announcement.topic)?
             .verify(&announcement.author_id)
@@ -0,0 +1,100 @@
+//! Named binary blob carried by packet messages.
+//!
+//! ```ddml
+//! type Attachment {
+//!     mask bytes name;
+//!     mask bytes data;
+//! }
+//! ```
+// Rust
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use spongos::{
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Mask},
+        io,
+        types::Bytes,
+    },
+    error::{Error as SpongosError, Result as SpongosResult},
+    PRP,
+};
+
+// Local
+
+/// A named blob of bytes sent alongside the payloads of a packet. Attachments are masked, so only
+/// the readers of the branch can access them.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Attachment {
+    /// Name of the attachment
+    name: String,
+    /// Content of the attachment
+    data: Vec<u8>,
+}
+
+impl Attachment {
+    /// Creates a new [`Attachment`]
+    ///
+    /// # Arguments
+    /// * `name`: The name of the attachment
+    /// * `data`: The content of the attachment
+    pub fn new<N, D>(name: N, data: D) -> Self
+    where
+        N: Into<String>,
+        D: Into<Vec<u8>>,
+    {
+        Self {
+            name: name.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Returns the name of the attachment
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the content of the attachment
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Mask<&Attachment> for sizeof::Context {
+    fn mask(&mut self, attachment: &Attachment) -> SpongosResult<&mut Self> {
+        self.mask(Bytes::new(&attachment.name))?
+            .mask(Bytes::new(&attachment.data))
+    }
+}
+
+impl<OS, F> Mask<&Attachment> for wrap::Context<OS, F>
+where
+    F: PRP,
+    OS: io::OStream,
+{
+    fn mask(&mut self, attachment: &Attachment) -> SpongosResult<&mut Self> {
+        self.mask(Bytes::new(&attachment.name))?
+            .mask(Bytes::new(&attachment.data))
+    }
+}
+
+impl<IS, F> Mask<&mut Attachment> for unwrap::Context<IS, F>
+where
+    F: PRP,
+    IS: io::IStream,
+{
+    fn mask(&mut self, attachment: &mut Attachment) -> SpongosResult<&mut Self> {
+        let mut name = Vec::new();
+        self.mask(Bytes::new(&mut name))?
+            .mask(Bytes::new(&mut attachment.data))?;
+        attachment.name = String::from_utf8(name).map_err(|e| SpongosError::Context("Mask", e.to_string()))?;
+        Ok(self)
+    }
+}
@@ -3,7 +3,8 @@
 //! The `BranchAnnounce` message creates a new branch in a Stream.
 //!
 //! It announces the [`Topic`] for the new branch, as well as informs of the previous branch topic
-//! the new branch is being generated from.
+//! the new branch is being generated from. It optionally declares the maximum number of packets
+//! that may be published in the new branch after each keyload.
 //!
 //! ```ddml
 //! message BranchAnnounce {
@@ -11,11 +12,15 @@
 //!     mask             u8     identifier;
 //!     mask             u8     new_topic;
 //!     mask             u8     previous_topic;
+//!     mask             u8     maybe(size(max_packets_per_keyload));
 //!     commit;
 //!     squeeze          u8     hash[64];
 //!     ed25519(hash)           sig;
 //! }
 //! ```
+//!
+//! Branch announcements of Streams version 2 declare no packet limit: their
+//! `max_packets_per_keyload` field is absent.
 
 // Rust
 use alloc::boxed::Box;
@@ -28,12 +33,16 @@ use async_trait::async_trait;
 // Streams
 use lets::{
     id::{Identifier, Identity},
-    message::{ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap, Topic},
+    message::{
+        ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap, Topic, STREAMS_VER,
+    },
+    sync::MaybeSend,
 };
 use spongos::{
     ddml::{
         commands::{sizeof, unwrap, wrap, Commit, Join, Mask},
         io,
+        types::{Maybe, Size},
     },
     error::Result,
     Spongos,
@@ -49,6 +58,10 @@ pub(crate) struct Wrap<'a> {
     user_id: &'a Identity,
     /// The new branch [`Topic`]
     new_topic: &'a Topic,
+    /// The maximum number of packets that may follow a keyload in the new branch, if limited
+    max_packets_per_keyload: Option<usize>,
+    /// The Streams version of the message
+    version: u8,
 }
 
 impl<'a> Wrap<'a> {
@@ -58,39 +71,54 @@ impl<'a> Wrap<'a> {
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
     /// * `user_id`: The [`Identity`] of the publisher
     /// * `new_topic`: the new branch [`Topic`]
-    pub(crate) fn new(initial_state: &'a mut Spongos, user_id: &'a Identity, new_topic: &'a Topic) -> Self {
+    /// * `max_packets_per_keyload`: The maximum number of packets that may follow a keyload in the
+    ///   new branch, if limited
+    /// * `version`: The Streams version of the message. Messages of
+    ///   [`lets::message::STREAMS_VER_2`] declare no packet limit.
+    pub(crate) fn new(
+        initial_state: &'a mut Spongos,
+        user_id: &'a Identity,
+        new_topic: &'a Topic,
+        max_packets_per_keyload: Option<usize>,
+        version: u8,
+    ) -> Self {
         Self {
             initial_state,
             user_id,
             new_topic,
+            max_packets_per_keyload,
+            version,
         }
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
     async fn sizeof(&mut self, announcement: &Wrap<'a>) -> Result<&mut Self> {
-        self.mask(announcement.user_id.identifier())?
-            .mask(announcement.new_topic)?
-            .sign_sizeof(announcement.user_id)
-            .await?
-            .commit()?;
+        self.mask(announcement.user_id.identifier())?.mask(announcement.new_topic)?;
+        if announcement.version == STREAMS_VER {
+            self.mask(Maybe::new(announcement.max_packets_per_keyload.map(Size::new)))?;
+        }
+        self.sign_sizeof(announcement.user_id).await?.commit()?;
         Ok(self)
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
 where
-    OS: io::OStream,
+    OS: io::OStream + MaybeSend,
 {
     async fn wrap(&mut self, announcement: &mut Wrap<'a>) -> Result<&mut Self> {
         self.join(announcement.initial_state)?
             .mask(announcement.user_id.identifier())?
-            .mask(announcement.new_topic)?
-            .sign(announcement.user_id)
-            .await?
-            .commit()?;
+            .mask(announcement.new_topic)?;
+        if announcement.version == STREAMS_VER {
+            self.mask(Maybe::new(announcement.max_packets_per_keyload.map(Size::new)))?;
+        }
+        self.sign(announcement.user_id).await?.commit()?;
         Ok(self)
     }
 }
@@ -101,6 +129,10 @@ pub(crate) struct Unwrap<'a> {
     initial_state: &'a mut Spongos,
     /// The new branch [`Topic`]
     new_topic: Topic,
+    /// The maximum number of packets that may follow a keyload in the new branch, if limited
+    max_packets_per_keyload: Option<Size>,
+    /// The Streams version of the message
+    version: u8,
 }
 
 impl<'a> Unwrap<'a> {
@@ -108,10 +140,13 @@ impl<'a> Unwrap<'a> {
     ///
     /// # Arguments
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
-    pub(crate) fn new(initial_state: &'a mut Spongos) -> Self {
+    /// * `version`: The Streams version of the message, as found in its header
+    pub(crate) fn new(initial_state: &'a mut Spongos, version: u8) -> Self {
         Self {
             initial_state,
             new_topic: Topic::default(),
+            max_packets_per_keyload: None,
+            version,
         }
     }
 
@@ -120,25 +155,33 @@ impl<'a> Unwrap<'a> {
         &self.new_topic
     }
 
+    /// Returns the maximum number of packets that may follow a keyload in the new branch, if
+    /// limited
+    pub(crate) fn max_packets_per_keyload(&self) -> Option<usize> {
+        self.max_packets_per_keyload.map(|max| max.inner())
+    }
+
     /// Consumes the [`Unwrap`], returning the new branch [`Topic`]
     pub(crate) fn into_new_topic(self) -> Topic {
         self.new_topic
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
 where
-    IS: io::IStream,
+    IS: io::IStream + MaybeSend,
 {
     async fn unwrap(&mut self, announcement: &mut Unwrap) -> Result<&mut Self> {
         let mut author_id = Identifier::default();
         self.join(announcement.initial_state)?
             .mask(&mut author_id)?
-            .mask(&mut announcement.new_topic)?
-            .verify(&author_id)
-            .await?
-            .commit()?;
+            .mask(&mut announcement.new_topic)?;
+        if announcement.version == STREAMS_VER {
+            self.mask(Maybe::new(&mut announcement.max_packets_per_keyload))?;
+        }
+        self.verify(&author_id).await?.commit()?;
         Ok(self)
     }
 }
@@ -0,0 +1,158 @@
+//! Custom message content, defined by the application.
+//!
+//! Custom messages carry the content of a message type registered with a [`MessageHandler`]. The
+//! message may be linked to any other message in the channel, and its content is wrapped by the
+//! handler between the key sequence and the MAC authenticating the message. Like in packets, the
+//! `key_sequence` counts the packets and custom messages published in the branch under its current
+//! key before this one.
+//!
+//! ```ddml
+//! message Custom {
+//!     join(spongos);
+//!     mask size key_sequence;
+//!     handler(content);
+//!     commit;
+//!     squeeze byte mac[32];
+//! }
+//! ```
+// Rust
+use alloc::{boxed::Box, vec::Vec};
+
+// 3rd-party
+use async_trait::async_trait;
+
+// IOTA
+
+// Streams
+use lets::message::{ContentSizeof, ContentUnwrap, ContentWrap};
+use spongos::{
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Commit, Join, Mask, Squeeze},
+        types::{Mac, Size},
+    },
+    error::Result,
+    Spongos,
+};
+
+// Local
+use crate::api::message_handler::MessageHandler;
+
+/// [`Mac`] for content verification
+const MAC: Mac = Mac::new(32);
+
+/// A struct that holds references needed for custom message encoding
+pub(crate) struct Wrap<'a> {
+    /// The base [`Spongos`] state that the message will be joined to
+    initial_state: &'a mut Spongos,
+    /// The [`MessageHandler`] of the message type
+    handler: &'a dyn MessageHandler,
+    /// The content of the message
+    content: &'a [u8],
+    /// Number of messages published under the key of the branch before the message
+    key_sequence: usize,
+}
+
+impl<'a> Wrap<'a> {
+    /// Creates a new [`Wrap`] struct for a custom message
+    ///
+    /// # Arguments
+    /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
+    /// * `handler`: The [`MessageHandler`] of the message type
+    /// * `content`: The content of the message
+    /// * `key_sequence`: Number of messages published under the key of the branch before this one
+    pub(crate) fn new(
+        initial_state: &'a mut Spongos,
+        handler: &'a dyn MessageHandler,
+        content: &'a [u8],
+        key_sequence: usize,
+    ) -> Self {
+        Self {
+            initial_state,
+            handler,
+            content,
+            key_sequence,
+        }
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
+    async fn sizeof(&mut self, custom: &Wrap<'a>) -> Result<&mut Self> {
+        self.mask(Size::new(custom.key_sequence))?;
+        custom.handler.sizeof(self, custom.content).await?;
+        self.commit()?.squeeze(&MAC)?;
+        Ok(self)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a> ContentWrap<Wrap<'a>> for wrap::Context<&mut [u8]> {
+    async fn wrap(&mut self, custom: &mut Wrap<'a>) -> Result<&mut Self> {
+        self.join(custom.initial_state)?.mask(Size::new(custom.key_sequence))?;
+        custom.handler.wrap(self, custom.content).await?;
+        self.commit()?.squeeze(&MAC)?;
+        Ok(self)
+    }
+}
+
+/// A struct that holds the placeholders needed for custom message decoding
+pub(crate) struct Unwrap<'a> {
+    /// The base [`Spongos`] state that the message will be joined to
+    initial_state: &'a mut Spongos,
+    /// The [`MessageHandler`] of the message type
+    handler: &'a dyn MessageHandler,
+    /// The type of the message
+    message_type: u8,
+    /// The content of the message
+    content: Vec<u8>,
+    /// Number of messages published under the key of the branch before the message
+    key_sequence: usize,
+}
+
+impl<'a> Unwrap<'a> {
+    /// Creates a new [`Unwrap`] struct for a custom message
+    ///
+    /// # Arguments
+    /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
+    /// * `handler`: The [`MessageHandler`] of the message type
+    /// * `message_type`: The type of the message, as found in its header
+    pub(crate) fn new(initial_state: &'a mut Spongos, handler: &'a dyn MessageHandler, message_type: u8) -> Self {
+        Self {
+            initial_state,
+            handler,
+            message_type,
+            content: Vec::new(),
+            key_sequence: 0,
+        }
+    }
+
+    /// Returns the type of the message
+    pub(crate) fn message_type(&self) -> u8 {
+        self.message_type
+    }
+
+    /// Returns the number of messages published under the key of the branch before the message
+    pub(crate) fn key_sequence(&self) -> usize {
+        self.key_sequence
+    }
+
+    /// Consumes the [`Unwrap`], returning the content of the message
+    pub(crate) fn into_content(self) -> Vec<u8> {
+        self.content
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'a> ContentUnwrap<Unwrap<'a>> for unwrap::Context<&[u8]> {
+    async fn unwrap(&mut self, custom: &mut Unwrap<'a>) -> Result<&mut Self> {
+        let mut key_sequence = Size::default();
+        self.join(custom.initial_state)?.mask(&mut key_sequence)?;
+        custom.key_sequence = key_sequence.inner();
+        custom.content = custom.handler.unwrap(self).await?;
+        self.commit()?.squeeze(&MAC)?;
+        Ok(self)
+    }
+}
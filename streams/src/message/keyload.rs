@@ -26,6 +26,31 @@
 //!     commit;
 //! }
 //! ```
+//!
+//! Keyloads of Streams version 1 are not signed, and are joined to the message they are linked to
+//! rather than to the announcement. Their pre shared keys precede their subscribers, who are
+//! identified by the first 16 bytes of their `x25519` public key:
+//!
+//! ```ddml
+//! message Keyload {
+//!     join(spongos);
+//!     absorb                      u8  nonce[32];
+//!     skip                        u8  size(n_psks);
+//!     repeated(n_psks):
+//!       fork;
+//!       mask                      u8  pskid[16];
+//!       absorb external           u8  psk[32];
+//!       commit;
+//!       mask                      u8  key[32];
+//!     skip                        u8  size(n_subscribers);
+//!     repeated(n_subscribers):
+//!       fork;
+//!       mask                      u8  key_id[16];
+//!       x25519(pub/priv_key)      u8  x25519_pubkey[32];
+//!     absorb external             u8  key[32];
+//!     commit;
+//! }
+//! ```
 // Rust
 use alloc::{boxed::Box, vec::Vec};
 use core::{iter::IntoIterator, marker::PhantomData};
@@ -42,11 +67,13 @@ use lets::{
     id::{Identifier, Identity, Permissioned, Psk, PskId},
     message::{
         self, ContentDecrypt, ContentEncrypt, ContentEncryptSizeOf, ContentSign, ContentSignSizeof, ContentVerify,
+        STREAMS_VER_1,
     },
+    sync::{MaybeSend, MaybeSync},
 };
 use spongos::{
     ddml::{
-        commands::{sizeof, unwrap, wrap, Absorb, Commit, Fork, Join, Mask},
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Fork, Join, Mask, Skip},
         io,
         modifiers::External,
         types::{NBytes, Size},
@@ -59,6 +86,10 @@ use spongos::{
 
 const NONCE_SIZE: usize = 16;
 const KEY_SIZE: usize = 32;
+/// Size of the nonce of keyloads of Streams version 1
+const V1_NONCE_SIZE: usize = 32;
+/// Size of the identifier of the subscribers of keyloads of Streams version 1
+const V1_KEY_ID_SIZE: usize = 16;
 
 /// A struct that holds references needed for keyload message encoding
 pub(crate) struct Wrap<'a, 'b, Subscribers, Psks> {
@@ -117,13 +148,14 @@ impl<'a, 'b, Subscribers, Psks> Wrap<'a, 'b, Subscribers, Psks> {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, 'b, Subscribers, Psks> message::ContentSizeof<Wrap<'a, 'b, Subscribers, Psks>> for sizeof::Context
 where
-    Subscribers: IntoIterator<Item = Permissioned<&'b Identifier>> + Clone,
-    Subscribers::IntoIter: ExactSizeIterator,
-    Psks: IntoIterator<Item = &'a (PskId, &'a Psk)> + Clone,
-    Psks::IntoIter: ExactSizeIterator,
+    Subscribers: IntoIterator<Item = Permissioned<&'b Identifier>> + Clone + MaybeSend + MaybeSync,
+    Subscribers::IntoIter: ExactSizeIterator + MaybeSend,
+    Psks: IntoIterator<Item = &'a (PskId, &'a Psk)> + Clone + MaybeSend + MaybeSync,
+    Psks::IntoIter: ExactSizeIterator + MaybeSend,
 {
     async fn sizeof(&mut self, keyload: &Wrap<'a, 'b, Subscribers, Psks>) -> Result<&mut sizeof::Context> {
         let subscribers = keyload.subscribers.clone().into_iter();
@@ -155,14 +187,15 @@ where
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, 'b, OS, Subscribers, Psks> message::ContentWrap<Wrap<'a, 'b, Subscribers, Psks>> for wrap::Context<OS>
 where
-    Subscribers: IntoIterator<Item = Permissioned<&'b Identifier>> + Clone,
-    Subscribers::IntoIter: ExactSizeIterator,
-    Psks: IntoIterator<Item = &'a (PskId, &'a Psk)> + Clone,
-    Psks::IntoIter: ExactSizeIterator,
-    OS: io::OStream,
+    Subscribers: IntoIterator<Item = Permissioned<&'b Identifier>> + Clone + MaybeSend + MaybeSync,
+    Subscribers::IntoIter: ExactSizeIterator + MaybeSend,
+    Psks: IntoIterator<Item = &'a (PskId, &'a Psk)> + Clone + MaybeSend + MaybeSync,
+    Psks::IntoIter: ExactSizeIterator + MaybeSend,
+    OS: io::OStream + MaybeSend,
 {
     async fn wrap(&mut self, keyload: &mut Wrap<'a, 'b, Subscribers, Psks>) -> Result<&mut Self> {
         let subscribers = keyload.subscribers.clone().into_iter();
@@ -210,6 +243,8 @@ pub(crate) struct Unwrap<'a> {
     author_id: &'a Identifier,
     /// The [`Identity`] of the reader
     user_id: Option<&'a Identity>,
+    /// The Streams version of the message
+    version: u8,
 }
 
 impl<'a> Unwrap<'a> {
@@ -219,11 +254,14 @@ impl<'a> Unwrap<'a> {
     /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
     /// * `user_id`: The optional [`Identity`] of the reading user
     /// * `author_id`: The [`Identifier`] of the author of the stream
+    /// * `version`: The Streams version of the message, as found in its header. Keyloads of
+    ///   [`STREAMS_VER_1`] only list the reading user among their subscribers.
     pub(crate) fn new(
         initial_state: &'a mut Spongos,
         user_id: Option<&'a Identity>,
         author_id: &'a Identifier,
         psk_store: &'a HashMap<PskId, Psk>,
+        version: u8,
     ) -> Self {
         Self {
             initial_state,
@@ -232,6 +270,7 @@ impl<'a> Unwrap<'a> {
             psk_store,
             author_id,
             user_id,
+            version,
         }
     }
 
@@ -241,12 +280,16 @@ impl<'a> Unwrap<'a> {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, IS> message::ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
 where
-    IS: io::IStream,
+    IS: io::IStream + MaybeSend,
 {
     async fn unwrap(&mut self, keyload: &mut Unwrap<'a>) -> Result<&mut Self> {
+        if keyload.version == STREAMS_VER_1 {
+            return unwrap_v1(self, keyload).await;
+        }
         let mut nonce = [0u8; NONCE_SIZE];
         let mut key: Option<[u8; KEY_SIZE]> = None;
         let mut n_subscribers = Size::default();
@@ -308,3 +351,76 @@ where
         Ok(self)
     }
 }
+
+/// Decodes a keyload of [`STREAMS_VER_1`], whose pre shared keys precede the subscribers. Other
+/// subscribers than the reader cannot be told from the first bytes of their public key, so they are
+/// not listed.
+///
+/// # Arguments
+/// * `ctx`: The [`unwrap::Context`] of the keyload, positioned after its header
+/// * `keyload`: The [`Unwrap`] to decode the keyload into
+async fn unwrap_v1<'a, 'b, IS>(
+    ctx: &'b mut unwrap::Context<IS>,
+    keyload: &mut Unwrap<'a>,
+) -> Result<&'b mut unwrap::Context<IS>>
+where
+    IS: io::IStream + MaybeSend,
+{
+    let mut nonce = [0u8; V1_NONCE_SIZE];
+    let mut key: Option<[u8; KEY_SIZE]> = None;
+    let mut n_psks = Size::default();
+    let mut n_subscribers = Size::default();
+    ctx.join(keyload.initial_state)?
+        .absorb(NBytes::new(&mut nonce))?
+        .skip(&mut n_psks)?;
+
+    for _ in 0..n_psks.inner() {
+        let mut fork = ctx.fork();
+        let mut psk_id = PskId::default();
+        fork.mask(&mut psk_id)?;
+
+        match keyload.psk_store.get(&psk_id) {
+            Some(psk) if key.is_none() => {
+                let mut masked_key = [0u8; KEY_SIZE];
+                fork.absorb(External::new(&NBytes::new(psk)))?
+                    .commit()?
+                    .mask(NBytes::new(&mut masked_key))?;
+                key = Some(masked_key);
+
+                keyload.psks.push(psk_id);
+            }
+            _ => {
+                fork.drop(KEY_SIZE)?;
+            }
+        }
+    }
+    ctx.skip(&mut n_subscribers)?;
+
+    let reader_key_id = keyload.user_id.and_then(|user_id| {
+        let public_key = user_id.ke_sk().ok()?.public_key().to_bytes();
+        let mut key_id = [0u8; V1_KEY_ID_SIZE];
+        key_id.copy_from_slice(&public_key[..V1_KEY_ID_SIZE]);
+        Some((user_id, key_id))
+    });
+    for _ in 0..n_subscribers.inner() {
+        let mut fork = ctx.fork();
+        let mut key_id = [0u8; V1_KEY_ID_SIZE];
+        fork.mask(NBytes::new(&mut key_id))?;
+
+        match reader_key_id {
+            Some((user_id, reader_key_id)) if key.is_none() && key_id == reader_key_id => {
+                fork.decrypt(user_id, key.get_or_insert([0u8; KEY_SIZE])).await?;
+                keyload.subscribers.push(Permissioned::Read(user_id.identifier().clone()));
+            }
+            _ => {
+                fork.drop(KEY_SIZE + x25519::PUBLIC_KEY_LENGTH)?;
+            }
+        }
+    }
+
+    if let Some(key) = key {
+        ctx.absorb(External::new(&NBytes::new(&key)))?;
+    }
+    ctx.commit()?;
+    Ok(ctx)
+}
@@ -12,3 +12,19 @@ pub(crate) const TAGGED_PACKET: u8 = 4;
 pub(crate) const SUBSCRIPTION: u8 = 5;
 /// Unsubscribe Message Type
 pub(crate) const UNSUBSCRIPTION: u8 = 6;
+
+/// Message types of [`lets::message::STREAMS_VER_1`], the first generation of the protocol
+pub(crate) mod v1 {
+    /// Announcement Message Type
+    pub(crate) const ANNOUNCEMENT: u8 = 0;
+    /// Keyload Message Type
+    pub(crate) const KEYLOAD: u8 = 1;
+    /// Signed Packet Message Type
+    pub(crate) const SIGNED_PACKET: u8 = 2;
+    /// Tagged Packet Message Type
+    pub(crate) const TAGGED_PACKET: u8 = 3;
+    /// Subscribe Message Type
+    pub(crate) const SUBSCRIPTION: u8 = 4;
+    /// Unsubscribe Message Type
+    pub(crate) const UNSUBSCRIPTION: u8 = 5;
+}
@@ -23,3 +23,9 @@ pub(crate) mod message_types;
 
 /// BranchAnnouncement message.
 pub(crate) mod branch_announcement;
+
+/// Packet attachment.
+pub(crate) mod attachment;
+
+/// Custom message.
+pub(crate) mod custom;
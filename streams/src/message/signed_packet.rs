@@ -1,6 +1,7 @@
 //! `SignedPacket` message _wrapping_ and _unwrapping_.
 //!
-//! `SignedPacket` messages contain a plain and a masked payload, signed by the sender.
+//! `SignedPacket` messages contain a plain and a masked payload, as well as masked attachments,
+//! signed by the sender.
 //!
 //! ```ddml
 //! message SignedPacket {
@@ -10,11 +11,37 @@
 //!     absorb              u8      public_payload[public_size];
 //!     mask                uint    masked_size;
 //!     mask                u8      masked_payload[masked_size];
+//!     mask                uint    attachments_count;
+//!     repeated(attachments_count):
+//!       mask              Attachment attachment;
+//!     mask                uint    key_sequence;
 //!     commit;
 //!     squeeze external    u8      hash[64];
 //!     ed25519(hash)       u8      signature[64];
 //! }
 //! ```
+//!
+//! The `key_sequence` counts the packets published in the branch under its current key before this
+//! one, so that readers can enforce the packet limit of the branch from authenticated data.
+//!
+//! Packets of Streams version 2 carry no attachments and no key sequence: their
+//! `attachments_count`, `attachment` and `key_sequence` fields are absent.
+//!
+//! Packets of Streams version 1 are signed by the author of the stream, who is not identified in
+//! the packet, and only mask the bytes of their masked payload:
+//!
+//! ```ddml
+//! message SignedPacket {
+//!     join(spongos);
+//!     absorb              uint    public_size;
+//!     absorb              u8      public_payload[public_size];
+//!     absorb              uint    masked_size;
+//!     mask                u8      masked_payload[masked_size];
+//!     commit;
+//!     squeeze external    u8      hash[32];
+//!     ed25519(hash)       u8      signature[64];
+//! }
+//! ```
 // Rust
 use alloc::{boxed::Box, vec::Vec};
 
@@ -26,19 +53,28 @@ use async_trait::async_trait;
 // Streams
 use lets::{
     id::{Identifier, Identity},
-    message::{ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap},
+    message::{
+        ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap, STREAMS_VER,
+        STREAMS_VER_1,
+    },
+    sync::MaybeSend,
 };
 use spongos::{
     ddml::{
-        commands::{sizeof, unwrap, wrap, Absorb, Join, Mask},
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Ed25519, Join, Mask, Squeeze},
         io,
-        types::Bytes,
+        modifiers::External,
+        types::{Bytes, NBytes, Size},
     },
     error::Result,
     Spongos,
 };
 
 // Local
+use crate::message::attachment::Attachment;
+
+/// Size of the hash signed by packets of Streams version 1
+const V1_HASH_SIZE: usize = 32;
 
 /// A struct that holds references needed for signed packet message encoding
 pub(crate) struct Wrap<'a> {
@@ -48,6 +84,12 @@ pub(crate) struct Wrap<'a> {
     public_payload: &'a [u8],
     /// Payload slice that will be masked
     masked_payload: &'a [u8],
+    /// Attachments that will be masked
+    attachments: &'a [Attachment],
+    /// Number of packets published under the key of the branch before the message
+    key_sequence: usize,
+    /// The Streams version of the message
+    version: u8,
     /// The [`Identity`] of the publisher
     user_id: &'a Identity,
 }
@@ -60,45 +102,69 @@ impl<'a> Wrap<'a> {
     /// * `user_id`: The [`Identity`] of the publishing user.
     /// * `public_payload`: A payload that will not be masked.
     /// * `masked_payload`: A payload taht will be masked.
+    /// * `attachments`: Attachments that will be masked.
+    /// * `key_sequence`: Number of packets published under the key of the branch before this one.
+    /// * `version`: The Streams version of the message. Messages of
+    ///   [`lets::message::STREAMS_VER_2`] carry neither the attachments nor the key sequence.
     pub(crate) fn new(
         initial_state: &'a mut Spongos,
         user_id: &'a Identity,
         public_payload: &'a [u8],
         masked_payload: &'a [u8],
+        attachments: &'a [Attachment],
+        key_sequence: usize,
+        version: u8,
     ) -> Self {
         Self {
             initial_state,
             user_id,
             public_payload,
             masked_payload,
+            attachments,
+            key_sequence,
+            version,
         }
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
     async fn sizeof(&mut self, signed_packet: &Wrap<'a>) -> Result<&mut Self> {
         self.mask(signed_packet.user_id.identifier())?
             .absorb(Bytes::new(signed_packet.public_payload))?
-            .mask(Bytes::new(signed_packet.masked_payload))?
-            .sign_sizeof(signed_packet.user_id)
-            .await?;
+            .mask(Bytes::new(signed_packet.masked_payload))?;
+        if signed_packet.version == STREAMS_VER {
+            self.mask(Size::new(signed_packet.attachments.len()))?;
+            for attachment in signed_packet.attachments {
+                self.mask(attachment)?;
+            }
+            self.mask(Size::new(signed_packet.key_sequence))?;
+        }
+        self.sign_sizeof(signed_packet.user_id).await?;
         Ok(self)
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
 where
-    OS: io::OStream,
+    OS: io::OStream + MaybeSend,
 {
     async fn wrap(&mut self, signed_packet: &mut Wrap<'a>) -> Result<&mut Self> {
         self.join(signed_packet.initial_state)?
             .mask(signed_packet.user_id.identifier())?
             .absorb(Bytes::new(signed_packet.public_payload))?
-            .mask(Bytes::new(signed_packet.masked_payload))?
-            .sign(signed_packet.user_id)
-            .await?;
+            .mask(Bytes::new(signed_packet.masked_payload))?;
+        if signed_packet.version == STREAMS_VER {
+            self.mask(Size::new(signed_packet.attachments.len()))?;
+            for attachment in signed_packet.attachments {
+                self.mask(attachment)?;
+            }
+            self.mask(Size::new(signed_packet.key_sequence))?;
+        }
+        self.sign(signed_packet.user_id).await?;
         Ok(self)
     }
 }
@@ -112,6 +178,12 @@ pub(crate) struct Unwrap<'a> {
     public_payload: Vec<u8>,
     /// A payload that was masked
     masked_payload: Vec<u8>,
+    /// Attachments that were masked
+    attachments: Vec<Attachment>,
+    /// Number of packets published under the key of the branch before the message, if declared
+    key_sequence: Option<usize>,
+    /// The Streams version of the message
+    version: u8,
     /// The [`Identifier`] of the publisher
     publisher_id: Identifier,
 }
@@ -121,15 +193,29 @@ impl<'a> Unwrap<'a> {
     ///
     /// # Arguments
     /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
-    pub(crate) fn new(initial_state: &'a mut Spongos) -> Self {
+    /// * `version`: The Streams version of the message, as found in its header
+    pub(crate) fn new(initial_state: &'a mut Spongos, version: u8) -> Self {
         Self {
             initial_state,
             public_payload: Default::default(),
             masked_payload: Default::default(),
+            attachments: Default::default(),
+            key_sequence: None,
+            version,
             publisher_id: Identifier::default(),
         }
     }
 
+    /// Sets the [`Identifier`] of the publisher, which packets of [`STREAMS_VER_1`] are verified
+    /// against as they do not carry it
+    ///
+    /// # Arguments
+    /// * `publisher_id`: The [`Identifier`] of the author of the stream
+    pub(crate) fn with_publisher_identifier(mut self, publisher_id: Identifier) -> Self {
+        self.publisher_id = publisher_id;
+        self
+    }
+
     /// Consumes the [`Unwrap`], returning the [`Identifier`] of the publisher
     pub(crate) fn into_publisher_identifier(self) -> Identifier {
         self.publisher_id
@@ -144,20 +230,60 @@ impl<'a> Unwrap<'a> {
     pub(crate) fn take_public_payload(&mut self) -> Vec<u8> {
         core::mem::take(&mut self.public_payload)
     }
+
+    /// Takes the attachments from the [`Unwrap`]
+    pub(crate) fn take_attachments(&mut self) -> Vec<Attachment> {
+        core::mem::take(&mut self.attachments)
+    }
+
+    /// Returns the number of packets published under the key of the branch before the message, if
+    /// declared by its version
+    pub(crate) fn key_sequence(&self) -> Option<usize> {
+        self.key_sequence
+    }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
 where
-    IS: io::IStream,
+    IS: io::IStream + MaybeSend,
 {
     async fn unwrap(&mut self, signed_packet: &mut Unwrap) -> Result<&mut Self> {
+        if signed_packet.version == STREAMS_VER_1 {
+            let mut masked_size = Size::default();
+            let mut hash = External::new(NBytes::new([0; V1_HASH_SIZE]));
+            self.join(signed_packet.initial_state)?
+                .absorb(Bytes::new(&mut signed_packet.public_payload))?
+                .absorb(&mut masked_size)?;
+            signed_packet.masked_payload.resize(masked_size.inner(), 0);
+            self.mask(NBytes::new(&mut signed_packet.masked_payload))?
+                .commit()?
+                .squeeze(hash.as_mut())?;
+            match &signed_packet.publisher_id {
+                Identifier::Ed25519(public_key) => self.ed25519(public_key, hash.as_ref())?,
+                #[cfg(feature = "did")]
+                Identifier::DID(_) => return Err(spongos::error::Error::SignatureMismatch),
+            };
+            return Ok(self);
+        }
         self.join(signed_packet.initial_state)?
             .mask(&mut signed_packet.publisher_id)?
             .absorb(Bytes::new(&mut signed_packet.public_payload))?
-            .mask(Bytes::new(&mut signed_packet.masked_payload))?
-            .verify(&signed_packet.publisher_id)
-            .await?;
+            .mask(Bytes::new(&mut signed_packet.masked_payload))?;
+        if signed_packet.version == STREAMS_VER {
+            let mut attachments_count = Size::default();
+            self.mask(&mut attachments_count)?;
+            for _ in 0..attachments_count.inner() {
+                let mut attachment = Attachment::default();
+                self.mask(&mut attachment)?;
+                signed_packet.attachments.push(attachment);
+            }
+            let mut key_sequence = Size::default();
+            self.mask(&mut key_sequence)?;
+            signed_packet.key_sequence = Some(key_sequence.inner());
+        }
+        self.verify(&signed_packet.publisher_id).await?;
         Ok(self)
     }
 }
@@ -1,5 +1,5 @@
-//! `TaggedPacket` message content. The message has a plain and masked payload and is authenticated
-//! with MAC.
+//! `TaggedPacket` message content. The message has a plain and masked payload, as well as masked
+//! attachments, and is authenticated with MAC.
 //!
 //! The message may be linked to any other message in the channel and can be published by any
 //! participant in a channel.
@@ -9,6 +9,30 @@
 //!     join(spongos);
 //!     absorb bytes public_payload;
 //!     mask bytes masked_payload;
+//!     mask size attachments_count;
+//!     repeated(attachments_count):
+//!       mask Attachment attachment;
+//!     mask size key_sequence;
+//!     commit;
+//!     squeeze byte mac[32];
+//! }
+//! ```
+//!
+//! The `key_sequence` counts the packets published in the branch under its current key before this
+//! one, so that readers can enforce the packet limit of the branch from authenticated data.
+//!
+//! Packets of Streams version 2 carry no attachments and no key sequence: their
+//! `attachments_count`, `attachment` and `key_sequence` fields are absent.
+//!
+//! Packets of Streams version 1 carry no attachments and no key sequence either, and only mask
+//! the bytes of their masked payload, whose size is absorbed:
+//!
+//! ```ddml
+//! message TaggedPacket {
+//!     join(spongos);
+//!     absorb bytes public_payload;
+//!     absorb size masked_size;
+//!     mask byte masked_payload[masked_size];
 //!     commit;
 //!     squeeze byte mac[32];
 //! }
@@ -22,18 +46,22 @@ use async_trait::async_trait;
 // IOTA
 
 // Streams
-use lets::message::{ContentSizeof, ContentUnwrap, ContentWrap};
+use lets::{
+    message::{ContentSizeof, ContentUnwrap, ContentWrap, STREAMS_VER, STREAMS_VER_1},
+    sync::MaybeSend,
+};
 use spongos::{
     ddml::{
         commands::{sizeof, unwrap, wrap, Absorb, Commit, Join, Mask, Squeeze},
         io,
-        types::{Bytes, Mac},
+        types::{Bytes, Mac, NBytes, Size},
     },
     error::Result,
     Spongos,
 };
 
 // Local
+use crate::message::attachment::Attachment;
 
 /// [`Mac`] for content verification
 const MAC: Mac = Mac::new(32);
@@ -46,6 +74,12 @@ pub(crate) struct Wrap<'a> {
     public_payload: &'a [u8],
     /// Payload slice that will be masked
     masked_payload: &'a [u8],
+    /// Attachments that will be masked
+    attachments: &'a [Attachment],
+    /// Number of packets published under the key of the branch before the message
+    key_sequence: usize,
+    /// The Streams version of the message
+    version: u8,
 }
 
 impl<'a> Wrap<'a> {
@@ -55,37 +89,65 @@ impl<'a> Wrap<'a> {
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
     /// * `public_payload`: A payload that will not be masked.
     /// * `masked_payload`: A payload taht will be masked.
-    pub(crate) fn new(initial_state: &'a mut Spongos, public_payload: &'a [u8], masked_payload: &'a [u8]) -> Self {
+    /// * `attachments`: Attachments that will be masked.
+    /// * `key_sequence`: Number of packets published under the key of the branch before this one.
+    /// * `version`: The Streams version of the message. Messages of
+    ///   [`lets::message::STREAMS_VER_2`] carry neither the attachments nor the key sequence.
+    pub(crate) fn new(
+        initial_state: &'a mut Spongos,
+        public_payload: &'a [u8],
+        masked_payload: &'a [u8],
+        attachments: &'a [Attachment],
+        key_sequence: usize,
+        version: u8,
+    ) -> Self {
         Self {
             initial_state,
             public_payload,
             masked_payload,
+            attachments,
+            key_sequence,
+            version,
         }
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
     async fn sizeof(&mut self, tagged_packet: &Wrap<'a>) -> Result<&mut Self> {
         self.absorb(Bytes::new(tagged_packet.public_payload))?
-            .mask(Bytes::new(tagged_packet.masked_payload))?
-            .commit()?
-            .squeeze(&MAC)?;
+            .mask(Bytes::new(tagged_packet.masked_payload))?;
+        if tagged_packet.version == STREAMS_VER {
+            self.mask(Size::new(tagged_packet.attachments.len()))?;
+            for attachment in tagged_packet.attachments {
+                self.mask(attachment)?;
+            }
+            self.mask(Size::new(tagged_packet.key_sequence))?;
+        }
+        self.commit()?.squeeze(&MAC)?;
         Ok(self)
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
 where
-    OS: io::OStream,
+    OS: io::OStream + MaybeSend,
 {
     async fn wrap(&mut self, tagged_packet: &mut Wrap<'a>) -> Result<&mut Self> {
         self.join(tagged_packet.initial_state)?
             .absorb(Bytes::new(tagged_packet.public_payload))?
-            .mask(Bytes::new(tagged_packet.masked_payload))?
-            .commit()?
-            .squeeze(&MAC)?;
+            .mask(Bytes::new(tagged_packet.masked_payload))?;
+        if tagged_packet.version == STREAMS_VER {
+            self.mask(Size::new(tagged_packet.attachments.len()))?;
+            for attachment in tagged_packet.attachments {
+                self.mask(attachment)?;
+            }
+            self.mask(Size::new(tagged_packet.key_sequence))?;
+        }
+        self.commit()?.squeeze(&MAC)?;
         Ok(self)
     }
 }
@@ -98,6 +160,12 @@ pub(crate) struct Unwrap<'a> {
     public_payload: Vec<u8>,
     /// A payload that was masked
     masked_payload: Vec<u8>,
+    /// Attachments that were masked
+    attachments: Vec<Attachment>,
+    /// Number of packets published under the key of the branch before the message, if declared
+    key_sequence: Option<usize>,
+    /// The Streams version of the message
+    version: u8,
 }
 
 impl<'a> Unwrap<'a> {
@@ -105,11 +173,15 @@ impl<'a> Unwrap<'a> {
     ///
     /// # Arguments
     /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
-    pub(crate) fn new(initial_state: &'a mut Spongos) -> Self {
+    /// * `version`: The Streams version of the message, as found in its header
+    pub(crate) fn new(initial_state: &'a mut Spongos, version: u8) -> Self {
         Self {
             initial_state,
             public_payload: Default::default(),
             masked_payload: Default::default(),
+            attachments: Default::default(),
+            key_sequence: None,
+            version,
         }
     }
 
@@ -122,19 +194,49 @@ impl<'a> Unwrap<'a> {
     pub(crate) fn take_public_payload(&mut self) -> Vec<u8> {
         core::mem::take(&mut self.public_payload)
     }
+
+    /// Takes the attachments from the [`Unwrap`]
+    pub(crate) fn take_attachments(&mut self) -> Vec<Attachment> {
+        core::mem::take(&mut self.attachments)
+    }
+
+    /// Returns the number of packets published under the key of the branch before the message, if
+    /// declared by its version
+    pub(crate) fn key_sequence(&self) -> Option<usize> {
+        self.key_sequence
+    }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
 where
-    IS: io::IStream,
+    IS: io::IStream + MaybeSend,
 {
     async fn unwrap(&mut self, tagged_packet: &mut Unwrap<'a>) -> Result<&mut Self> {
         self.join(tagged_packet.initial_state)?
-            .absorb(Bytes::new(&mut tagged_packet.public_payload))?
-            .mask(Bytes::new(&mut tagged_packet.masked_payload))?
-            .commit()?
-            .squeeze(&MAC)?;
+            .absorb(Bytes::new(&mut tagged_packet.public_payload))?;
+        if tagged_packet.version == STREAMS_VER_1 {
+            let mut masked_size = Size::default();
+            self.absorb(&mut masked_size)?;
+            tagged_packet.masked_payload.resize(masked_size.inner(), 0);
+            self.mask(NBytes::new(&mut tagged_packet.masked_payload))?;
+        } else {
+            self.mask(Bytes::new(&mut tagged_packet.masked_payload))?;
+        }
+        if tagged_packet.version == STREAMS_VER {
+            let mut attachments_count = Size::default();
+            self.mask(&mut attachments_count)?;
+            for _ in 0..attachments_count.inner() {
+                let mut attachment = Attachment::default();
+                self.mask(&mut attachment)?;
+                tagged_packet.attachments.push(attachment);
+            }
+            let mut key_sequence = Size::default();
+            self.mask(&mut key_sequence)?;
+            tagged_packet.key_sequence = Some(key_sequence.inner());
+        }
+        self.commit()?.squeeze(&MAC)?;
         Ok(self)
     }
 }